@@ -0,0 +1,350 @@
+use anyhow::{ensure, Context as _, Result};
+use passes_client::{
+    accounts,
+    instructions::{self, MarketOptions},
+    passes::state::{Config, IssuanceParams, PassesSupply, RewardPool},
+    quote::{self, Currency, Quote},
+};
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Signature, Signer},
+};
+
+use crate::{read_keypair, Command, Context, Inspect, Trade};
+
+/// Run a command, returns what to print
+pub fn run(ctx: &Context, command: Command) -> Result<String> {
+    let program_id = &ctx.program_id;
+    let marketplace_id = ctx.marketplace_id;
+    let signer = ctx.payer.pubkey();
+    match command {
+        Command::Init {
+            mint,
+            authority,
+            protocol_fee_bps,
+            owner_fee_bps,
+        } => {
+            let authority = authority.as_deref().map(read_keypair).transpose()?;
+            let ix = instructions::init(
+                program_id,
+                marketplace_id,
+                &signer,
+                &authority
+                    .as_ref()
+                    .map_or(signer, |authority| authority.pubkey()),
+                &mint,
+                protocol_fee_bps,
+                owner_fee_bps,
+            );
+            sent(ctx.send_with(&[ix], &authority.iter().collect::<Vec<_>>())?)
+        }
+        Command::Fees {
+            protocol_fee_bps,
+            owner_fee_bps,
+        } => {
+            let ixs = fee_instructions(
+                program_id,
+                marketplace_id,
+                &signer,
+                protocol_fee_bps,
+                owner_fee_bps,
+            );
+            ensure!(
+                !ixs.is_empty(),
+                "nothing to set, pass --protocol-fee-bps and/or --owner-fee-bps"
+            );
+            sent(ctx.send(&ixs)?)
+        }
+        Command::FeeDst { token, sol } => {
+            let ixs = fee_dst_instructions(program_id, marketplace_id, &signer, token, sol);
+            ensure!(!ixs.is_empty(), "nothing to set, pass --token and/or --sol");
+            sent(ctx.send(&ixs)?)
+        }
+        Command::IsolateReserve { owner, proposal } => {
+            let market = Market::fetch(ctx, &owner)?;
+            ensure!(
+                !market.passes_supply.isolated_reserve,
+                "the reserve of {} is already isolated",
+                owner
+            );
+            ensure!(
+                market.passes_supply.stats_complete,
+                "the trade stats of {} don't cover its whole history, its reserve can't be isolated",
+                owner
+            );
+            let ix = instructions::isolate_reserve(
+                program_id,
+                marketplace_id,
+                &signer,
+                &owner,
+                &market.config.payment_mint,
+                proposal,
+            );
+            sent(ctx.send(&[ix])?)
+        }
+        Command::Issue {
+            amount,
+            max_supply,
+            launch_ts,
+            price_offset,
+            vesting_duration,
+            isolated_reserve,
+        } => {
+            let config = accounts::fetch_config(&ctx.rpc, program_id, marketplace_id)?;
+            let params = IssuanceParams {
+                max_supply,
+                launch_ts,
+                price_offset,
+                vesting_duration,
+                isolated_reserve,
+                ..Default::default()
+            };
+            let ix = instructions::issue_passes(
+                program_id,
+                marketplace_id,
+                &signer,
+                &config.payment_mint,
+                amount,
+                params,
+            );
+            sent(ctx.send(&[ix])?)
+        }
+        Command::Buy { trade, holder_page } => {
+            let market = Market::fetch(ctx, &trade.owner)?;
+            let quote = market.quote(ctx, &trade, false)?;
+            let ix = trade_instruction(program_id, &signer, &market, &trade, holder_page, false);
+            Ok(format!(
+                "{}\n{}",
+                format_quote(&quote, trade.currency()),
+                sent(ctx.send(&[ix])?)?
+            ))
+        }
+        Command::Sell(trade) => {
+            let market = Market::fetch(ctx, &trade.owner)?;
+            let quote = market.quote(ctx, &trade, true)?;
+            let ix = trade_instruction(program_id, &signer, &market, &trade, None, true);
+            Ok(format!(
+                "{}\n{}",
+                format_quote(&quote, trade.currency()),
+                sent(ctx.send(&[ix])?)?
+            ))
+        }
+        Command::Quote { trade, sell } => {
+            let market = Market::fetch(ctx, &trade.owner)?;
+            let quote = market.quote(ctx, &trade, sell)?;
+            Ok(format_quote(&quote, trade.currency()))
+        }
+        Command::Inspect(Inspect::Config) => {
+            let config = accounts::fetch_config(&ctx.rpc, program_id, marketplace_id)?;
+            Ok(format!("{:#?}", config))
+        }
+        Command::Inspect(Inspect::Market { owner }) => {
+            let market = Market::fetch(ctx, &owner)?;
+            let metadata =
+                accounts::fetch_passes_metadata(&ctx.rpc, program_id, marketplace_id, &owner)?;
+            let mut output = format!("{:#?}", market.passes_supply);
+            if let Some(metadata) = metadata {
+                output += &format!("\n{:#?}", metadata);
+            }
+            if let Some(reward_pool) = market.reward_pool {
+                output += &format!("\n{:#?}", reward_pool);
+            }
+            Ok(output)
+        }
+        Command::Inspect(Inspect::Balance { owner, holder }) => {
+            let holder = holder.unwrap_or(signer);
+            match accounts::fetch_passes_balance(
+                &ctx.rpc,
+                program_id,
+                marketplace_id,
+                &owner,
+                &holder,
+            )? {
+                Some(passes_balance) => Ok(format!("{:#?}", passes_balance)),
+                None => Ok(format!("{} holds no passes of {}", holder, owner)),
+            }
+        }
+    }
+}
+
+fn sent(signature: Signature) -> Result<String> {
+    Ok(format!("Signature: {}", signature))
+}
+
+/// State of a market needed to quote and build its trades
+pub struct Market {
+    pub config: Config,
+    pub passes_supply: PassesSupply,
+    pub reward_pool: Option<RewardPool>,
+}
+
+impl Market {
+    fn fetch(ctx: &Context, owner: &Pubkey) -> Result<Self> {
+        let marketplace_id = ctx.marketplace_id;
+        let config = accounts::fetch_config(&ctx.rpc, &ctx.program_id, marketplace_id)?;
+        let passes_supply =
+            accounts::fetch_passes_supply(&ctx.rpc, &ctx.program_id, marketplace_id, owner)?
+                .with_context(|| format!("{} has not issued passes", owner))?;
+        let reward_pool = if passes_supply.rewards {
+            accounts::fetch_reward_pool(&ctx.rpc, &ctx.program_id, marketplace_id, owner)?
+        } else {
+            None
+        };
+        Ok(Self {
+            config,
+            passes_supply,
+            reward_pool,
+        })
+    }
+
+    fn quote(&self, ctx: &Context, trade: &Trade, sell: bool) -> Result<Quote> {
+        if !sell {
+            return Ok(quote::quote_buy(
+                &self.config,
+                &self.passes_supply,
+                self.reward_pool.as_ref(),
+                trade.currency(),
+                trade.amount,
+            )?);
+        }
+        let seller = ctx.payer.pubkey();
+        let passes_balance = accounts::fetch_passes_balance(
+            &ctx.rpc,
+            &ctx.program_id,
+            ctx.marketplace_id,
+            &trade.owner,
+            &seller,
+        )?
+        .with_context(|| format!("{} holds no passes of {}", seller, trade.owner))?;
+        let seller_is_owner = seller == trade.owner;
+        let owner_vesting = if seller_is_owner && self.passes_supply.vesting {
+            accounts::fetch_owner_vesting(
+                &ctx.rpc,
+                &ctx.program_id,
+                ctx.marketplace_id,
+                &trade.owner,
+            )?
+        } else {
+            None
+        };
+        let now = ctx.rpc.get_block_time(ctx.rpc.get_slot()?)?;
+        Ok(quote::quote_sell(
+            &self.config,
+            &self.passes_supply,
+            self.reward_pool.as_ref(),
+            &passes_balance,
+            seller_is_owner,
+            owner_vesting.as_ref(),
+            trade.currency(),
+            trade.amount,
+            now,
+        )?)
+    }
+}
+
+pub fn fee_instructions(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    admin: &Pubkey,
+    protocol_fee_bps: Option<u64>,
+    owner_fee_bps: Option<u64>,
+) -> Vec<Instruction> {
+    let protocol = protocol_fee_bps.map(|fee_bps| {
+        instructions::set_protocol_fee_bps(program_id, marketplace_id, admin, fee_bps)
+    });
+    let owner = owner_fee_bps
+        .map(|fee_bps| instructions::set_owner_fee_bps(program_id, marketplace_id, admin, fee_bps));
+    protocol.into_iter().chain(owner).collect()
+}
+
+pub fn fee_dst_instructions(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    admin: &Pubkey,
+    token: Option<Pubkey>,
+    sol: Option<Pubkey>,
+) -> Vec<Instruction> {
+    let token = token.map(|wallet| {
+        instructions::set_protocol_fee_dst(program_id, marketplace_id, admin, &wallet)
+    });
+    let sol = sol.map(|wallet| {
+        instructions::set_protocol_fee_sol_dst(program_id, marketplace_id, admin, &wallet)
+    });
+    token.into_iter().chain(sol).collect()
+}
+
+/// `holder_page` is the page a buy credits instead of the signer's balance
+pub fn trade_instruction(
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    market: &Market,
+    trade: &Trade,
+    holder_page: Option<u32>,
+    sell: bool,
+) -> Instruction {
+    let config = &market.config;
+    let marketplace_id = config.marketplace_id;
+    let options = MarketOptions::from(&market.passes_supply);
+    match (trade.currency(), sell) {
+        (Currency::Token, false) => instructions::buy_passes(
+            program_id,
+            marketplace_id,
+            signer,
+            &trade.owner,
+            &config.payment_mint,
+            &config.protocol_fee_token_wallet,
+            trade.amount,
+            vec![],
+            holder_page,
+            options,
+        ),
+        (Currency::Sol, false) => instructions::buy_passes_sol(
+            program_id,
+            marketplace_id,
+            signer,
+            &trade.owner,
+            &config.protocol_fee_sol_wallet,
+            trade.amount,
+            vec![],
+            holder_page,
+            options,
+        ),
+        (Currency::Token, true) => instructions::sell_passes(
+            program_id,
+            marketplace_id,
+            signer,
+            &trade.owner,
+            &config.payment_mint,
+            &config.protocol_fee_token_wallet,
+            trade.amount,
+            options,
+        ),
+        (Currency::Sol, true) => instructions::sell_passes_sol(
+            program_id,
+            marketplace_id,
+            signer,
+            &trade.owner,
+            &config.protocol_fee_sol_wallet,
+            trade.amount,
+            options,
+        ),
+    }
+}
+
+pub fn format_quote(quote: &Quote, currency: Currency) -> String {
+    let unit = match currency {
+        Currency::Token => "token base units",
+        Currency::Sol => "lamports",
+    };
+    format!(
+        "Price: {}\nProtocol fees: {}\nOwner fees: {}\nHolder fees: {}\nEarly exit fees: {}\nTotal: {} ({})",
+        quote.price,
+        quote.protocol_fees,
+        quote.owner_fees,
+        quote.holder_fees,
+        quote.early_exit_fees,
+        quote.total,
+        unit
+    )
+}
@@ -0,0 +1,214 @@
+//! Operator CLI for the passes program
+
+mod commands;
+#[cfg(test)]
+mod tests;
+
+use anyhow::{Context as _, Result};
+use clap::{Parser, Subcommand};
+use passes_client::quote::Currency;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+
+#[derive(Debug, Parser)]
+#[clap(name = "passes", about = "Operate the passes program")]
+pub struct Cli {
+    /// RPC endpoint
+    #[clap(long, short, global = true, default_value = "http://127.0.0.1:8899")]
+    pub url: String,
+    /// Keypair that signs and pays for transactions
+    #[clap(long, short, global = true, default_value = "~/.config/solana/id.json")]
+    pub keypair: String,
+    /// Program id of the deployment
+    #[clap(long, global = true, default_value_t = passes_client::passes::ID)]
+    pub program_id: Pubkey,
+    /// Marketplace of the deployment, 0 is the default marketplace
+    #[clap(long, short, global = true, default_value_t = 0)]
+    pub marketplace: u64,
+    #[clap(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Initialize the marketplace config and escrows, the signer becomes its admin
+    Init {
+        /// Mint of the payment token
+        #[clap(long)]
+        mint: Pubkey,
+        /// Keypair of the upgrade authority approving the marketplace, the signer by default
+        #[clap(long)]
+        authority: Option<String>,
+        #[clap(long, default_value_t = 100)]
+        protocol_fee_bps: u64,
+        #[clap(long, default_value_t = 100)]
+        owner_fee_bps: u64,
+    },
+    /// Set the protocol and/or owner fee
+    Fees {
+        #[clap(long)]
+        protocol_fee_bps: Option<u64>,
+        #[clap(long)]
+        owner_fee_bps: Option<u64>,
+    },
+    /// Set the protocol fee destinations
+    FeeDst {
+        /// Token account receiving the token protocol fees
+        #[clap(long)]
+        token: Option<Pubkey>,
+        /// Account receiving the SOL protocol fees
+        #[clap(long)]
+        sol: Option<Pubkey>,
+    },
+    /// Move a market's reserve out of the marketplace escrows into its own wallets
+    IsolateReserve {
+        /// Owner of the market
+        #[clap(long)]
+        owner: Pubkey,
+        /// Id of the executed proposal to isolate the market, required by a multisig config
+        #[clap(long)]
+        proposal: Option<u64>,
+    },
+    /// Issue the first passes of the signer's market
+    Issue {
+        #[clap(long, default_value_t = 1)]
+        amount: u64,
+        /// Supply cap, 0 means no cap
+        #[clap(long, default_value_t = 0)]
+        max_supply: u64,
+        /// Unix timestamp before which buys fail
+        #[clap(long, default_value_t = 0)]
+        launch_ts: i64,
+        /// Supply added to the curve position
+        #[clap(long, default_value_t = 0)]
+        price_offset: u64,
+        /// Seconds over which the issued passes vest, 0 means no vesting
+        #[clap(long, default_value_t = 0)]
+        vesting_duration: i64,
+        /// Keep the market's reserve in its own wallets instead of the marketplace escrows
+        #[clap(long)]
+        isolated_reserve: bool,
+    },
+    /// Buy passes of an owner's market
+    Buy {
+        #[clap(flatten)]
+        trade: Trade,
+        /// Credit the passes to this holder page instead of the signer's balance
+        #[clap(long)]
+        holder_page: Option<u32>,
+    },
+    /// Sell passes of an owner's market
+    Sell(Trade),
+    /// Quote a buy or sell without sending it
+    Quote {
+        #[clap(flatten)]
+        trade: Trade,
+        /// Quote a sell of the signer's passes instead of a buy
+        #[clap(long)]
+        sell: bool,
+    },
+    /// Print program accounts
+    #[clap(subcommand)]
+    Inspect(Inspect),
+}
+
+#[derive(Debug, clap::Args)]
+pub struct Trade {
+    /// Owner of the market
+    #[clap(long)]
+    pub owner: Pubkey,
+    #[clap(long)]
+    pub amount: u64,
+    /// Trade in SOL instead of the payment token
+    #[clap(long)]
+    pub sol: bool,
+}
+
+impl Trade {
+    pub fn currency(&self) -> Currency {
+        if self.sol {
+            Currency::Sol
+        } else {
+            Currency::Token
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Inspect {
+    /// The marketplace config
+    Config,
+    /// A market's supply, settings and metadata
+    Market { owner: Pubkey },
+    /// A holder's balance in a market, the signer by default
+    Balance {
+        owner: Pubkey,
+        holder: Option<Pubkey>,
+    },
+}
+
+/// RPC connection, signer, program and marketplace the commands run against
+pub struct Context {
+    pub rpc: RpcClient,
+    pub payer: Keypair,
+    pub program_id: Pubkey,
+    pub marketplace_id: u64,
+}
+
+impl Context {
+    pub fn new(cli: &Cli) -> Result<Self> {
+        Ok(Self {
+            rpc: RpcClient::new_with_commitment(cli.url.clone(), CommitmentConfig::confirmed()),
+            payer: read_keypair(&cli.keypair)?,
+            program_id: cli.program_id,
+            marketplace_id: cli.marketplace,
+        })
+    }
+
+    pub fn send(&self, ixs: &[Instruction]) -> Result<Signature> {
+        self.send_with(ixs, &[])
+    }
+
+    /// Send signed by the payer and `signers`
+    pub fn send_with(&self, ixs: &[Instruction], signers: &[&Keypair]) -> Result<Signature> {
+        let blockhash = self.rpc.get_latest_blockhash()?;
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend(signers);
+        let tx = Transaction::new_signed_with_payer(
+            ixs,
+            Some(&self.payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+        self.rpc
+            .send_and_confirm_transaction(&tx)
+            .context("transaction failed")
+    }
+}
+
+pub fn read_keypair(path: &str) -> Result<Keypair> {
+    let path = expand_home(path);
+    read_keypair_file(&path)
+        .map_err(|err| anyhow::anyhow!("failed to read keypair {}: {}", path, err))
+}
+
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{}/{}", home, rest),
+        _ => path.to_string(),
+    }
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let ctx = Context::new(&cli)?;
+    let output = commands::run(&ctx, cli.command)?;
+    println!("{}", output);
+    Ok(())
+}
@@ -0,0 +1,424 @@
+use anchor_lang::{AccountDeserialize, Discriminator};
+use anchor_spl::{
+    associated_token::get_associated_token_address,
+    token::spl_token::{
+        self,
+        solana_program::{program_option::COption, program_pack::Pack},
+    },
+};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use clap::Parser;
+use passes_client::{
+    accounts::{self, decode},
+    passes::{
+        state::{Config, PassesSupply},
+        ONE_SOL, ONE_USDC,
+    },
+    pda,
+    quote::{Currency, Quote},
+};
+use passes_test_utils::add_upgradeable_program;
+use serde_json::{json, Value};
+use solana_client::{
+    client_error,
+    rpc_client::{RpcClient, RpcClientConfig},
+    rpc_request::RpcRequest,
+    rpc_sender::{RpcSender, RpcTransportStats},
+};
+use solana_program_test::{tokio, BanksClient, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    clock::Clock,
+    commitment_config::CommitmentConfig,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+use crate::{
+    commands::{
+        fee_dst_instructions, fee_instructions, format_quote, run, trade_instruction, Market,
+    },
+    Cli, Command, Context, Inspect, Trade,
+};
+
+fn zeroed<T: AccountDeserialize + Discriminator>(len: usize) -> T {
+    let mut data = vec![0; len];
+    data[..8].copy_from_slice(&T::DISCRIMINATOR);
+    decode(&Pubkey::default(), &data).unwrap()
+}
+
+fn parse(args: &[&str]) -> Cli {
+    Cli::try_parse_from(std::iter::once("passes").chain(args.iter().copied())).unwrap()
+}
+
+#[test]
+fn test_parse() {
+    let owner = Pubkey::new_unique();
+    let program_id = Pubkey::new_unique();
+
+    let cli = parse(&["inspect", "config"]);
+    assert_eq!(cli.url, "http://127.0.0.1:8899");
+    assert_eq!(cli.program_id, passes_client::passes::ID);
+    assert_eq!(cli.marketplace, 0);
+    assert!(matches!(cli.command, Command::Inspect(Inspect::Config)));
+
+    let cli = parse(&["inspect", "config", "--marketplace", "2"]);
+    assert_eq!(cli.marketplace, 2);
+
+    let cli = parse(&[
+        "buy",
+        "--owner",
+        &owner.to_string(),
+        "--amount",
+        "3",
+        "--sol",
+        "--holder-page",
+        "1",
+        "--url",
+        "https://api.devnet.solana.com",
+        "--program-id",
+        &program_id.to_string(),
+    ]);
+    assert_eq!(cli.url, "https://api.devnet.solana.com");
+    assert_eq!(cli.program_id, program_id);
+    match cli.command {
+        Command::Buy { trade, holder_page } => {
+            assert_eq!(trade.owner, owner);
+            assert_eq!(trade.amount, 3);
+            assert_eq!(trade.currency(), Currency::Sol);
+            assert_eq!(holder_page, Some(1));
+        }
+        command => panic!("unexpected command {:?}", command),
+    }
+
+    let cli = parse(&[
+        "quote",
+        "--owner",
+        &owner.to_string(),
+        "--amount",
+        "1",
+        "--sell",
+    ]);
+    assert!(matches!(cli.command, Command::Quote { sell: true, .. }));
+
+    assert!(
+        Cli::try_parse_from(["passes", "buy", "--owner", "not-a-key", "--amount", "1"]).is_err()
+    );
+    assert!(Cli::try_parse_from(["passes", "sell", "--owner", &owner.to_string()]).is_err());
+
+    let cli = parse(&["issue", "--isolated-reserve"]);
+    assert!(matches!(
+        cli.command,
+        Command::Issue {
+            isolated_reserve: true,
+            ..
+        }
+    ));
+    let cli = parse(&[
+        "isolate-reserve",
+        "--owner",
+        &owner.to_string(),
+        "--proposal",
+        "3",
+    ]);
+    match cli.command {
+        Command::IsolateReserve {
+            owner: reserve_owner,
+            proposal,
+        } => {
+            assert_eq!(reserve_owner, owner);
+            assert_eq!(proposal, Some(3));
+        }
+        command => panic!("unexpected command {:?}", command),
+    }
+}
+
+#[test]
+fn test_instructions() {
+    let program_id = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+
+    assert!(fee_instructions(&program_id, 0, &admin, None, None).is_empty());
+    let ixs = fee_instructions(&program_id, 2, &admin, Some(50), Some(75));
+    assert_eq!(ixs.len(), 2);
+    assert!(ixs[0]
+        .accounts
+        .iter()
+        .any(|meta| meta.pubkey == pda::config(&program_id, 2)));
+    let wallet = Pubkey::new_unique();
+    let ixs = fee_dst_instructions(&program_id, 0, &admin, None, Some(wallet));
+    assert_eq!(ixs.len(), 1);
+    assert!(ixs[0].accounts.iter().any(|meta| meta.pubkey == wallet));
+
+    // trades use the marketplace of the fetched config
+    let mut config: Config = zeroed(Config::LEN);
+    config.marketplace_id = 2;
+    config.payment_mint = Pubkey::new_unique();
+    config.protocol_fee_token_wallet = Pubkey::new_unique();
+    config.protocol_fee_sol_wallet = Pubkey::new_unique();
+    let mut passes_supply: PassesSupply = zeroed(PassesSupply::LEN);
+    passes_supply.rewards = true;
+    let market = Market {
+        config,
+        passes_supply,
+        reward_pool: None,
+    };
+    let owner = Pubkey::new_unique();
+    let trade = Trade {
+        owner,
+        amount: 2,
+        sol: false,
+    };
+    let ix = trade_instruction(&program_id, &admin, &market, &trade, None, true);
+    let keys: Vec<_> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+    assert!(keys.contains(&market.config.protocol_fee_token_wallet));
+    assert!(keys.contains(&pda::reward_pool(&program_id, 2, &owner)));
+    assert!(keys.contains(&pda::passes_supply(&program_id, 2, &owner)));
+    assert!(!keys.contains(&pda::owner_vesting(&program_id, 2, &owner)));
+
+    assert!(!keys.contains(&pda::reserve_token_wallet(&program_id, 2, &owner)));
+
+    let trade = Trade { sol: true, ..trade };
+    let ix = trade_instruction(&program_id, &admin, &market, &trade, None, false);
+    assert!(ix
+        .accounts
+        .iter()
+        .any(|meta| meta.pubkey == market.config.protocol_fee_sol_wallet));
+
+    // buys into a holder page pass the page instead of the balance
+    let ix = trade_instruction(&program_id, &admin, &market, &trade, Some(3), false);
+    let keys: Vec<_> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+    assert!(keys.contains(&pda::holder_page(&program_id, 2, &owner, 3)));
+    assert!(!keys.contains(&pda::passes_balance(&program_id, 2, &owner, &admin)));
+
+    // isolated markets trade against their reserve wallets
+    let mut market = market;
+    market.passes_supply.isolated_reserve = true;
+    let ix = trade_instruction(&program_id, &admin, &market, &trade, None, true);
+    assert!(ix
+        .accounts
+        .iter()
+        .any(|meta| meta.pubkey == pda::reserve_sol_wallet(&program_id, 2, &owner)));
+}
+
+#[test]
+fn test_format_quote() {
+    let quote = Quote {
+        price: 100,
+        protocol_fees: 1,
+        owner_fees: 1,
+        total: 102,
+        ..Default::default()
+    };
+    let output = format_quote(&quote, Currency::Sol);
+    assert!(output.starts_with("Price: 100\n"));
+    assert!(output.ends_with("Total: 102 (lamports)"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_round_trip() {
+    let payer = Keypair::new();
+    let mint = Pubkey::new_unique();
+    let (ctx, mut banks) = start(&payer, &mint).await;
+    let program_id = ctx.program_id;
+    let owner = payer.pubkey();
+    let wallet = get_associated_token_address(&owner, &mint);
+    let tokens = banks
+        .get_packed_account_data::<spl_token::state::Account>(wallet)
+        .await
+        .unwrap()
+        .amount;
+
+    // the payer deploys, administers, issues and trades, so fees come back to it
+    let (owner_arg, mint_arg) = (owner.to_string(), mint.to_string());
+    let run_args = |args: &[&str]| run(&ctx, parse(args).command);
+    for args in [
+        &["init", "--mint", &mint_arg][..],
+        &["issue", "--amount", "2"],
+        &["buy", "--owner", &owner_arg, "--amount", "3"],
+    ] {
+        let output = run_args(args).unwrap();
+        assert!(output.contains("Signature: "), "{}", output);
+    }
+    let passes_supply = accounts::fetch_passes_supply(&ctx.rpc, &program_id, 0, &owner)
+        .unwrap()
+        .unwrap();
+    assert_eq!(passes_supply.amount, 5);
+    assert_eq!(passes_supply.issued_amount, 2);
+    let output = run_args(&["inspect", "balance", &owner_arg]).unwrap();
+    assert!(output.contains("amount: 5,"), "{}", output);
+
+    // the quote is checked before sending and fails like the program, the last pass and the
+    // issued passes aren't sold
+    let output = run_args(&["quote", "--owner", &owner_arg, "--amount", "3", "--sell"]).unwrap();
+    assert!(output.starts_with(&format!(
+        "Price: {}\n",
+        passes_client::passes::curve_price(2, 3).unwrap()
+    )));
+    let err = run_args(&["sell", "--owner", &owner_arg, "--amount", "5"]).unwrap_err();
+    assert!(err.to_string().contains("LastPass"), "{}", err);
+    let err = run_args(&["sell", "--owner", &owner_arg, "--amount", "4"]).unwrap_err();
+    assert!(err.to_string().contains("IssuedPassesLocked"), "{}", err);
+
+    let output = run_args(&["sell", "--owner", &owner_arg, "--amount", "3"]).unwrap();
+    assert!(output.contains("Signature: "), "{}", output);
+    let passes_balance = accounts::fetch_passes_balance(&ctx.rpc, &program_id, 0, &owner, &owner)
+        .unwrap()
+        .unwrap();
+    assert_eq!(passes_balance.amount, 2);
+
+    // the sell paid back the buy and the escrow is empty again
+    let tokens_after = banks
+        .get_packed_account_data::<spl_token::state::Account>(wallet)
+        .await
+        .unwrap()
+        .amount;
+    assert_eq!(tokens_after, tokens);
+    let escrow = banks
+        .get_packed_account_data::<spl_token::state::Account>(pda::escrow_token_wallet(
+            &program_id,
+            0,
+            &mint,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(escrow.amount, 0);
+
+    // failed transactions fail the command
+    assert!(run_args(&["init", "--mint", &mint_arg]).is_err());
+}
+
+// Start a bank with the program deployed by `payer`, who holds SOL and payment tokens of `mint`
+async fn start(payer: &Keypair, mint: &Pubkey) -> (Context, BanksClient) {
+    let program_id = passes_client::passes::ID;
+    let mut program_test = ProgramTest::default();
+
+    add_upgradeable_program(&mut program_test, "passes", &program_id, &payer.pubkey());
+
+    program_test.add_account(
+        payer.pubkey(),
+        Account {
+            lamports: 100 * ONE_SOL,
+            ..Account::default()
+        },
+    );
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        mint_authority: COption::Some(payer.pubkey()),
+        supply: 1_000 * ONE_USDC,
+        decimals: 6,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    program_test.add_account(*mint, token_account(data));
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint: *mint,
+        owner: payer.pubkey(),
+        amount: 1_000 * ONE_USDC,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+    program_test.add_account(
+        get_associated_token_address(&payer.pubkey(), mint),
+        token_account(data),
+    );
+
+    let (banks, _, _) = program_test.start().await;
+    let ctx = Context {
+        rpc: RpcClient::new_sender(
+            BanksSender(banks.clone()),
+            RpcClientConfig::with_commitment(CommitmentConfig::confirmed()),
+        ),
+        payer: payer.insecure_clone(),
+        program_id,
+        marketplace_id: 0,
+    };
+    (ctx, banks)
+}
+
+fn token_account(data: Vec<u8>) -> Account {
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: spl_token::id(),
+        ..Account::default()
+    }
+}
+
+// Answers the JSON-RPC requests the CLI makes from a program-test bank
+struct BanksSender(BanksClient);
+
+#[async_trait]
+impl RpcSender for BanksSender {
+    async fn send(&self, request: RpcRequest, params: Value) -> client_error::Result<Value> {
+        let mut banks = self.0.clone();
+        let slot = banks.get_root_slot().await.unwrap();
+        let response = |value: Value| json!({ "context": { "slot": slot }, "value": value });
+        Ok(match request {
+            RpcRequest::GetVersion => json!({ "solana-core": "1.17.7" }),
+            RpcRequest::GetSlot => json!(slot),
+            RpcRequest::GetBlockTime => {
+                json!(banks.get_sysvar::<Clock>().await.unwrap().unix_timestamp)
+            }
+            RpcRequest::GetLatestBlockhash => {
+                let blockhash = banks.get_latest_blockhash().await.unwrap();
+                response(json!({
+                    "blockhash": blockhash.to_string(),
+                    "lastValidBlockHeight": u64::MAX,
+                }))
+            }
+            RpcRequest::GetAccountInfo => {
+                let address: Pubkey = params[0].as_str().unwrap().parse().unwrap();
+                let account = banks.get_account(address).await.unwrap();
+                response(json!(account.map(|account| json!({
+                    "lamports": account.lamports,
+                    "data": [BASE64.encode(&account.data), "base64"],
+                    "owner": account.owner.to_string(),
+                    "executable": account.executable,
+                    "rentEpoch": account.rent_epoch,
+                    "space": account.data.len(),
+                }))))
+            }
+            // like preflight on a cluster, failed transactions fail to send
+            RpcRequest::SendTransaction => {
+                let data = BASE64.decode(params[0].as_str().unwrap()).unwrap();
+                let tx: Transaction = bincode::deserialize(&data).unwrap();
+                let signature = tx.signatures[0];
+                banks
+                    .process_transaction(tx)
+                    .await
+                    .map_err(|err| err.unwrap())?;
+                json!(signature.to_string())
+            }
+            RpcRequest::GetSignatureStatuses => {
+                let signature = params[0][0].as_str().unwrap().parse().unwrap();
+                let status = banks.get_transaction_status(signature).await.unwrap();
+                response(json!([status.map(|status| json!({
+                    "slot": status.slot,
+                    "confirmations": null,
+                    "status": match &status.err {
+                        Some(err) => json!({ "Err": err }),
+                        None => json!({ "Ok": null }),
+                    },
+                    "err": status.err,
+                    "confirmationStatus": "finalized",
+                }))]))
+            }
+            request => panic!("unexpected request {}", request),
+        })
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        RpcTransportStats::default()
+    }
+
+    fn url(&self) -> String {
+        "program-test".to_string()
+    }
+}
@@ -0,0 +1,153 @@
+//! Account fetching and decoding
+
+use anchor_lang::{AccountDeserialize, Discriminator};
+use passes::state::{
+    Config, HolderPage, OwnerVesting, PassesBalance, PassesMetadata, PassesStake, PassesSupply,
+    Proposal, RewardPool, StakePool,
+};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    error::{ClientError, Result},
+    pda,
+};
+
+/// Decode an anchor account from its data, checking the discriminator
+pub fn decode<T: AccountDeserialize>(address: &Pubkey, data: &[u8]) -> Result<T> {
+    T::try_deserialize(&mut &data[..])
+        .map_err(|err| ClientError::InvalidAccount(*address, err.to_string()))
+}
+
+/// Decode a zero copy holder page, which is too large to copy onto the stack on chain
+pub fn decode_holder_page(address: &Pubkey, data: &[u8]) -> Result<Box<HolderPage>> {
+    let len = 8 + std::mem::size_of::<HolderPage>();
+    if data.len() < len || data[..8] != HolderPage::DISCRIMINATOR {
+        return Err(ClientError::InvalidAccount(
+            *address,
+            "not a holder page".to_string(),
+        ));
+    }
+    Ok(Box::new(*bytemuck::from_bytes::<HolderPage>(&data[8..len])))
+}
+
+/// Fetch and decode an account, `None` when it doesn't exist
+pub fn fetch_optional<T: AccountDeserialize>(
+    rpc: &RpcClient,
+    address: &Pubkey,
+) -> Result<Option<T>> {
+    let account = rpc
+        .get_account_with_commitment(address, rpc.commitment())?
+        .value;
+    account
+        .map(|account| decode(address, &account.data))
+        .transpose()
+}
+
+/// Fetch and decode an account that must exist
+pub fn fetch<T: AccountDeserialize>(rpc: &RpcClient, address: &Pubkey) -> Result<T> {
+    fetch_optional(rpc, address)?.ok_or(ClientError::AccountNotFound(*address))
+}
+
+pub fn fetch_config(rpc: &RpcClient, program_id: &Pubkey, marketplace_id: u64) -> Result<Config> {
+    fetch(rpc, &pda::config(program_id, marketplace_id))
+}
+
+pub fn fetch_proposal(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    id: u64,
+) -> Result<Proposal> {
+    fetch(rpc, &pda::proposal(program_id, marketplace_id, id))
+}
+
+pub fn fetch_passes_supply(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    owner: &Pubkey,
+) -> Result<Option<PassesSupply>> {
+    fetch_optional(rpc, &pda::passes_supply(program_id, marketplace_id, owner))
+}
+
+pub fn fetch_passes_balance(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    owner: &Pubkey,
+    holder: &Pubkey,
+) -> Result<Option<PassesBalance>> {
+    fetch_optional(
+        rpc,
+        &pda::passes_balance(program_id, marketplace_id, owner, holder),
+    )
+}
+
+pub fn fetch_passes_metadata(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    owner: &Pubkey,
+) -> Result<Option<PassesMetadata>> {
+    fetch_optional(
+        rpc,
+        &pda::passes_metadata(program_id, marketplace_id, owner),
+    )
+}
+
+pub fn fetch_owner_vesting(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    owner: &Pubkey,
+) -> Result<Option<OwnerVesting>> {
+    fetch_optional(rpc, &pda::owner_vesting(program_id, marketplace_id, owner))
+}
+
+pub fn fetch_reward_pool(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    owner: &Pubkey,
+) -> Result<Option<RewardPool>> {
+    fetch_optional(rpc, &pda::reward_pool(program_id, marketplace_id, owner))
+}
+
+pub fn fetch_stake_pool(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    owner: &Pubkey,
+) -> Result<Option<StakePool>> {
+    fetch_optional(rpc, &pda::stake_pool(program_id, marketplace_id, owner))
+}
+
+pub fn fetch_passes_stake(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    owner: &Pubkey,
+    holder: &Pubkey,
+) -> Result<Option<PassesStake>> {
+    fetch_optional(
+        rpc,
+        &pda::passes_stake(program_id, marketplace_id, owner, holder),
+    )
+}
+
+pub fn fetch_holder_page(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    owner: &Pubkey,
+    page: u32,
+) -> Result<Option<Box<HolderPage>>> {
+    let address = pda::holder_page(program_id, marketplace_id, owner, page);
+    let account = rpc
+        .get_account_with_commitment(&address, rpc.commitment())?
+        .value;
+    account
+        .map(|account| decode_holder_page(&address, &account.data))
+        .transpose()
+}
@@ -0,0 +1,1019 @@
+//! Instruction builders, one per program instruction
+
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::get_associated_token_address;
+use passes::{accounts, instruction, state};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+use crate::pda;
+
+/// Optional accounts a market's instructions require, read from its `PassesSupply`
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MarketOptions {
+    /// The owner's issued passes vest, the owner passes `OwnerVesting` on sells and airdrops
+    pub vesting: bool,
+    /// Holders share rewards, trades and holder pages pass the `RewardPool`
+    pub rewards: bool,
+    /// The market's reserve is isolated, trades pass its reserve wallet
+    pub isolated_reserve: bool,
+}
+
+impl From<&state::PassesSupply> for MarketOptions {
+    fn from(passes_supply: &state::PassesSupply) -> Self {
+        Self {
+            vesting: passes_supply.vesting,
+            rewards: passes_supply.rewards,
+            isolated_reserve: passes_supply.isolated_reserve,
+        }
+    }
+}
+
+impl MarketOptions {
+    fn owner_vesting(
+        &self,
+        program_id: &Pubkey,
+        marketplace_id: u64,
+        owner: &Pubkey,
+    ) -> Option<Pubkey> {
+        self.vesting
+            .then(|| pda::owner_vesting(program_id, marketplace_id, owner))
+    }
+
+    fn reward_pool(
+        &self,
+        program_id: &Pubkey,
+        marketplace_id: u64,
+        owner: &Pubkey,
+    ) -> Option<Pubkey> {
+        self.rewards
+            .then(|| pda::reward_pool(program_id, marketplace_id, owner))
+    }
+
+    fn reserve_token_wallet(
+        &self,
+        program_id: &Pubkey,
+        marketplace_id: u64,
+        owner: &Pubkey,
+    ) -> Option<Pubkey> {
+        self.isolated_reserve
+            .then(|| pda::reserve_token_wallet(program_id, marketplace_id, owner))
+    }
+
+    fn reserve_sol_wallet(
+        &self,
+        program_id: &Pubkey,
+        marketplace_id: u64,
+        owner: &Pubkey,
+    ) -> Option<Pubkey> {
+        self.isolated_reserve
+            .then(|| pda::reserve_sol_wallet(program_id, marketplace_id, owner))
+    }
+}
+
+// Anchor marks a missing optional account with the declared program id, point it at `program_id`
+fn build(
+    program_id: &Pubkey,
+    accounts: impl ToAccountMetas,
+    args: impl InstructionData,
+) -> Instruction {
+    let accounts = accounts
+        .to_account_metas(None)
+        .into_iter()
+        .map(|meta| {
+            if meta.pubkey == passes::ID {
+                AccountMeta::new_readonly(*program_id, false)
+            } else {
+                meta
+            }
+        })
+        .collect();
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: args.data(),
+    }
+}
+
+// config
+
+/// `authority` is the program upgrade authority approving the marketplace, it can be the admin
+#[allow(clippy::too_many_arguments)]
+pub fn init(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    admin: &Pubkey,
+    authority: &Pubkey,
+    payment_mint: &Pubkey,
+    protocol_fee_bps: u64,
+    owner_fee_bps: u64,
+) -> Instruction {
+    let accounts = accounts::Init {
+        admin: *admin,
+        authority: *authority,
+        config: pda::config(program_id, marketplace_id),
+        escrow_token_wallet: pda::escrow_token_wallet(program_id, marketplace_id, payment_mint),
+        escrow_sol_wallet: pda::escrow_sol_wallet(program_id, marketplace_id),
+        protocol_fee_wallet: get_associated_token_address(admin, payment_mint),
+        program: *program_id,
+        program_data: pda::program_data(program_id),
+        payment_mint: *payment_mint,
+        system_program: system_program::ID,
+        token_program: anchor_spl::token::ID,
+        associated_token_program: anchor_spl::associated_token::ID,
+    };
+    let args = instruction::Init {
+        marketplace_id,
+        protocol_fee_bps,
+        owner_fee_bps,
+    };
+    build(program_id, accounts, args)
+}
+
+// accounts created before versioning only exist in the default marketplace
+
+pub fn migrate_config(program_id: &Pubkey, admin: &Pubkey) -> Instruction {
+    let accounts = accounts::MigrateConfig {
+        admin: *admin,
+        config: pda::config(program_id, 0),
+        system_program: system_program::ID,
+    };
+    build(program_id, accounts, instruction::MigrateConfig {})
+}
+
+pub fn migrate_supply(program_id: &Pubkey, payer: &Pubkey, owner: &Pubkey) -> Instruction {
+    let accounts = accounts::MigrateSupply {
+        payer: *payer,
+        passes_supply: pda::passes_supply(program_id, 0, owner),
+        passes_owner: *owner,
+        system_program: system_program::ID,
+    };
+    build(program_id, accounts, instruction::MigrateSupply {})
+}
+
+pub fn migrate_balance(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    payer: &Pubkey,
+    owner: &Pubkey,
+    holder: &Pubkey,
+) -> Instruction {
+    let accounts = accounts::MigrateBalance {
+        payer: *payer,
+        config: pda::config(program_id, marketplace_id),
+        passes_balance: pda::passes_balance(program_id, marketplace_id, owner, holder),
+        passes_owner: *owner,
+        holder: *holder,
+        system_program: system_program::ID,
+    };
+    build(program_id, accounts, instruction::MigrateBalance {})
+}
+
+/// Move an existing market's reserve out of the marketplace escrows into its own wallets.
+/// `proposal_id` is the executed `IsolateReserve` proposal a multisig config requires.
+pub fn isolate_reserve(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    admin: &Pubkey,
+    owner: &Pubkey,
+    payment_mint: &Pubkey,
+    proposal_id: Option<u64>,
+) -> Instruction {
+    let accounts = accounts::IsolateReserve {
+        admin: *admin,
+        config: pda::config(program_id, marketplace_id),
+        proposal: proposal_id.map(|id| pda::proposal(program_id, marketplace_id, id)),
+        passes_supply: pda::passes_supply(program_id, marketplace_id, owner),
+        reserve_token_wallet: pda::reserve_token_wallet(program_id, marketplace_id, owner),
+        reserve_sol_wallet: pda::reserve_sol_wallet(program_id, marketplace_id, owner),
+        escrow_token_wallet: pda::escrow_token_wallet(program_id, marketplace_id, payment_mint),
+        escrow_sol_wallet: pda::escrow_sol_wallet(program_id, marketplace_id),
+        passes_owner: *owner,
+        payment_mint: *payment_mint,
+        system_program: system_program::ID,
+        token_program: anchor_spl::token::ID,
+    };
+    build(program_id, accounts, instruction::IsolateReserve {})
+}
+
+fn set_fee_percent(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    admin: &Pubkey,
+) -> accounts::SetFeePercent {
+    accounts::SetFeePercent {
+        admin: *admin,
+        config: pda::config(program_id, marketplace_id),
+        system_program: system_program::ID,
+    }
+}
+
+pub fn set_protocol_fee_bps(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    admin: &Pubkey,
+    fee_bps: u64,
+) -> Instruction {
+    let accounts = set_fee_percent(program_id, marketplace_id, admin);
+    build(
+        program_id,
+        accounts,
+        instruction::SetProtocolFeeBps { fee_bps },
+    )
+}
+
+pub fn set_owner_fee_bps(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    admin: &Pubkey,
+    fee_bps: u64,
+) -> Instruction {
+    let accounts = set_fee_percent(program_id, marketplace_id, admin);
+    build(
+        program_id,
+        accounts,
+        instruction::SetOwnerFeeBps { fee_bps },
+    )
+}
+
+pub fn set_protocol_fee_dst(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    admin: &Pubkey,
+    protocol_fee_wallet: &Pubkey,
+) -> Instruction {
+    let accounts = accounts::SetProtocolFeeDst {
+        admin: *admin,
+        config: pda::config(program_id, marketplace_id),
+        protocol_fee_wallet: *protocol_fee_wallet,
+        system_program: system_program::ID,
+    };
+    build(program_id, accounts, instruction::SetProtocolFeeDst {})
+}
+
+pub fn set_protocol_fee_sol_dst(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    admin: &Pubkey,
+    protocol_fee_wallet: &Pubkey,
+) -> Instruction {
+    let accounts = accounts::SetProtocolFeeSolDst {
+        admin: *admin,
+        config: pda::config(program_id, marketplace_id),
+        protocol_fee_wallet: *protocol_fee_wallet,
+        system_program: system_program::ID,
+    };
+    build(program_id, accounts, instruction::SetProtocolFeeSolDst {})
+}
+
+pub fn set_guardian(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    admin: &Pubkey,
+    guardian: &Pubkey,
+) -> Instruction {
+    let accounts = accounts::SetGuardian {
+        admin: *admin,
+        config: pda::config(program_id, marketplace_id),
+    };
+    let args = instruction::SetGuardian {
+        guardian: *guardian,
+    };
+    build(program_id, accounts, args)
+}
+
+pub fn set_admins(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    admin: &Pubkey,
+    admins: Vec<Pubkey>,
+    threshold: u8,
+) -> Instruction {
+    let accounts = accounts::SetAdmins {
+        admin: *admin,
+        config: pda::config(program_id, marketplace_id),
+    };
+    build(
+        program_id,
+        accounts,
+        instruction::SetAdmins { admins, threshold },
+    )
+}
+
+/// `proposal_id` is the config's `proposal_count` when proposing
+pub fn propose_config_change(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    proposer: &Pubkey,
+    proposal_id: u64,
+    change: state::ConfigChange,
+) -> Instruction {
+    let accounts = accounts::ProposeConfigChange {
+        proposer: *proposer,
+        config: pda::config(program_id, marketplace_id),
+        proposal: pda::proposal(program_id, marketplace_id, proposal_id),
+        system_program: system_program::ID,
+    };
+    build(
+        program_id,
+        accounts,
+        instruction::ProposeConfigChange { change },
+    )
+}
+
+pub fn approve_proposal(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    approver: &Pubkey,
+    proposal_id: u64,
+) -> Instruction {
+    let accounts = accounts::ApproveProposal {
+        approver: *approver,
+        config: pda::config(program_id, marketplace_id),
+        proposal: pda::proposal(program_id, marketplace_id, proposal_id),
+    };
+    build(program_id, accounts, instruction::ApproveProposal {})
+}
+
+/// `protocol_fee_token_wallet` is required when the proposal changes the token fee destination
+pub fn execute_proposal(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    executor: &Pubkey,
+    proposal_id: u64,
+    protocol_fee_token_wallet: Option<Pubkey>,
+) -> Instruction {
+    let accounts = accounts::ExecuteProposal {
+        executor: *executor,
+        config: pda::config(program_id, marketplace_id),
+        proposal: pda::proposal(program_id, marketplace_id, proposal_id),
+        protocol_fee_token_wallet,
+    };
+    build(program_id, accounts, instruction::ExecuteProposal {})
+}
+
+fn set_pause(program_id: &Pubkey, marketplace_id: u64, authority: &Pubkey) -> accounts::SetPause {
+    accounts::SetPause {
+        authority: *authority,
+        config: pda::config(program_id, marketplace_id),
+    }
+}
+
+pub fn set_buys_paused(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    authority: &Pubkey,
+    paused: bool,
+) -> Instruction {
+    let accounts = set_pause(program_id, marketplace_id, authority);
+    build(program_id, accounts, instruction::SetBuysPaused { paused })
+}
+
+pub fn set_sells_paused(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    authority: &Pubkey,
+    paused: bool,
+) -> Instruction {
+    let accounts = set_pause(program_id, marketplace_id, authority);
+    build(program_id, accounts, instruction::SetSellsPaused { paused })
+}
+
+pub fn set_issuance_paused(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    authority: &Pubkey,
+    paused: bool,
+) -> Instruction {
+    let accounts = set_pause(program_id, marketplace_id, authority);
+    build(
+        program_id,
+        accounts,
+        instruction::SetIssuancePaused { paused },
+    )
+}
+
+// market settings
+
+pub fn set_passes_paused(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    owner: &Pubkey,
+    paused: bool,
+) -> Instruction {
+    let accounts = accounts::SetPassesPaused {
+        config: pda::config(program_id, marketplace_id),
+        owner: *owner,
+        passes_supply: pda::passes_supply(program_id, marketplace_id, owner),
+    };
+    build(
+        program_id,
+        accounts,
+        instruction::SetPassesPaused { paused },
+    )
+}
+
+pub fn set_wallet_cap(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    owner: &Pubkey,
+    max_wallet_amount: u64,
+    max_wallet_bps: u64,
+) -> Instruction {
+    let accounts = accounts::SetWalletCap {
+        config: pda::config(program_id, marketplace_id),
+        owner: *owner,
+        passes_supply: pda::passes_supply(program_id, marketplace_id, owner),
+    };
+    let args = instruction::SetWalletCap {
+        max_wallet_amount,
+        max_wallet_bps,
+    };
+    build(program_id, accounts, args)
+}
+
+pub fn set_holding_period(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    owner: &Pubkey,
+    min_hold_secs: i64,
+    early_exit_fee_bps: u64,
+) -> Instruction {
+    let accounts = accounts::SetHoldingPeriod {
+        config: pda::config(program_id, marketplace_id),
+        owner: *owner,
+        passes_supply: pda::passes_supply(program_id, marketplace_id, owner),
+    };
+    let args = instruction::SetHoldingPeriod {
+        min_hold_secs,
+        early_exit_fee_bps,
+    };
+    build(program_id, accounts, args)
+}
+
+pub fn issue_passes(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    owner: &Pubkey,
+    payment_mint: &Pubkey,
+    amount: u64,
+    params: state::IssuanceParams,
+) -> Instruction {
+    let owner_vesting = (params.vesting_duration > 0)
+        .then(|| pda::owner_vesting(program_id, marketplace_id, owner));
+    let reserve = MarketOptions {
+        isolated_reserve: params.isolated_reserve,
+        ..Default::default()
+    };
+    let accounts = accounts::IssuePasses {
+        owner: *owner,
+        passes_supply: pda::passes_supply(program_id, marketplace_id, owner),
+        passes_balance: pda::passes_balance(program_id, marketplace_id, owner, owner),
+        passes_metadata: pda::passes_metadata(program_id, marketplace_id, owner),
+        owner_vesting,
+        config: pda::config(program_id, marketplace_id),
+        reserve_token_wallet: reserve.reserve_token_wallet(program_id, marketplace_id, owner),
+        reserve_sol_wallet: reserve.reserve_sol_wallet(program_id, marketplace_id, owner),
+        owner_fee_wallet: get_associated_token_address(owner, payment_mint),
+        payment_mint: *payment_mint,
+        system_program: system_program::ID,
+        token_program: anchor_spl::token::ID,
+        associated_token_program: anchor_spl::associated_token::ID,
+    };
+    build(
+        program_id,
+        accounts,
+        instruction::IssuePasses { amount, params },
+    )
+}
+
+pub fn update_passes_metadata(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    owner: &Pubkey,
+    name: String,
+    description_uri: String,
+    image_uri: String,
+    social_handle: String,
+) -> Instruction {
+    let accounts = accounts::UpdatePassesMetadata {
+        config: pda::config(program_id, marketplace_id),
+        owner: *owner,
+        passes_supply: pda::passes_supply(program_id, marketplace_id, owner),
+        passes_metadata: pda::passes_metadata(program_id, marketplace_id, owner),
+        system_program: system_program::ID,
+    };
+    let args = instruction::UpdatePassesMetadata {
+        name,
+        description_uri,
+        image_uri,
+        social_handle,
+    };
+    build(program_id, accounts, args)
+}
+
+/// Gift passes from the owner's balance, one (recipient, amount) pair per recipient
+pub fn airdrop_passes(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    owner: &Pubkey,
+    recipients: &[(Pubkey, u64)],
+    options: MarketOptions,
+) -> Instruction {
+    let accounts = accounts::AirdropPasses {
+        config: pda::config(program_id, marketplace_id),
+        owner: *owner,
+        passes_supply: pda::passes_supply(program_id, marketplace_id, owner),
+        passes_balance: pda::passes_balance(program_id, marketplace_id, owner, owner),
+        owner_vesting: options.owner_vesting(program_id, marketplace_id, owner),
+        reward_pool: options.reward_pool(program_id, marketplace_id, owner),
+        system_program: system_program::ID,
+    };
+    let args = instruction::AirdropPasses {
+        amounts: recipients.iter().map(|(_, amount)| *amount).collect(),
+    };
+    let mut ix = build(program_id, accounts, args);
+    for (recipient, _) in recipients {
+        ix.accounts
+            .push(AccountMeta::new_readonly(*recipient, false));
+        ix.accounts.push(AccountMeta::new(
+            pda::passes_balance(program_id, marketplace_id, owner, recipient),
+            false,
+        ));
+    }
+    ix
+}
+
+// trading
+
+pub fn get_price(program_id: &Pubkey, invoker: &Pubkey, supply: u64, amount: u64) -> Instruction {
+    let accounts = accounts::GetPrice { invoker: *invoker };
+    build(
+        program_id,
+        accounts,
+        instruction::GetPrice { supply, amount },
+    )
+}
+
+pub fn get_price_sol(
+    program_id: &Pubkey,
+    invoker: &Pubkey,
+    supply: u64,
+    amount: u64,
+) -> Instruction {
+    let accounts = accounts::GetPrice { invoker: *invoker };
+    build(
+        program_id,
+        accounts,
+        instruction::GetPriceSol { supply, amount },
+    )
+}
+
+pub fn get_trade_stats(program_id: &Pubkey, marketplace_id: u64, owner: &Pubkey) -> Instruction {
+    let accounts = accounts::GetTradeStats {
+        config: pda::config(program_id, marketplace_id),
+        passes_supply: pda::passes_supply(program_id, marketplace_id, owner),
+        passes_owner: *owner,
+    };
+    build(program_id, accounts, instruction::GetTradeStats {})
+}
+
+/// The buyer's balance account, or with `holder_page` that page instead
+fn buyer_holding(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    owner: &Pubkey,
+    buyer: &Pubkey,
+    holder_page: Option<u32>,
+) -> (Option<Pubkey>, Option<Pubkey>) {
+    match holder_page {
+        Some(page) => (
+            None,
+            Some(pda::holder_page(program_id, marketplace_id, owner, page)),
+        ),
+        None => (
+            Some(pda::passes_balance(
+                program_id,
+                marketplace_id,
+                owner,
+                buyer,
+            )),
+            None,
+        ),
+    }
+}
+
+/// `protocol_fee_wallet` is the config's `protocol_fee_token_wallet`,
+/// `proof` the buyer's allowlist proof during a presale, `holder_page`
+/// the page to credit the passes to instead of the buyer's balance
+#[allow(clippy::too_many_arguments)]
+pub fn buy_passes(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    buyer: &Pubkey,
+    owner: &Pubkey,
+    payment_mint: &Pubkey,
+    protocol_fee_wallet: &Pubkey,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+    holder_page: Option<u32>,
+    options: MarketOptions,
+) -> Instruction {
+    let (passes_balance, holder_page) =
+        buyer_holding(program_id, marketplace_id, owner, buyer, holder_page);
+    let accounts = accounts::BuyPasses {
+        buyer: *buyer,
+        passes_supply: pda::passes_supply(program_id, marketplace_id, owner),
+        passes_balance,
+        holder_page,
+        reward_pool: options.reward_pool(program_id, marketplace_id, owner),
+        config: pda::config(program_id, marketplace_id),
+        owner_fee_wallet: get_associated_token_address(owner, payment_mint),
+        escrow_wallet: pda::escrow_token_wallet(program_id, marketplace_id, payment_mint),
+        reserve_wallet: options.reserve_token_wallet(program_id, marketplace_id, owner),
+        passes_owner: *owner,
+        payment_mint: *payment_mint,
+        protocol_fee_wallet: *protocol_fee_wallet,
+        buyer_wallet: get_associated_token_address(buyer, payment_mint),
+        system_program: system_program::ID,
+        token_program: anchor_spl::token::ID,
+    };
+    build(
+        program_id,
+        accounts,
+        instruction::BuyPasses { amount, proof },
+    )
+}
+
+/// `protocol_fee_wallet` is the config's `protocol_fee_sol_wallet`,
+/// `proof` the buyer's allowlist proof during a presale, `holder_page`
+/// the page to credit the passes to instead of the buyer's balance
+#[allow(clippy::too_many_arguments)]
+pub fn buy_passes_sol(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    buyer: &Pubkey,
+    owner: &Pubkey,
+    protocol_fee_wallet: &Pubkey,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+    holder_page: Option<u32>,
+    options: MarketOptions,
+) -> Instruction {
+    let (passes_balance, holder_page) =
+        buyer_holding(program_id, marketplace_id, owner, buyer, holder_page);
+    let accounts = accounts::BuyPassesSol {
+        buyer: *buyer,
+        passes_supply: pda::passes_supply(program_id, marketplace_id, owner),
+        passes_balance,
+        holder_page,
+        reward_pool: options.reward_pool(program_id, marketplace_id, owner),
+        config: pda::config(program_id, marketplace_id),
+        escrow_wallet: pda::escrow_sol_wallet(program_id, marketplace_id),
+        reserve_wallet: options.reserve_sol_wallet(program_id, marketplace_id, owner),
+        passes_owner: *owner,
+        protocol_fee_wallet: *protocol_fee_wallet,
+        system_program: system_program::ID,
+    };
+    build(
+        program_id,
+        accounts,
+        instruction::BuyPassesSol { amount, proof },
+    )
+}
+
+/// `protocol_fee_wallet` is the config's `protocol_fee_token_wallet`
+#[allow(clippy::too_many_arguments)]
+pub fn sell_passes(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    seller: &Pubkey,
+    owner: &Pubkey,
+    payment_mint: &Pubkey,
+    protocol_fee_wallet: &Pubkey,
+    amount: u64,
+    options: MarketOptions,
+) -> Instruction {
+    let accounts = accounts::SellPasses {
+        seller: *seller,
+        passes_supply: pda::passes_supply(program_id, marketplace_id, owner),
+        passes_balance: pda::passes_balance(program_id, marketplace_id, owner, seller),
+        owner_vesting: options.owner_vesting(program_id, marketplace_id, owner),
+        reward_pool: options.reward_pool(program_id, marketplace_id, owner),
+        config: pda::config(program_id, marketplace_id),
+        owner_fee_wallet: get_associated_token_address(owner, payment_mint),
+        escrow_wallet: pda::escrow_token_wallet(program_id, marketplace_id, payment_mint),
+        reserve_wallet: options.reserve_token_wallet(program_id, marketplace_id, owner),
+        passes_owner: *owner,
+        payment_mint: *payment_mint,
+        protocol_fee_wallet: *protocol_fee_wallet,
+        seller_wallet: get_associated_token_address(seller, payment_mint),
+        system_program: system_program::ID,
+        token_program: anchor_spl::token::ID,
+        associated_token_program: anchor_spl::associated_token::ID,
+    };
+    build(program_id, accounts, instruction::SellPasses { amount })
+}
+
+/// `protocol_fee_wallet` is the config's `protocol_fee_sol_wallet`
+pub fn sell_passes_sol(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    seller: &Pubkey,
+    owner: &Pubkey,
+    protocol_fee_wallet: &Pubkey,
+    amount: u64,
+    options: MarketOptions,
+) -> Instruction {
+    let accounts = accounts::SellPassesSol {
+        seller: *seller,
+        passes_supply: pda::passes_supply(program_id, marketplace_id, owner),
+        passes_balance: pda::passes_balance(program_id, marketplace_id, owner, seller),
+        owner_vesting: options.owner_vesting(program_id, marketplace_id, owner),
+        reward_pool: options.reward_pool(program_id, marketplace_id, owner),
+        config: pda::config(program_id, marketplace_id),
+        escrow_wallet: pda::escrow_sol_wallet(program_id, marketplace_id),
+        reserve_wallet: options.reserve_sol_wallet(program_id, marketplace_id, owner),
+        passes_owner: *owner,
+        protocol_fee_wallet: *protocol_fee_wallet,
+        system_program: system_program::ID,
+    };
+    build(program_id, accounts, instruction::SellPassesSol { amount })
+}
+
+/// `holder_page` the page with the holder's entry, if some of their passes are paged
+pub fn verify_holding(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    owner: &Pubkey,
+    holder: &Pubkey,
+    holder_page: Option<u32>,
+    min_amount: u64,
+) -> Instruction {
+    let accounts = accounts::VerifyHolding {
+        config: pda::config(program_id, marketplace_id),
+        passes_balance: pda::passes_balance(program_id, marketplace_id, owner, holder),
+        holder_page: holder_page
+            .map(|page| pda::holder_page(program_id, marketplace_id, owner, page)),
+        holder: *holder,
+        passes_owner: *owner,
+    };
+    build(
+        program_id,
+        accounts,
+        instruction::VerifyHolding { min_amount },
+    )
+}
+
+// holder pages
+
+pub fn create_holder_page(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    payer: &Pubkey,
+    owner: &Pubkey,
+    page: u32,
+) -> Instruction {
+    let accounts = accounts::CreateHolderPage {
+        config: pda::config(program_id, marketplace_id),
+        payer: *payer,
+        passes_supply: pda::passes_supply(program_id, marketplace_id, owner),
+        holder_page: pda::holder_page(program_id, marketplace_id, owner, page),
+        passes_owner: *owner,
+        system_program: system_program::ID,
+    };
+    build(program_id, accounts, instruction::CreateHolderPage { page })
+}
+
+pub fn convert_to_holder_page(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    holder: &Pubkey,
+    owner: &Pubkey,
+    page: u32,
+    options: MarketOptions,
+) -> Instruction {
+    let accounts = accounts::ConvertToHolderPage {
+        config: pda::config(program_id, marketplace_id),
+        holder: *holder,
+        passes_balance: pda::passes_balance(program_id, marketplace_id, owner, holder),
+        passes_supply: pda::passes_supply(program_id, marketplace_id, owner),
+        holder_page: pda::holder_page(program_id, marketplace_id, owner, page),
+        reward_pool: options.reward_pool(program_id, marketplace_id, owner),
+        passes_owner: *owner,
+    };
+    build(program_id, accounts, instruction::ConvertToHolderPage {})
+}
+
+pub fn convert_from_holder_page(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    holder: &Pubkey,
+    owner: &Pubkey,
+    page: u32,
+    options: MarketOptions,
+) -> Instruction {
+    let accounts = accounts::ConvertFromHolderPage {
+        config: pda::config(program_id, marketplace_id),
+        holder: *holder,
+        passes_balance: pda::passes_balance(program_id, marketplace_id, owner, holder),
+        passes_supply: pda::passes_supply(program_id, marketplace_id, owner),
+        holder_page: pda::holder_page(program_id, marketplace_id, owner, page),
+        reward_pool: options.reward_pool(program_id, marketplace_id, owner),
+        passes_owner: *owner,
+        system_program: system_program::ID,
+    };
+    build(program_id, accounts, instruction::ConvertFromHolderPage {})
+}
+
+// holder rewards
+
+pub fn set_reward_share(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    owner: &Pubkey,
+    holder_fee_bps: u64,
+) -> Instruction {
+    let accounts = accounts::SetRewardShare {
+        config: pda::config(program_id, marketplace_id),
+        owner: *owner,
+        passes_supply: pda::passes_supply(program_id, marketplace_id, owner),
+        reward_pool: pda::reward_pool(program_id, marketplace_id, owner),
+        system_program: system_program::ID,
+    };
+    build(
+        program_id,
+        accounts,
+        instruction::SetRewardShare { holder_fee_bps },
+    )
+}
+
+pub fn deposit_rewards(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    depositor: &Pubkey,
+    owner: &Pubkey,
+    payment_mint: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let accounts = accounts::DepositRewards {
+        depositor: *depositor,
+        passes_supply: pda::passes_supply(program_id, marketplace_id, owner),
+        reward_pool: pda::reward_pool(program_id, marketplace_id, owner),
+        config: pda::config(program_id, marketplace_id),
+        escrow_wallet: pda::escrow_token_wallet(program_id, marketplace_id, payment_mint),
+        passes_owner: *owner,
+        payment_mint: *payment_mint,
+        depositor_wallet: get_associated_token_address(depositor, payment_mint),
+        token_program: anchor_spl::token::ID,
+    };
+    build(program_id, accounts, instruction::DepositRewards { amount })
+}
+
+pub fn deposit_rewards_sol(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    depositor: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let accounts = accounts::DepositRewardsSol {
+        config: pda::config(program_id, marketplace_id),
+        depositor: *depositor,
+        passes_supply: pda::passes_supply(program_id, marketplace_id, owner),
+        reward_pool: pda::reward_pool(program_id, marketplace_id, owner),
+        escrow_wallet: pda::escrow_sol_wallet(program_id, marketplace_id),
+        passes_owner: *owner,
+        system_program: system_program::ID,
+    };
+    build(
+        program_id,
+        accounts,
+        instruction::DepositRewardsSol { amount },
+    )
+}
+
+pub fn claim_rewards(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    holder: &Pubkey,
+    owner: &Pubkey,
+    payment_mint: &Pubkey,
+) -> Instruction {
+    let accounts = accounts::ClaimRewards {
+        holder: *holder,
+        passes_balance: pda::passes_balance(program_id, marketplace_id, owner, holder),
+        reward_pool: pda::reward_pool(program_id, marketplace_id, owner),
+        config: pda::config(program_id, marketplace_id),
+        escrow_wallet: pda::escrow_token_wallet(program_id, marketplace_id, payment_mint),
+        passes_owner: *owner,
+        payment_mint: *payment_mint,
+        holder_wallet: get_associated_token_address(holder, payment_mint),
+        token_program: anchor_spl::token::ID,
+    };
+    build(program_id, accounts, instruction::ClaimRewards {})
+}
+
+pub fn claim_rewards_sol(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    holder: &Pubkey,
+    owner: &Pubkey,
+) -> Instruction {
+    let accounts = accounts::ClaimRewardsSol {
+        config: pda::config(program_id, marketplace_id),
+        holder: *holder,
+        passes_balance: pda::passes_balance(program_id, marketplace_id, owner, holder),
+        reward_pool: pda::reward_pool(program_id, marketplace_id, owner),
+        escrow_wallet: pda::escrow_sol_wallet(program_id, marketplace_id),
+        passes_owner: *owner,
+    };
+    build(program_id, accounts, instruction::ClaimRewardsSol {})
+}
+
+// staking
+
+pub fn fund_stake_rewards(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    owner: &Pubkey,
+    payment_mint: &Pubkey,
+    amount: u64,
+    duration: i64,
+) -> Instruction {
+    let accounts = accounts::FundStakeRewards {
+        owner: *owner,
+        stake_pool: pda::stake_pool(program_id, marketplace_id, owner),
+        config: pda::config(program_id, marketplace_id),
+        escrow_wallet: pda::escrow_token_wallet(program_id, marketplace_id, payment_mint),
+        payment_mint: *payment_mint,
+        owner_wallet: get_associated_token_address(owner, payment_mint),
+        system_program: system_program::ID,
+        token_program: anchor_spl::token::ID,
+    };
+    build(
+        program_id,
+        accounts,
+        instruction::FundStakeRewards { amount, duration },
+    )
+}
+
+pub fn stake_passes(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    holder: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let accounts = accounts::StakePasses {
+        config: pda::config(program_id, marketplace_id),
+        holder: *holder,
+        passes_balance: pda::passes_balance(program_id, marketplace_id, owner, holder),
+        stake_pool: pda::stake_pool(program_id, marketplace_id, owner),
+        passes_stake: pda::passes_stake(program_id, marketplace_id, owner, holder),
+        passes_owner: *owner,
+        system_program: system_program::ID,
+    };
+    build(program_id, accounts, instruction::StakePasses { amount })
+}
+
+pub fn unstake_passes(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    holder: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let accounts = accounts::UnstakePasses {
+        config: pda::config(program_id, marketplace_id),
+        holder: *holder,
+        passes_balance: pda::passes_balance(program_id, marketplace_id, owner, holder),
+        stake_pool: pda::stake_pool(program_id, marketplace_id, owner),
+        passes_stake: pda::passes_stake(program_id, marketplace_id, owner, holder),
+        passes_owner: *owner,
+    };
+    build(program_id, accounts, instruction::UnstakePasses { amount })
+}
+
+pub fn claim_stake_rewards(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    holder: &Pubkey,
+    owner: &Pubkey,
+    payment_mint: &Pubkey,
+) -> Instruction {
+    let accounts = accounts::ClaimStakeRewards {
+        holder: *holder,
+        stake_pool: pda::stake_pool(program_id, marketplace_id, owner),
+        passes_stake: pda::passes_stake(program_id, marketplace_id, owner, holder),
+        config: pda::config(program_id, marketplace_id),
+        escrow_wallet: pda::escrow_token_wallet(program_id, marketplace_id, payment_mint),
+        passes_owner: *owner,
+        payment_mint: *payment_mint,
+        holder_wallet: get_associated_token_address(holder, payment_mint),
+        token_program: anchor_spl::token::ID,
+    };
+    build(program_id, accounts, instruction::ClaimStakeRewards {})
+}
@@ -0,0 +1,14 @@
+//! Rust client for the passes program: PDA derivation, instruction builders,
+//! account fetching and decoding, and off-chain price quotes.
+
+pub mod accounts;
+pub mod error;
+pub mod instructions;
+pub mod pda;
+pub mod quote;
+
+#[cfg(test)]
+mod tests;
+
+pub use error::ClientError;
+pub use passes;
@@ -0,0 +1,186 @@
+//! Program derived addresses of the passes program.
+//!
+//! Every PDA but the program data belongs to a marketplace, 0 is the default marketplace.
+
+use passes::state::{self, marketplace_seed};
+use solana_sdk::{bpf_loader_upgradeable, pubkey::Pubkey};
+
+pub fn config(program_id: &Pubkey, marketplace_id: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[state::Config::SEED, &marketplace_seed(marketplace_id)],
+        program_id,
+    )
+    .0
+}
+
+pub fn escrow_token_wallet(program_id: &Pubkey, marketplace_id: u64, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            state::Config::ESCROW_TOKEN_SEED,
+            &marketplace_seed(marketplace_id),
+            mint.as_ref(),
+        ],
+        program_id,
+    )
+    .0
+}
+
+pub fn escrow_sol_wallet(program_id: &Pubkey, marketplace_id: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[state::EscrowSOL::SEED, &marketplace_seed(marketplace_id)],
+        program_id,
+    )
+    .0
+}
+
+pub fn proposal(program_id: &Pubkey, marketplace_id: u64, id: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            state::Proposal::SEED,
+            &marketplace_seed(marketplace_id),
+            &id.to_le_bytes(),
+        ],
+        program_id,
+    )
+    .0
+}
+
+pub fn passes_supply(program_id: &Pubkey, marketplace_id: u64, owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            state::PassesSupply::SEED,
+            &marketplace_seed(marketplace_id),
+            owner.as_ref(),
+        ],
+        program_id,
+    )
+    .0
+}
+
+/// Token wallet of a market's isolated reserve
+pub fn reserve_token_wallet(program_id: &Pubkey, marketplace_id: u64, owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            state::PassesSupply::RESERVE_TOKEN_SEED,
+            &marketplace_seed(marketplace_id),
+            owner.as_ref(),
+        ],
+        program_id,
+    )
+    .0
+}
+
+/// Lamport vault of a market's isolated reserve
+pub fn reserve_sol_wallet(program_id: &Pubkey, marketplace_id: u64, owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            state::PassesSupply::RESERVE_SOL_SEED,
+            &marketplace_seed(marketplace_id),
+            owner.as_ref(),
+        ],
+        program_id,
+    )
+    .0
+}
+
+pub fn passes_balance(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    owner: &Pubkey,
+    holder: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            state::PassesBalance::SEED,
+            &marketplace_seed(marketplace_id),
+            owner.as_ref(),
+            holder.as_ref(),
+        ],
+        program_id,
+    )
+    .0
+}
+
+pub fn passes_metadata(program_id: &Pubkey, marketplace_id: u64, owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            state::PassesMetadata::SEED,
+            &marketplace_seed(marketplace_id),
+            owner.as_ref(),
+        ],
+        program_id,
+    )
+    .0
+}
+
+pub fn owner_vesting(program_id: &Pubkey, marketplace_id: u64, owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            state::OwnerVesting::SEED,
+            &marketplace_seed(marketplace_id),
+            owner.as_ref(),
+        ],
+        program_id,
+    )
+    .0
+}
+
+pub fn reward_pool(program_id: &Pubkey, marketplace_id: u64, owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            state::RewardPool::SEED,
+            &marketplace_seed(marketplace_id),
+            owner.as_ref(),
+        ],
+        program_id,
+    )
+    .0
+}
+
+pub fn stake_pool(program_id: &Pubkey, marketplace_id: u64, owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            state::StakePool::SEED,
+            &marketplace_seed(marketplace_id),
+            owner.as_ref(),
+        ],
+        program_id,
+    )
+    .0
+}
+
+pub fn passes_stake(
+    program_id: &Pubkey,
+    marketplace_id: u64,
+    owner: &Pubkey,
+    holder: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            state::PassesStake::SEED,
+            &marketplace_seed(marketplace_id),
+            owner.as_ref(),
+            holder.as_ref(),
+        ],
+        program_id,
+    )
+    .0
+}
+
+pub fn holder_page(program_id: &Pubkey, marketplace_id: u64, owner: &Pubkey, page: u32) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            state::HolderPage::SEED,
+            &marketplace_seed(marketplace_id),
+            owner.as_ref(),
+            &page.to_le_bytes(),
+        ],
+        program_id,
+    )
+    .0
+}
+
+/// The program data account of the upgradeable program, needed by `init`
+pub fn program_data(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id()).0
+}
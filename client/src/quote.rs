@@ -0,0 +1,163 @@
+//! Off-chain price quotes, computed the same way as the trade instructions
+
+use anchor_lang::error;
+use passes::{
+    calc_fee, curve_price, curve_price_sol,
+    error::PassesError,
+    split_sell_price,
+    state::{Config, OwnerVesting, PassesBalance, PassesSupply, RewardPool},
+};
+
+use crate::error::Result;
+
+/// Currency a market trades in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Currency {
+    Token,
+    Sol,
+}
+
+/// Breakdown of a trade, in token base units or lamports
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Quote {
+    /// Bonding curve price of the passes
+    pub price: u64,
+    pub protocol_fees: u64,
+    /// Owner fees left after the holders' share
+    pub owner_fees: u64,
+    /// Holders' share of the owner fees
+    pub holder_fees: u64,
+    /// Charged on sells within the holding period
+    pub early_exit_fees: u64,
+    /// Paid by the buyer on buys, received by the seller on sells
+    pub total: u64,
+}
+
+/// Curve price of `amount` passes at curve position `supply`, equal to `calc_price`/`calc_price_sol`
+pub fn price(currency: Currency, supply: u64, amount: u64) -> Result<u64> {
+    Ok(match currency {
+        Currency::Token => curve_price(supply, amount)?,
+        Currency::Sol => curve_price_sol(supply, amount)?,
+    })
+}
+
+// Fail with `error` unless `condition` holds, like the program's `require!`
+fn require(condition: bool, error: PassesError) -> Result<()> {
+    if condition {
+        Ok(())
+    } else {
+        Err(error!(error).into())
+    }
+}
+
+// Price and fees shared by buys and sells, `supply` is the market supply before the curve offset
+fn fees(
+    config: &Config,
+    passes_supply: &PassesSupply,
+    reward_pool: Option<&RewardPool>,
+    currency: Currency,
+    supply: u64,
+    amount: u64,
+) -> Result<Quote> {
+    let price = price(currency, passes_supply.curve_supply(supply)?, amount)?;
+    require(price > 0, PassesError::ZeroPrice)?;
+    let protocol_fees = calc_fee(config.protocol_fee_bps, price)?;
+    let owner_fees = calc_fee(config.owner_fee_bps, price)?;
+    let holder_fees = match reward_pool {
+        _ if !passes_supply.rewards => 0,
+        Some(reward_pool) => {
+            reward_pool.holder_fees(owner_fees, passes_supply.rewarded_supply())?
+        }
+        None => return Err(error!(PassesError::RewardPoolMissing).into()),
+    };
+    Ok(Quote {
+        price,
+        protocol_fees,
+        owner_fees: owner_fees - holder_fees,
+        holder_fees,
+        ..Default::default()
+    })
+}
+
+/// Quote buying `amount` passes, `reward_pool` is needed when the market shares rewards
+pub fn quote_buy(
+    config: &Config,
+    passes_supply: &PassesSupply,
+    reward_pool: Option<&RewardPool>,
+    currency: Currency,
+    amount: u64,
+) -> Result<Quote> {
+    let mut quote = fees(
+        config,
+        passes_supply,
+        reward_pool,
+        currency,
+        passes_supply.amount,
+        amount,
+    )?;
+    quote.total = quote.price + quote.protocol_fees + quote.owner_fees + quote.holder_fees;
+    Ok(quote)
+}
+
+/// Quote selling `amount` passes at `now` from `passes_balance`, the seller's balance.
+/// `seller_is_owner` tells whether the passes owner sells, who then passes `owner_vesting` when
+/// their issued passes vest. Fails with the error the sell instruction would.
+#[allow(clippy::too_many_arguments)]
+pub fn quote_sell(
+    config: &Config,
+    passes_supply: &PassesSupply,
+    reward_pool: Option<&RewardPool>,
+    passes_balance: &PassesBalance,
+    seller_is_owner: bool,
+    owner_vesting: Option<&OwnerVesting>,
+    currency: Currency,
+    amount: u64,
+    now: i64,
+) -> Result<Quote> {
+    let balance = passes_balance.amount;
+    require(
+        !config.sells_paused && !passes_supply.paused,
+        PassesError::Paused,
+    )?;
+    require(passes_supply.amount > amount, PassesError::LastPass)?;
+    passes_supply
+        .clone()
+        .check_currency(currency == Currency::Sol)?;
+    require(balance >= amount, PassesError::InsufficientPasses)?;
+    require(
+        passes_balance.unstaked() >= amount,
+        PassesError::PassesStaked,
+    )?;
+    if seller_is_owner && passes_supply.vesting {
+        let owner_vesting = owner_vesting.ok_or(error!(PassesError::VestingAccountMissing))?;
+        let locked = owner_vesting.locked(now)?;
+        require(balance - amount >= locked, PassesError::PassesVesting)?;
+    }
+    if seller_is_owner {
+        passes_supply.check_issued(balance, amount)?;
+    }
+
+    let mut quote = fees(
+        config,
+        passes_supply,
+        reward_pool,
+        currency,
+        passes_supply.amount - amount,
+        amount,
+    )?;
+    let early_exit_fees =
+        passes_supply.early_exit_fee(passes_balance.last_buy_ts, now, quote.price)?;
+    let (total, owner_fees, early_exit_fees) = split_sell_price(
+        config,
+        passes_supply,
+        quote.price,
+        quote.protocol_fees,
+        quote.owner_fees,
+        quote.holder_fees,
+        early_exit_fees,
+    )?;
+    quote.owner_fees = owner_fees;
+    quote.early_exit_fees = early_exit_fees;
+    quote.total = total;
+    Ok(quote)
+}
@@ -0,0 +1,380 @@
+use anchor_lang::{AccountDeserialize, Discriminator};
+use passes::{
+    error::PassesError,
+    state::{
+        Config, HolderPage, MarketCurrency, OwnerVesting, PassesBalance, PassesSupply, RewardPool,
+    },
+};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    accounts::{decode, decode_holder_page},
+    instructions::{self, MarketOptions},
+    pda,
+    quote::{self, Currency},
+    ClientError,
+};
+
+// Decode a zeroed account of `len` bytes, then tests set the fields they need
+fn zeroed<T: AccountDeserialize + Discriminator>(len: usize) -> T {
+    let mut data = vec![0; len];
+    data[..8].copy_from_slice(&T::DISCRIMINATOR);
+    decode(&Pubkey::default(), &data).unwrap()
+}
+
+fn config(protocol_fee_bps: u64, owner_fee_bps: u64) -> Config {
+    let mut config: Config = zeroed(Config::LEN);
+    config.protocol_fee_bps = protocol_fee_bps;
+    config.owner_fee_bps = owner_fee_bps;
+    config
+}
+
+fn passes_supply(amount: u64) -> PassesSupply {
+    let mut passes_supply: PassesSupply = zeroed(PassesSupply::LEN);
+    passes_supply.amount = amount;
+    passes_supply
+}
+
+fn passes_balance(amount: u64) -> PassesBalance {
+    let mut passes_balance: PassesBalance = zeroed(PassesBalance::LEN);
+    passes_balance.amount = amount;
+    passes_balance
+}
+
+fn assert_passes_error<T: std::fmt::Debug>(res: Result<T, ClientError>, error: PassesError) {
+    match res {
+        Err(ClientError::Program(anchor_lang::error::Error::AnchorError(err))) => {
+            assert_eq!(err.error_code_number, u32::from(error), "{}", err)
+        }
+        res => panic!("expected {:?}, got {:?}", error, res),
+    }
+}
+
+#[test]
+fn test_pda() {
+    let owner = Pubkey::new_unique();
+    let holder = Pubkey::new_unique();
+    for marketplace_id in [0, 1] {
+        assert_eq!(
+            pda::passes_balance(&passes::ID, marketplace_id, &owner, &holder),
+            passes::gate::passes_balance_address(marketplace_id, &owner, &holder)
+        );
+        assert_eq!(
+            pda::config(&passes::ID, marketplace_id),
+            passes::gate::config_address(marketplace_id)
+        );
+    }
+    assert_ne!(
+        pda::passes_balance(&passes::ID, 0, &owner, &holder),
+        pda::passes_balance(&passes::ID, 0, &holder, &owner)
+    );
+    assert_ne!(
+        pda::holder_page(&passes::ID, 0, &owner, 0),
+        pda::holder_page(&passes::ID, 0, &owner, 1)
+    );
+
+    // the default marketplace keeps the addresses from before marketplaces
+    assert_eq!(
+        pda::passes_supply(&passes::ID, 0, &owner),
+        Pubkey::find_program_address(&[b"supply", owner.as_ref()], &passes::ID).0
+    );
+    assert_ne!(
+        pda::passes_supply(&passes::ID, 0, &owner),
+        pda::passes_supply(&passes::ID, 1, &owner)
+    );
+    assert_ne!(
+        pda::escrow_sol_wallet(&passes::ID, 0),
+        pda::escrow_sol_wallet(&passes::ID, 1)
+    );
+}
+
+#[test]
+fn test_price_matches_program() {
+    assert_eq!(quote::price(Currency::Token, 0, 1).unwrap(), 0);
+    for supply in [1, 2, 10, 100, 1_000, 5_000] {
+        for amount in [1, 2, 5, 50] {
+            assert_eq!(
+                quote::price(Currency::Token, supply, amount).unwrap(),
+                passes::calc_price(supply, amount).unwrap()
+            );
+            assert_eq!(
+                quote::price(Currency::Sol, supply, amount).unwrap(),
+                passes::calc_price_sol(supply, amount).unwrap()
+            );
+        }
+    }
+}
+
+#[test]
+fn test_quote_buy_and_sell() {
+    let config = config(100, 100);
+
+    let quote = quote::quote_buy(&config, &passes_supply(1), None, Currency::Sol, 10).unwrap();
+    assert_eq!(quote.price, 240_625_000);
+    assert_eq!(quote.protocol_fees, 2_406_250);
+    assert_eq!(quote.owner_fees, 2_406_250);
+    assert_eq!(quote.total, 240_625_000 + 2 * 2_406_250);
+
+    let quote = quote::quote_sell(
+        &config,
+        &passes_supply(11),
+        None,
+        &passes_balance(5),
+        false,
+        None,
+        Currency::Sol,
+        5,
+        0,
+    )
+    .unwrap();
+    assert_eq!(quote.price, passes::curve_price_sol(6, 5).unwrap());
+    assert_eq!(
+        quote.total,
+        quote.price - quote.protocol_fees - quote.owner_fees
+    );
+
+    // the holders' share comes out of the owner fees
+    let mut rewarded = passes_supply(11);
+    rewarded.rewards = true;
+    let mut reward_pool: RewardPool = zeroed(RewardPool::LEN);
+    reward_pool.holder_fee_bps = 5_000;
+    let quote =
+        quote::quote_buy(&config, &rewarded, Some(&reward_pool), Currency::Token, 1).unwrap();
+    assert_eq!(
+        quote.holder_fees + quote.owner_fees,
+        quote::quote_buy(&config, &passes_supply(11), None, Currency::Token, 1)
+            .unwrap()
+            .owner_fees
+    );
+    assert!(quote.holder_fees > 0);
+
+    // sells within the holding period pay the early exit fee
+    let mut held = passes_supply(11);
+    held.min_hold_secs = 100;
+    held.early_exit_fee_bps = 1_000;
+    let mut passes_balance = passes_balance(1);
+    passes_balance.last_buy_ts = 1_000;
+    let early = quote::quote_sell(
+        &config,
+        &held,
+        None,
+        &passes_balance,
+        false,
+        None,
+        Currency::Token,
+        1,
+        1_050,
+    )
+    .unwrap();
+    assert_eq!(early.early_exit_fees, early.price / 10);
+    let late = quote::quote_sell(
+        &config,
+        &held,
+        None,
+        &passes_balance,
+        false,
+        None,
+        Currency::Token,
+        1,
+        1_100,
+    )
+    .unwrap();
+    assert_eq!(late.early_exit_fees, 0);
+    assert_eq!(late.total, early.total + early.early_exit_fees);
+}
+
+#[test]
+fn test_quote_sell_errors() {
+    let config = config(100, 100);
+    let owner_sells = |passes_supply: &PassesSupply, owner_vesting, amount, now| {
+        quote::quote_sell(
+            &config,
+            passes_supply,
+            None,
+            &passes_balance(5),
+            true,
+            owner_vesting,
+            Currency::Token,
+            amount,
+            now,
+        )
+    };
+    let sells = |passes_supply: &PassesSupply, passes_balance: &PassesBalance, amount| {
+        quote::quote_sell(
+            &config,
+            passes_supply,
+            None,
+            passes_balance,
+            false,
+            None,
+            Currency::Token,
+            amount,
+            0,
+        )
+    };
+
+    // the checks run in the sell instruction's order
+    let mut paused = passes_supply(11);
+    paused.paused = true;
+    assert_passes_error(sells(&paused, &passes_balance(5), 1), PassesError::Paused);
+    assert_passes_error(
+        sells(&passes_supply(5), &passes_balance(5), 5),
+        PassesError::LastPass,
+    );
+    let mut sol_market = passes_supply(11);
+    sol_market.currency = MarketCurrency::Sol;
+    assert_passes_error(
+        sells(&sol_market, &passes_balance(5), 1),
+        PassesError::CurrencyMismatch,
+    );
+    assert_passes_error(
+        sells(&passes_supply(11), &passes_balance(5), 6),
+        PassesError::InsufficientPasses,
+    );
+    let mut staked = passes_balance(5);
+    staked.staked_amount = 3;
+    assert_passes_error(
+        sells(&passes_supply(11), &staked, 3),
+        PassesError::PassesStaked,
+    );
+
+    // the owner's issued passes vest and stay locked
+    let mut vesting = passes_supply(11);
+    vesting.vesting = true;
+    vesting.issued_amount = 2;
+    assert_passes_error(
+        owner_sells(&vesting, None, 1, 0),
+        PassesError::VestingAccountMissing,
+    );
+    let mut owner_vesting: OwnerVesting = zeroed(OwnerVesting::LEN);
+    owner_vesting.amount = 2;
+    owner_vesting.end_ts = 100;
+    assert_passes_error(
+        owner_sells(&vesting, Some(&owner_vesting), 4, 0),
+        PassesError::PassesVesting,
+    );
+    assert_passes_error(
+        owner_sells(&vesting, Some(&owner_vesting), 4, 100),
+        PassesError::IssuedPassesLocked,
+    );
+    assert!(owner_sells(&vesting, Some(&owner_vesting), 3, 100).is_ok());
+
+    // markets sharing rewards need their pool
+    let mut rewarded = passes_supply(11);
+    rewarded.rewards = true;
+    assert_passes_error(
+        sells(&rewarded, &passes_balance(5), 1),
+        PassesError::RewardPoolMissing,
+    );
+
+    // sells within the holding period fail without an early exit fee
+    let mut held = passes_supply(11);
+    held.min_hold_secs = 100;
+    assert_passes_error(
+        sells(&held, &passes_balance(5), 1),
+        PassesError::HoldingPeriodNotOver,
+    );
+}
+
+#[test]
+fn test_instructions() {
+    let program_id = Pubkey::new_unique();
+    let buyer = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let fee_wallet = Pubkey::new_unique();
+
+    // missing optional accounts point at the program
+    let ix = instructions::buy_passes_sol(
+        &program_id,
+        0,
+        &buyer,
+        &owner,
+        &fee_wallet,
+        1,
+        vec![],
+        None,
+        MarketOptions::default(),
+    );
+    assert_eq!(ix.program_id, program_id);
+    assert_eq!(
+        ix.accounts[2].pubkey,
+        pda::passes_balance(&program_id, 0, &owner, &buyer)
+    );
+    assert_eq!(ix.accounts[3].pubkey, program_id);
+    assert_eq!(ix.accounts[4].pubkey, program_id);
+    assert!(!ix.accounts[4].is_writable);
+    assert_eq!(ix.accounts[7].pubkey, program_id);
+    assert!(ix.accounts.iter().all(|meta| meta.pubkey != passes::ID));
+
+    let ix = instructions::buy_passes_sol(
+        &program_id,
+        1,
+        &buyer,
+        &owner,
+        &fee_wallet,
+        1,
+        vec![],
+        Some(2),
+        MarketOptions {
+            vesting: false,
+            rewards: true,
+            isolated_reserve: true,
+        },
+    );
+    // buys into a holder page pass it instead of the balance
+    assert_eq!(ix.accounts[2].pubkey, program_id);
+    assert_eq!(
+        ix.accounts[3].pubkey,
+        pda::holder_page(&program_id, 1, &owner, 2)
+    );
+    assert!(ix.accounts[3].is_writable);
+    assert_eq!(
+        ix.accounts[4].pubkey,
+        pda::reward_pool(&program_id, 1, &owner)
+    );
+    assert!(ix
+        .accounts
+        .iter()
+        .any(|meta| meta.pubkey == pda::config(&program_id, 1)));
+    assert!(ix.accounts[4].is_writable);
+    assert_eq!(
+        ix.accounts[7].pubkey,
+        pda::reserve_sol_wallet(&program_id, 1, &owner)
+    );
+    assert!(ix.accounts[7].is_writable);
+
+    // airdrop recipients follow the named accounts in pairs
+    let recipient = Pubkey::new_unique();
+    let ix = instructions::airdrop_passes(
+        &program_id,
+        0,
+        &owner,
+        &[(recipient, 2)],
+        MarketOptions::default(),
+    );
+    let len = ix.accounts.len();
+    assert_eq!(ix.accounts[len - 2].pubkey, recipient);
+    assert_eq!(
+        ix.accounts[len - 1].pubkey,
+        pda::passes_balance(&program_id, 0, &owner, &recipient)
+    );
+    assert!(ix.accounts[len - 1].is_writable);
+}
+
+#[test]
+fn test_decode() {
+    let address = Pubkey::new_unique();
+    let mut data = vec![0; PassesSupply::LEN];
+    assert!(matches!(
+        decode::<PassesSupply>(&address, &data),
+        Err(ClientError::InvalidAccount(key, _)) if key == address
+    ));
+    data[..8].copy_from_slice(&PassesSupply::DISCRIMINATOR);
+    data[9] = 7;
+    assert_eq!(decode::<PassesSupply>(&address, &data).unwrap().amount, 7);
+
+    let mut data = vec![0; 8 + std::mem::size_of::<HolderPage>()];
+    assert!(decode_holder_page(&address, &data).is_err());
+    data[..8].copy_from_slice(&HolderPage::DISCRIMINATOR);
+    assert!(decode_holder_page(&address, &data).is_ok());
+}
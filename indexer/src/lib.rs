@@ -0,0 +1,56 @@
+//! Indexer of the passes program: replays program transactions, oldest first,
+//! and keeps per-market supply, holders, trades, fee totals and price candles in SQLite.
+
+pub mod logs;
+pub mod source;
+pub mod store;
+
+#[cfg(test)]
+mod tests;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::store::Store;
+
+/// A successful program transaction, as fetched from a ledger or recorded in a fixture file
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct TransactionRecord {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub logs: Vec<String>,
+}
+
+pub struct Indexer {
+    pub program_id: Pubkey,
+    pub store: Store,
+}
+
+impl Indexer {
+    pub fn new(program_id: Pubkey, store: Store) -> Self {
+        Self { program_id, store }
+    }
+
+    /// Index a transaction, returns false when it was already indexed
+    pub fn process(&mut self, record: &TransactionRecord) -> Result<bool> {
+        let events = logs::parse_logs(&self.program_id, &record.logs)
+            .with_context(|| format!("failed to decode transaction {}", record.signature))?;
+        self.store.apply(record, &events)
+    }
+
+    /// Index transactions in order, returns how many were new
+    pub fn replay<'a>(
+        &mut self,
+        records: impl IntoIterator<Item = &'a TransactionRecord>,
+    ) -> Result<usize> {
+        let mut count = 0;
+        for record in records {
+            if self.process(record)? {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+}
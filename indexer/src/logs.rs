@@ -0,0 +1,174 @@
+//! Decoding of the program's transaction logs into market events
+
+use std::{collections::HashMap, str::FromStr};
+
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use anyhow::{anyhow, Context, Result};
+use base64::Engine;
+use passes_client::{
+    passes::{events::PassesTraded, state::TradeSide},
+    quote::Currency,
+};
+use solana_sdk::pubkey::Pubkey;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+/// A buy or sell, amounts in token base units or lamports
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trade {
+    pub marketplace_id: u64,
+    pub owner: Pubkey,
+    pub trader: Pubkey,
+    pub side: Side,
+    pub currency: Currency,
+    pub amount: u64,
+    pub price: u64,
+    pub protocol_fees: u64,
+    pub owner_fees: u64,
+    pub holder_fees: u64,
+    pub early_exit_fees: u64,
+    /// Market supply after the trade
+    pub supply: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// The owner issued the first passes of their market
+    Issue {
+        marketplace_id: u64,
+        owner: Pubkey,
+        amount: u64,
+    },
+    Trade(Trade),
+    /// The owner gifted passes from their balance
+    Airdrop {
+        marketplace_id: u64,
+        owner: Pubkey,
+        recipient: Pubkey,
+        amount: u64,
+    },
+}
+
+/// Decode the events of `program_id` from the logs of a successful transaction.
+/// Trades come from the `PassesTraded` events, issues and airdrops from the log lines.
+pub fn parse_logs(program_id: &Pubkey, logs: &[String]) -> Result<Vec<Event>> {
+    let program = program_id.to_string();
+    let mut events = vec![];
+    // invoked programs, the innermost last
+    let mut stack: Vec<&str> = vec![];
+    for log in logs {
+        if let Some(data) = log.strip_prefix("Program data: ") {
+            if stack.last() == Some(&program.as_str()) {
+                events.extend(decode_trade(data)?.map(Event::Trade));
+            }
+            continue;
+        }
+        if let Some(rest) = log.strip_prefix("Program ") {
+            if let Some((id, _)) = rest.split_once(" invoke [") {
+                stack.push(id);
+                continue;
+            }
+            if rest.ends_with(" success") || rest.contains(" failed") {
+                stack.pop();
+                continue;
+            }
+        }
+        if stack.last() != Some(&program.as_str()) {
+            continue;
+        }
+        let Some(message) = log.strip_prefix("Program log: ") else {
+            continue;
+        };
+        let Some((kind, body)) = message.split_once(": ") else {
+            continue;
+        };
+        let fields = Fields::parse(body);
+        match kind {
+            "Issue passes" => events.push(Event::Issue {
+                marketplace_id: fields.u64("marketplace_id")?,
+                owner: fields.pubkey("owner")?,
+                amount: fields.u64("amount")?,
+            }),
+            "Airdrop passes" if fields.has("recipient") => events.push(Event::Airdrop {
+                marketplace_id: fields.u64("marketplace_id")?,
+                owner: fields.pubkey("owner")?,
+                recipient: fields.pubkey("recipient")?,
+                amount: fields.u64("amount")?,
+            }),
+            _ => {}
+        }
+    }
+    Ok(events)
+}
+
+// A "Program data:" line holding a `PassesTraded` event, other events are skipped
+fn decode_trade(data: &str) -> Result<Option<Trade>> {
+    let data = base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .with_context(|| format!("invalid program data {}", data))?;
+    if !data.starts_with(&PassesTraded::DISCRIMINATOR) {
+        return Ok(None);
+    }
+    let event = PassesTraded::try_from_slice(&data[8..]).context("invalid trade event")?;
+    Ok(Some(Trade {
+        marketplace_id: event.marketplace_id,
+        owner: event.owner,
+        trader: event.trader,
+        side: match event.side {
+            TradeSide::Buy => Side::Buy,
+            TradeSide::Sell => Side::Sell,
+        },
+        currency: if event.sol {
+            Currency::Sol
+        } else {
+            Currency::Token
+        },
+        amount: event.amount,
+        price: event.price,
+        protocol_fees: event.protocol_fees,
+        owner_fees: event.owner_fees,
+        holder_fees: event.holder_fees,
+        early_exit_fees: event.early_exit_fees,
+        supply: event.supply,
+    }))
+}
+
+// The "name value, name value" fields of a log line
+struct Fields<'a>(HashMap<&'a str, &'a str>);
+
+impl<'a> Fields<'a> {
+    fn parse(body: &'a str) -> Self {
+        Self(
+            body.split(", ")
+                .filter_map(|field| field.rsplit_once(' '))
+                .collect(),
+        )
+    }
+
+    fn has(&self, name: &str) -> bool {
+        self.0.contains_key(name)
+    }
+
+    fn get(&self, name: &str) -> Result<&'a str> {
+        self.0
+            .get(name)
+            .copied()
+            .ok_or_else(|| anyhow!("missing log field {}", name))
+    }
+
+    fn u64(&self, name: &str) -> Result<u64> {
+        let value = self.get(name)?;
+        value
+            .parse()
+            .with_context(|| format!("invalid {} {}", name, value))
+    }
+
+    fn pubkey(&self, name: &str) -> Result<Pubkey> {
+        let value = self.get(name)?;
+        Pubkey::from_str(value).with_context(|| format!("invalid {} {}", name, value))
+    }
+}
@@ -0,0 +1,91 @@
+//! Index passes program transactions into SQLite and query the markets
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use passes_client::quote::Currency;
+use passes_indexer::{source, store::Store, Indexer};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+
+#[derive(Debug, Parser)]
+#[clap(name = "passes-indexer", about = "Index passes program transactions")]
+struct Cli {
+    /// SQLite database file
+    #[clap(long, global = true, default_value = "passes-index.db")]
+    db: String,
+    /// Program id of the deployment
+    #[clap(long, global = true, default_value_t = passes_client::passes::ID)]
+    program_id: Pubkey,
+    /// Marketplace of the queried markets
+    #[clap(long, global = true, default_value_t = 0)]
+    marketplace: u64,
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Replay recorded transaction files, one JSON record per line
+    Replay { files: Vec<String> },
+    /// Fetch and index the program's transactions since the last indexed one
+    Sync {
+        #[clap(long, short, default_value = "http://127.0.0.1:8899")]
+        url: String,
+    },
+    /// Print a market's supply, fee totals and holders
+    Market { owner: Pubkey },
+    /// Print the markets a wallet holds passes of, in every marketplace
+    Holdings { holder: Pubkey },
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let mut indexer = Indexer::new(cli.program_id, Store::open(&cli.db)?);
+    match cli.command {
+        Command::Replay { files } => {
+            for file in files {
+                let records = source::read_fixture(&file)?;
+                let count = indexer.replay(&records)?;
+                println!(
+                    "{}: indexed {} of {} transactions",
+                    file,
+                    count,
+                    records.len()
+                );
+            }
+        }
+        Command::Sync { url } => {
+            let rpc = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+            let until = indexer.store.last_signature()?;
+            let records = source::fetch_transactions(&rpc, &cli.program_id, until.as_deref())?;
+            let count = indexer.replay(&records)?;
+            println!("Indexed {} transactions", count);
+        }
+        Command::Market { owner } => {
+            let Some(market) = indexer.store.market(cli.marketplace, &owner)? else {
+                println!(
+                    "{} has no indexed market in marketplace {}",
+                    owner, cli.marketplace
+                );
+                return Ok(());
+            };
+            println!("{:#?}", market);
+            for currency in [Currency::Token, Currency::Sol] {
+                println!(
+                    "{:?} {:#?}",
+                    currency,
+                    indexer.store.fees(cli.marketplace, &owner, currency)?
+                );
+            }
+            for (holder, amount) in indexer.store.holders(cli.marketplace, &owner)? {
+                println!("{} {}", holder, amount);
+            }
+        }
+        Command::Holdings { holder } => {
+            for (marketplace_id, owner, amount) in indexer.store.holdings(&holder)? {
+                println!("{} {} {}", marketplace_id, owner, amount);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,76 @@
+//! Sources of program transactions: recorded fixture files and an RPC node
+
+use std::{fs, path::Path, str::FromStr};
+
+use anyhow::{Context, Result};
+use solana_client::{
+    rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
+    rpc_config::RpcTransactionConfig,
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::{option_serializer::OptionSerializer, UiTransactionEncoding};
+
+use crate::TransactionRecord;
+
+/// Read a fixture file, one JSON transaction record per line
+pub fn read_fixture(path: impl AsRef<Path>) -> Result<Vec<TransactionRecord>> {
+    let path = path.as_ref();
+    let data = fs::read_to_string(path).with_context(|| format!("failed to read {:?}", path))?;
+    parse_fixture(&data).with_context(|| format!("invalid fixture {:?}", path))
+}
+
+pub fn parse_fixture(data: &str) -> Result<Vec<TransactionRecord>> {
+    data.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Ok(serde_json::from_str(line)?))
+        .collect()
+}
+
+/// Fetch the successful transactions of the program after `until`, oldest first
+pub fn fetch_transactions(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    until: Option<&str>,
+) -> Result<Vec<TransactionRecord>> {
+    let until = until.map(Signature::from_str).transpose()?;
+    let mut signatures = vec![];
+    let mut before = None;
+    loop {
+        let config = GetConfirmedSignaturesForAddress2Config {
+            before,
+            until,
+            limit: None,
+            commitment: Some(rpc.commitment()),
+        };
+        let page = rpc.get_signatures_for_address_with_config(program_id, config)?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        before = Some(Signature::from_str(&last.signature)?);
+        signatures.extend(page.into_iter().filter(|status| status.err.is_none()));
+    }
+
+    let config = RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Json),
+        commitment: Some(rpc.commitment()),
+        max_supported_transaction_version: Some(0),
+    };
+    signatures
+        .into_iter()
+        .rev()
+        .map(|status| {
+            let signature = Signature::from_str(&status.signature)?;
+            let tx = rpc.get_transaction_with_config(&signature, config)?;
+            let logs = match tx.transaction.meta.map(|meta| meta.log_messages) {
+                Some(OptionSerializer::Some(logs)) => logs,
+                _ => vec![],
+            };
+            Ok(TransactionRecord {
+                signature: status.signature,
+                slot: tx.slot,
+                block_time: tx.block_time,
+                logs,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,484 @@
+//! SQLite store of the indexed markets
+
+use std::{path::Path, str::FromStr};
+
+use anyhow::Result;
+use passes_client::quote::Currency;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    logs::{Event, Side, Trade},
+    TransactionRecord,
+};
+
+/// Length of the price candles in seconds
+pub const CANDLE_SECS: i64 = 3_600;
+
+// Amounts are stored as SQLite integers, which are i64
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS transactions (
+    signature TEXT PRIMARY KEY,
+    slot INTEGER NOT NULL,
+    block_time INTEGER
+);
+CREATE TABLE IF NOT EXISTS markets (
+    marketplace_id INTEGER NOT NULL,
+    owner TEXT NOT NULL,
+    supply INTEGER NOT NULL,
+    trade_count INTEGER NOT NULL DEFAULT 0,
+    issued_slot INTEGER NOT NULL,
+    PRIMARY KEY (marketplace_id, owner)
+);
+CREATE TABLE IF NOT EXISTS holders (
+    marketplace_id INTEGER NOT NULL,
+    owner TEXT NOT NULL,
+    holder TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    PRIMARY KEY (marketplace_id, owner, holder)
+);
+CREATE TABLE IF NOT EXISTS trades (
+    marketplace_id INTEGER NOT NULL,
+    signature TEXT NOT NULL,
+    idx INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    owner TEXT NOT NULL,
+    trader TEXT NOT NULL,
+    side TEXT NOT NULL,
+    currency TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    price INTEGER NOT NULL,
+    protocol_fees INTEGER NOT NULL,
+    owner_fees INTEGER NOT NULL,
+    holder_fees INTEGER NOT NULL,
+    early_exit_fees INTEGER NOT NULL,
+    supply INTEGER NOT NULL,
+    PRIMARY KEY (marketplace_id, signature, idx)
+);
+CREATE INDEX IF NOT EXISTS trades_owner ON trades (marketplace_id, owner, slot);
+CREATE TABLE IF NOT EXISTS fees (
+    marketplace_id INTEGER NOT NULL,
+    owner TEXT NOT NULL,
+    currency TEXT NOT NULL,
+    volume INTEGER NOT NULL,
+    protocol_fees INTEGER NOT NULL,
+    owner_fees INTEGER NOT NULL,
+    holder_fees INTEGER NOT NULL,
+    early_exit_fees INTEGER NOT NULL,
+    PRIMARY KEY (marketplace_id, owner, currency)
+);
+CREATE TABLE IF NOT EXISTS candles (
+    marketplace_id INTEGER NOT NULL,
+    owner TEXT NOT NULL,
+    currency TEXT NOT NULL,
+    start_ts INTEGER NOT NULL,
+    open INTEGER NOT NULL,
+    high INTEGER NOT NULL,
+    low INTEGER NOT NULL,
+    close INTEGER NOT NULL,
+    volume INTEGER NOT NULL,
+    PRIMARY KEY (marketplace_id, owner, currency, start_ts)
+);
+";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Market {
+    pub marketplace_id: u64,
+    pub owner: Pubkey,
+    pub supply: u64,
+    pub trade_count: u64,
+    pub issued_slot: u64,
+}
+
+/// Trade volume and fees of a market in one currency
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Fees {
+    pub volume: u64,
+    pub protocol_fees: u64,
+    pub owner_fees: u64,
+    pub holder_fees: u64,
+    pub early_exit_fees: u64,
+}
+
+/// Price per pass over a candle, `volume` is the number of passes traded
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Candle {
+    pub start_ts: i64,
+    pub open: u64,
+    pub high: u64,
+    pub low: u64,
+    pub close: u64,
+    pub volume: u64,
+}
+
+/// A trade as stored, with the transaction it came from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TradeRecord {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub trade: Trade,
+}
+
+pub struct Store {
+    conn: Connection,
+}
+
+fn currency_name(currency: Currency) -> &'static str {
+    match currency {
+        Currency::Token => "token",
+        Currency::Sol => "sol",
+    }
+}
+
+fn pubkey(value: String) -> rusqlite::Result<Pubkey> {
+    Pubkey::from_str(&value).map_err(|err| {
+        rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(err))
+    })
+}
+
+impl Store {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// Apply the events of a transaction, returns false when it was already indexed
+    pub fn apply(&mut self, record: &TransactionRecord, events: &[Event]) -> Result<bool> {
+        let tx = self.conn.transaction()?;
+        let inserted = tx.execute(
+            "INSERT OR IGNORE INTO transactions (signature, slot, block_time) VALUES (?1, ?2, ?3)",
+            params![record.signature, record.slot as i64, record.block_time],
+        )?;
+        if inserted == 0 {
+            return Ok(false);
+        }
+        for (idx, event) in events.iter().enumerate() {
+            match event {
+                Event::Issue {
+                    marketplace_id,
+                    owner,
+                    amount,
+                } => {
+                    tx.execute(
+                        "INSERT OR REPLACE INTO markets (marketplace_id, owner, supply, trade_count, issued_slot)
+                        VALUES (?1, ?2, ?3, 0, ?4)",
+                        params![
+                            *marketplace_id as i64,
+                            owner.to_string(),
+                            *amount as i64,
+                            record.slot as i64
+                        ],
+                    )?;
+                    add_holding(&tx, *marketplace_id, owner, owner, *amount as i64)?;
+                }
+                Event::Airdrop {
+                    marketplace_id,
+                    owner,
+                    recipient,
+                    amount,
+                } => {
+                    add_holding(&tx, *marketplace_id, owner, owner, -(*amount as i64))?;
+                    add_holding(&tx, *marketplace_id, owner, recipient, *amount as i64)?;
+                }
+                Event::Trade(trade) => apply_trade(&tx, record, idx, trade)?,
+            }
+        }
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// Signature of the latest indexed transaction, where a sync resumes from
+    pub fn last_signature(&self) -> Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT signature FROM transactions ORDER BY slot DESC, rowid DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    pub fn market(&self, marketplace_id: u64, owner: &Pubkey) -> Result<Option<Market>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT supply, trade_count, issued_slot FROM markets
+                WHERE marketplace_id = ?1 AND owner = ?2",
+                params![marketplace_id as i64, owner.to_string()],
+                |row| {
+                    Ok(Market {
+                        marketplace_id,
+                        owner: *owner,
+                        supply: row.get::<_, i64>(0)? as u64,
+                        trade_count: row.get::<_, i64>(1)? as u64,
+                        issued_slot: row.get::<_, i64>(2)? as u64,
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    /// Holders of a market with a positive balance, largest first
+    pub fn holders(&self, marketplace_id: u64, owner: &Pubkey) -> Result<Vec<(Pubkey, u64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT holder, amount FROM holders
+            WHERE marketplace_id = ?1 AND owner = ?2 AND amount > 0 ORDER BY amount DESC, holder",
+        )?;
+        let holders = stmt
+            .query_map(params![marketplace_id as i64, owner.to_string()], |row| {
+                Ok((pubkey(row.get(0)?)?, row.get::<_, i64>(1)? as u64))
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(holders)
+    }
+
+    /// Markets a wallet holds passes of in every marketplace, as marketplace id, owner and balance
+    pub fn holdings(&self, holder: &Pubkey) -> Result<Vec<(u64, Pubkey, u64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT marketplace_id, owner, amount FROM holders
+            WHERE holder = ?1 AND amount > 0 ORDER BY amount DESC, marketplace_id, owner",
+        )?;
+        let holdings = stmt
+            .query_map(params![holder.to_string()], |row| {
+                Ok((
+                    row.get::<_, i64>(0)? as u64,
+                    pubkey(row.get(1)?)?,
+                    row.get::<_, i64>(2)? as u64,
+                ))
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(holdings)
+    }
+
+    /// Trades of a market, oldest first
+    pub fn trades(&self, marketplace_id: u64, owner: &Pubkey) -> Result<Vec<TradeRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT signature, slot, block_time, trader, side, currency, amount, price, protocol_fees,
+                owner_fees, holder_fees, early_exit_fees, supply
+            FROM trades WHERE marketplace_id = ?1 AND owner = ?2 ORDER BY slot, signature, idx",
+        )?;
+        let trades = stmt
+            .query_map(params![marketplace_id as i64, owner.to_string()], |row| {
+                let side: String = row.get(4)?;
+                let currency: String = row.get(5)?;
+                Ok(TradeRecord {
+                    signature: row.get(0)?,
+                    slot: row.get::<_, i64>(1)? as u64,
+                    block_time: row.get(2)?,
+                    trade: Trade {
+                        marketplace_id,
+                        owner: *owner,
+                        trader: pubkey(row.get(3)?)?,
+                        side: if side == "buy" { Side::Buy } else { Side::Sell },
+                        currency: if currency == "sol" {
+                            Currency::Sol
+                        } else {
+                            Currency::Token
+                        },
+                        amount: row.get::<_, i64>(6)? as u64,
+                        price: row.get::<_, i64>(7)? as u64,
+                        protocol_fees: row.get::<_, i64>(8)? as u64,
+                        owner_fees: row.get::<_, i64>(9)? as u64,
+                        holder_fees: row.get::<_, i64>(10)? as u64,
+                        early_exit_fees: row.get::<_, i64>(11)? as u64,
+                        supply: row.get::<_, i64>(12)? as u64,
+                    },
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(trades)
+    }
+
+    pub fn fees(&self, marketplace_id: u64, owner: &Pubkey, currency: Currency) -> Result<Fees> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT volume, protocol_fees, owner_fees, holder_fees, early_exit_fees
+                FROM fees WHERE marketplace_id = ?1 AND owner = ?2 AND currency = ?3",
+                params![
+                    marketplace_id as i64,
+                    owner.to_string(),
+                    currency_name(currency)
+                ],
+                |row| {
+                    Ok(Fees {
+                        volume: row.get::<_, i64>(0)? as u64,
+                        protocol_fees: row.get::<_, i64>(1)? as u64,
+                        owner_fees: row.get::<_, i64>(2)? as u64,
+                        holder_fees: row.get::<_, i64>(3)? as u64,
+                        early_exit_fees: row.get::<_, i64>(4)? as u64,
+                    })
+                },
+            )
+            .optional()?
+            .unwrap_or_default())
+    }
+
+    /// Price candles of a market, oldest first
+    pub fn candles(
+        &self,
+        marketplace_id: u64,
+        owner: &Pubkey,
+        currency: Currency,
+    ) -> Result<Vec<Candle>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT start_ts, open, high, low, close, volume FROM candles
+            WHERE marketplace_id = ?1 AND owner = ?2 AND currency = ?3 ORDER BY start_ts",
+        )?;
+        let params = params![
+            marketplace_id as i64,
+            owner.to_string(),
+            currency_name(currency)
+        ];
+        let candles = stmt
+            .query_map(params, |row| {
+                Ok(Candle {
+                    start_ts: row.get(0)?,
+                    open: row.get::<_, i64>(1)? as u64,
+                    high: row.get::<_, i64>(2)? as u64,
+                    low: row.get::<_, i64>(3)? as u64,
+                    close: row.get::<_, i64>(4)? as u64,
+                    volume: row.get::<_, i64>(5)? as u64,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(candles)
+    }
+}
+
+fn add_holding(
+    tx: &Transaction,
+    marketplace_id: u64,
+    owner: &Pubkey,
+    holder: &Pubkey,
+    amount: i64,
+) -> Result<()> {
+    tx.execute(
+        "INSERT INTO holders (marketplace_id, owner, holder, amount) VALUES (?1, ?2, ?3, ?4)
+        ON CONFLICT (marketplace_id, owner, holder) DO UPDATE SET amount = amount + excluded.amount",
+        params![marketplace_id as i64, owner.to_string(), holder.to_string(), amount],
+    )?;
+    Ok(())
+}
+
+fn apply_trade(
+    tx: &Transaction,
+    record: &TransactionRecord,
+    idx: usize,
+    trade: &Trade,
+) -> Result<()> {
+    let marketplace_id = trade.marketplace_id as i64;
+    let owner = trade.owner.to_string();
+    let currency = currency_name(trade.currency);
+    let side = match trade.side {
+        Side::Buy => "buy",
+        Side::Sell => "sell",
+    };
+    tx.execute(
+        "INSERT INTO trades (marketplace_id, signature, idx, slot, block_time, owner, trader, side,
+            currency, amount, price, protocol_fees, owner_fees, holder_fees, early_exit_fees, supply)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+        params![
+            marketplace_id,
+            record.signature,
+            idx as i64,
+            record.slot as i64,
+            record.block_time,
+            owner,
+            trade.trader.to_string(),
+            side,
+            currency,
+            trade.amount as i64,
+            trade.price as i64,
+            trade.protocol_fees as i64,
+            trade.owner_fees as i64,
+            trade.holder_fees as i64,
+            trade.early_exit_fees as i64,
+            trade.supply as i64,
+        ],
+    )?;
+
+    // markets issued before the indexed history start at the first trade
+    tx.execute(
+        "INSERT INTO markets (marketplace_id, owner, supply, trade_count, issued_slot)
+        VALUES (?1, ?2, ?3, 1, ?4)
+        ON CONFLICT (marketplace_id, owner) DO UPDATE SET
+            supply = excluded.supply,
+            trade_count = trade_count + 1",
+        params![
+            marketplace_id,
+            owner,
+            trade.supply as i64,
+            record.slot as i64
+        ],
+    )?;
+    let amount = match trade.side {
+        Side::Buy => trade.amount as i64,
+        Side::Sell => -(trade.amount as i64),
+    };
+    add_holding(
+        tx,
+        trade.marketplace_id,
+        &trade.owner,
+        &trade.trader,
+        amount,
+    )?;
+
+    tx.execute(
+        "INSERT INTO fees (marketplace_id, owner, currency, volume, protocol_fees, owner_fees,
+            holder_fees, early_exit_fees)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+        ON CONFLICT (marketplace_id, owner, currency) DO UPDATE SET
+            volume = volume + excluded.volume,
+            protocol_fees = protocol_fees + excluded.protocol_fees,
+            owner_fees = owner_fees + excluded.owner_fees,
+            holder_fees = holder_fees + excluded.holder_fees,
+            early_exit_fees = early_exit_fees + excluded.early_exit_fees",
+        params![
+            marketplace_id,
+            owner,
+            currency,
+            trade.price as i64,
+            trade.protocol_fees as i64,
+            trade.owner_fees as i64,
+            trade.holder_fees as i64,
+            trade.early_exit_fees as i64,
+        ],
+    )?;
+
+    // candles need the block time, the price is the average per pass of the trade
+    if let Some(block_time) = record.block_time {
+        let start_ts = block_time - block_time.rem_euclid(CANDLE_SECS);
+        let price = (trade.price / trade.amount.max(1)) as i64;
+        tx.execute(
+            "INSERT INTO candles (marketplace_id, owner, currency, start_ts, open, high, low, close,
+                volume)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?5, ?5, ?5, ?6)
+            ON CONFLICT (marketplace_id, owner, currency, start_ts) DO UPDATE SET
+                high = max(high, excluded.high),
+                low = min(low, excluded.low),
+                close = excluded.close,
+                volume = volume + excluded.volume",
+            params![
+                marketplace_id,
+                owner,
+                currency,
+                start_ts,
+                price,
+                trade.amount as i64
+            ],
+        )?;
+    }
+    Ok(())
+}
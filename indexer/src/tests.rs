@@ -0,0 +1,283 @@
+use std::str::FromStr;
+
+use passes_client::quote::Currency;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    logs::{parse_logs, Event, Side},
+    source::parse_fixture,
+    store::{Candle, Fees, Store},
+    Indexer, TransactionRecord,
+};
+
+// Program logs recorded from the program tests
+const TRADES: &str = include_str!("../fixtures/trades.jsonl");
+const REWARDS: &str = include_str!("../fixtures/rewards.jsonl");
+
+fn key(value: &str) -> Pubkey {
+    Pubkey::from_str(value).unwrap()
+}
+
+fn indexer(fixture: &str) -> (Indexer, Vec<TransactionRecord>) {
+    let records = parse_fixture(fixture).unwrap();
+    let mut indexer = Indexer::new(passes_client::passes::ID, Store::open_in_memory().unwrap());
+    assert_eq!(indexer.replay(&records).unwrap(), records.len());
+    (indexer, records)
+}
+
+#[test]
+fn test_parse_logs() {
+    let records = parse_fixture(TRADES).unwrap();
+    let program_id = passes_client::passes::ID;
+
+    // init and settings don't change markets
+    assert!(parse_logs(&program_id, &records[0].logs)
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        parse_logs(&program_id, &records[1].logs).unwrap(),
+        vec![Event::Issue {
+            marketplace_id: 0,
+            owner: key("BXtVvvHJ1F6qPsWtTjYW9m5aAdkc5ZRAa7rcSEHsL91W"),
+            amount: 1
+        }]
+    );
+
+    let events = parse_logs(&program_id, &records[3].logs).unwrap();
+    let Event::Trade(trade) = &events[0] else {
+        panic!("unexpected event {:?}", events[0]);
+    };
+    assert_eq!(trade.side, Side::Sell);
+    assert_eq!(trade.currency, Currency::Token);
+    assert_eq!(
+        trade.trader,
+        key("GTx19H1NXBnbfZpS4u7eyHXJQtCBDZBmHWVyszzcEPwn")
+    );
+    assert_eq!(
+        (trade.amount, trade.price, trade.protocol_fees, trade.supply),
+        (5, 2_062_500, 20_625, 6)
+    );
+
+    let events = parse_logs(&program_id, &records[5].logs).unwrap();
+    assert!(matches!(&events[0], Event::Trade(trade) if trade.currency == Currency::Sol));
+
+    // logs of other programs are ignored
+    assert!(parse_logs(&Pubkey::new_unique(), &records[2].logs)
+        .unwrap()
+        .is_empty());
+    let mut logs = records[2].logs.clone();
+    let trade_log = logs
+        .iter()
+        .position(|log| log.starts_with("Program data: "))
+        .unwrap();
+    logs.insert(
+        trade_log,
+        "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA invoke [2]".to_string(),
+    );
+    logs.insert(
+        trade_log + 2,
+        "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA success".to_string(),
+    );
+    assert!(parse_logs(&program_id, &logs).unwrap().is_empty());
+
+    // the trade log lines aren't what trades are decoded from
+    let mut logs = records[2].logs.clone();
+    logs.retain(|log| !log.contains("Buy passes"));
+    assert_eq!(
+        parse_logs(&program_id, &logs).unwrap(),
+        parse_logs(&program_id, &records[2].logs).unwrap()
+    );
+
+    // malformed program logs fail loudly
+    let mut logs = records[2].logs.clone();
+    let len = logs[trade_log].len();
+    logs[trade_log].truncate(len - 8);
+    assert!(parse_logs(&program_id, &logs).is_err());
+    let mut logs = records[1].logs.clone();
+    let issue_log = logs
+        .iter()
+        .position(|log| log.contains("Issue passes"))
+        .unwrap();
+    logs[issue_log] = logs[issue_log].replace("amount 1", "amount one");
+    assert!(parse_logs(&program_id, &logs).is_err());
+}
+
+#[test]
+fn test_markets_and_holders() {
+    let (mut indexer, records) = indexer(TRADES);
+    let store = &indexer.store;
+
+    let owner = key("BXtVvvHJ1F6qPsWtTjYW9m5aAdkc5ZRAa7rcSEHsL91W");
+    let trader = key("GTx19H1NXBnbfZpS4u7eyHXJQtCBDZBmHWVyszzcEPwn");
+    let market = store.market(0, &owner).unwrap().unwrap();
+    assert_eq!(
+        (market.supply, market.trade_count, market.issued_slot),
+        (6, 2, 1_010)
+    );
+    assert_eq!(
+        store.holders(0, &owner).unwrap(),
+        vec![(trader, 5), (owner, 1)]
+    );
+    assert_eq!(store.holdings(&trader).unwrap(), vec![(0, owner, 5)]);
+    let trades = store.trades(0, &owner).unwrap();
+    assert_eq!(trades.len(), 2);
+    assert_eq!(trades[0].trade.side, Side::Buy);
+    assert_eq!(trades[1].signature, records[3].signature);
+
+    // airdrops move passes from the owner to the recipients
+    let owner = key("J7fNu4B6vXLQpw91AEuDJJbHTqmyQHnF3b7pX2H7HhvR");
+    let recipient = key("7WuGVoYSiDjvpsAwS9NUAupJJoq2mznGQ3aQA6c5qyaC");
+    assert_eq!(store.market(0, &owner).unwrap().unwrap().supply, 60);
+    assert_eq!(
+        store.holders(0, &owner).unwrap(),
+        vec![(owner, 59), (recipient, 1)]
+    );
+
+    assert!(store.market(0, &Pubkey::new_unique()).unwrap().is_none());
+    assert_eq!(
+        store.last_signature().unwrap(),
+        Some(records.last().unwrap().signature.clone())
+    );
+
+    // replaying indexed transactions changes nothing
+    assert_eq!(indexer.replay(&records).unwrap(), 0);
+    assert_eq!(
+        indexer.store.holders(0, &owner).unwrap(),
+        vec![(owner, 59), (recipient, 1)]
+    );
+}
+
+#[test]
+fn test_marketplaces() {
+    let (mut indexer, records) = indexer(TRADES);
+
+    // a market of marketplace 1 is only found under that marketplace
+    let owner = key("7g2dN4t7LjBctu3BfgJMiJfgr8QNZsFS5SqfQxkZyrKi");
+    let buyer = key("74Ubhq2kEWep2jP7eyfsMgexP6tDUWqbsWYXTUGqGU5T");
+    let store = &indexer.store;
+    assert!(store.market(0, &owner).unwrap().is_none());
+    let market = store.market(1, &owner).unwrap().unwrap();
+    assert_eq!((market.marketplace_id, market.supply), (1, 11));
+    assert_eq!(store.trades(1, &owner).unwrap()[0].trade.marketplace_id, 1);
+    assert_eq!(
+        store
+            .fees(1, &owner, Currency::Token)
+            .unwrap()
+            .protocol_fees,
+        120_313
+    );
+    assert_eq!(store.holdings(&buyer).unwrap(), vec![(1, owner, 10)]);
+
+    // the same owner issuing in the default marketplace gets a separate market
+    let issue = &records[14];
+    let record = TransactionRecord {
+        signature: format!("{}-0", issue.signature),
+        slot: issue.slot + 1,
+        block_time: issue.block_time,
+        logs: issue
+            .logs
+            .iter()
+            .map(|log| log.replace("marketplace_id 1", "marketplace_id 0"))
+            .collect(),
+    };
+    assert!(indexer.process(&record).unwrap());
+    let store = &indexer.store;
+    assert_eq!(store.market(0, &owner).unwrap().unwrap().supply, 1);
+    assert_eq!(store.market(1, &owner).unwrap().unwrap().supply, 11);
+    assert_eq!(store.holders(0, &owner).unwrap(), vec![(owner, 1)]);
+    assert_eq!(
+        store.holders(1, &owner).unwrap(),
+        vec![(buyer, 10), (owner, 1)]
+    );
+    assert!(store.trades(0, &owner).unwrap().is_empty());
+}
+
+#[test]
+fn test_fees_and_candles() {
+    let (indexer, _) = indexer(TRADES);
+    let store = &indexer.store;
+
+    let owner = key("BXtVvvHJ1F6qPsWtTjYW9m5aAdkc5ZRAa7rcSEHsL91W");
+    assert_eq!(
+        store.fees(0, &owner, Currency::Token).unwrap(),
+        Fees {
+            volume: 2_406_250 + 2_062_500,
+            protocol_fees: 24_063 + 20_625,
+            owner_fees: 24_063 + 20_625,
+            holder_fees: 0,
+            early_exit_fees: 0,
+        }
+    );
+    assert_eq!(
+        store.fees(0, &owner, Currency::Sol).unwrap(),
+        Fees::default()
+    );
+
+    // both trades fall into the same hour, priced per pass
+    assert_eq!(
+        store.candles(0, &owner, Currency::Token).unwrap(),
+        vec![Candle {
+            start_ts: 1_700_002_800,
+            open: 240_625,
+            high: 412_500,
+            low: 240_625,
+            close: 412_500,
+            volume: 15,
+        }]
+    );
+
+    let owner = key("B6sKcZ9AVRWN7Qz4buTHt9jRZAXTZBB8Dbwdy46zxyp1");
+    assert_eq!(
+        store.fees(0, &owner, Currency::Sol).unwrap().volume,
+        240_625_000
+    );
+    assert_eq!(store.candles(0, &owner, Currency::Sol).unwrap().len(), 1);
+    assert!(store
+        .candles(0, &owner, Currency::Token)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_early_exit_fees() {
+    let (indexer, _) = indexer(TRADES);
+    let store = &indexer.store;
+
+    // the first sell is within the holding period and pays a 10% early exit fee
+    let owner = key("2ongUjLc7W8N3dKcc6eUznB9uePuzLRpKPBg2QcorYQP");
+    let trades = store.trades(0, &owner).unwrap();
+    assert_eq!(trades.len(), 3);
+    assert_eq!(trades[1].trade.side, Side::Sell);
+    assert_eq!(trades[1].trade.early_exit_fees, 250_000);
+    assert_eq!(trades[2].trade.early_exit_fees, 0);
+    assert_eq!(
+        store.fees(0, &owner, Currency::Sol).unwrap(),
+        Fees {
+            volume: 3_125_000 + 2_500_000 + 625_000,
+            protocol_fees: 31_250 + 25_000 + 6_250,
+            owner_fees: 31_250 + 25_000 + 6_250,
+            holder_fees: 0,
+            early_exit_fees: 250_000,
+        }
+    );
+    assert_eq!(store.market(0, &owner).unwrap().unwrap().supply, 1);
+}
+
+#[test]
+fn test_holder_fees() {
+    let (indexer, _) = indexer(REWARDS);
+    let store = &indexer.store;
+
+    let owner = key("6P5MUMo8YqFpyjfTmuUvNRXnJodY9d3F5qWPVA8Exj6m");
+    let fees = store.fees(0, &owner, Currency::Sol).unwrap();
+    assert_eq!(fees.owner_fees, 1_203_125 + 1_031_250);
+    assert_eq!(fees.holder_fees, 1_203_125 + 1_031_250);
+    let market = store.market(0, &owner).unwrap().unwrap();
+    assert_eq!((market.supply, market.trade_count), (6, 2));
+
+    // candles an hour apart
+    let candles = store.candles(0, &owner, Currency::Sol).unwrap();
+    assert_eq!(candles.len(), 2);
+    assert_eq!(candles[0].close, 24_062_500);
+    assert_eq!(candles[1].open, 41_250_000);
+}
@@ -0,0 +1,58 @@
+//! Example of a program gating one of its instructions on pass ownership with `passes::gate`
+
+use anchor_lang::prelude::*;
+use passes::{gate, program::Passes};
+
+#[cfg(test)]
+mod tests;
+
+declare_id!("3eiYKBoctNmexmnsovcNFnNcVJSwFDkaQWjx5bHqP1eq");
+
+#[program]
+pub mod gated {
+    use super::*;
+
+    /// Succeeds only when the holder holds at least `min_amount` passes of the passes owner
+    pub fn enter(ctx: Context<Enter>, min_amount: u64) -> Result<()> {
+        gate::require_holding(
+            ctx.accounts.passes_program.to_account_info(),
+            ctx.accounts.config.to_account_info(),
+            ctx.accounts.passes_balance.to_account_info(),
+            ctx.accounts
+                .holder_page
+                .as_ref()
+                .map(|holder_page| holder_page.to_account_info()),
+            ctx.accounts.holder.to_account_info(),
+            ctx.accounts.passes_owner.to_account_info(),
+            min_amount,
+        )?;
+        msg!(
+            "Enter: owner {}, holder {}",
+            ctx.accounts.passes_owner.key(),
+            ctx.accounts.holder.key()
+        );
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Enter<'info> {
+    // signer
+    pub holder: Signer<'info>,
+
+    // passes accounts, checked by the passes program
+    /// CHECK: The marketplace config, see `gate::config_address`
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: The holder's balance, see `gate::passes_balance_address`
+    pub passes_balance: UncheckedAccount<'info>,
+
+    /// CHECK: A holder page with the holder's entry, see `gate::holder_page_address`
+    pub holder_page: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub passes_owner: UncheckedAccount<'info>,
+
+    // programs
+    pub passes_program: Program<'info, Passes>,
+}
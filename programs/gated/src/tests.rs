@@ -0,0 +1,167 @@
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::token::spl_token::{
+    self,
+    solana_program::{program_option::COption, program_pack::Pack},
+};
+use passes::{error::PassesError, gate, state::IssuanceParams, ONE_SOL, ONE_USDC};
+use passes_client::instructions::{self, MarketOptions};
+use passes_test_utils::add_upgradeable_program;
+use solana_program_test::{tokio, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+#[tokio::test]
+async fn test_enter() {
+    let admin = Keypair::new();
+    let owner = Keypair::new();
+    let holder = Keypair::new();
+    let mint = Pubkey::new_unique();
+    let mut ctx = start(&admin, &[&owner, &holder], &mint).await;
+    let program_id = passes::ID;
+
+    let ix = instructions::init(
+        &program_id,
+        0,
+        &admin.pubkey(),
+        &admin.pubkey(),
+        &mint,
+        500,
+        500,
+    );
+    send(&mut ctx, ix, &admin).await.unwrap();
+    let params = IssuanceParams::default();
+    let ix = instructions::issue_passes(&program_id, 0, &owner.pubkey(), &mint, 1, params);
+    send(&mut ctx, ix, &owner).await.unwrap();
+    let ix = instructions::buy_passes_sol(
+        &program_id,
+        0,
+        &holder.pubkey(),
+        &owner.pubkey(),
+        &admin.pubkey(),
+        3,
+        vec![],
+        None,
+        MarketOptions::default(),
+    );
+    send(&mut ctx, ix, &holder).await.unwrap();
+
+    let res = enter(&mut ctx, &holder, &owner.pubkey(), None, 3).await;
+    assert!(res.is_ok());
+    let res = enter(&mut ctx, &holder, &owner.pubkey(), None, 4).await;
+    assert_insufficient_passes(res);
+    let stranger = Keypair::new();
+    let res = enter(&mut ctx, &stranger, &owner.pubkey(), None, 1).await;
+    assert_insufficient_passes(res);
+
+    // passes in a holder page count when the caller passes the page
+    let ix = instructions::create_holder_page(&program_id, 0, &owner.pubkey(), &owner.pubkey(), 0);
+    send(&mut ctx, ix, &owner).await.unwrap();
+    let ix = instructions::convert_to_holder_page(
+        &program_id,
+        0,
+        &holder.pubkey(),
+        &owner.pubkey(),
+        0,
+        MarketOptions::default(),
+    );
+    send(&mut ctx, ix, &holder).await.unwrap();
+
+    let res = enter(&mut ctx, &holder, &owner.pubkey(), None, 3).await;
+    assert_insufficient_passes(res);
+    let res = enter(&mut ctx, &holder, &owner.pubkey(), Some(0), 3).await;
+    assert!(res.is_ok());
+    let res = enter(&mut ctx, &holder, &owner.pubkey(), Some(0), 4).await;
+    assert_insufficient_passes(res);
+}
+
+async fn enter(
+    ctx: &mut ProgramTestContext,
+    holder: &Keypair,
+    owner: &Pubkey,
+    holder_page: Option<u32>,
+    min_amount: u64,
+) -> Result<(), BanksClientError> {
+    let accounts = crate::accounts::Enter {
+        holder: holder.pubkey(),
+        config: gate::config_address(0),
+        passes_balance: gate::passes_balance_address(0, owner, &holder.pubkey()),
+        holder_page: holder_page.map(|page| gate::holder_page_address(0, owner, page)),
+        passes_owner: *owner,
+        passes_program: passes::ID,
+    };
+    let ix = Instruction {
+        program_id: crate::ID,
+        accounts: accounts.to_account_metas(None),
+        data: crate::instruction::Enter { min_amount }.data(),
+    };
+    send(ctx, ix, holder).await
+}
+
+async fn send(
+    ctx: &mut ProgramTestContext,
+    ix: Instruction,
+    signer: &Keypair,
+) -> Result<(), BanksClientError> {
+    let blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, signer],
+        blockhash,
+    );
+    ctx.banks_client.process_transaction(tx).await
+}
+
+fn assert_insufficient_passes(res: Result<(), BanksClientError>) {
+    assert!(matches!(
+        res,
+        Err(BanksClientError::TransactionError(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(code)
+        ))) if code == u32::from(PassesError::InsufficientPasses)
+    ));
+}
+
+// Start a bank with the passes program deployed by `admin`, the gated program, SOL for `users`
+// and a payment `mint`
+async fn start(admin: &Keypair, users: &[&Keypair], mint: &Pubkey) -> ProgramTestContext {
+    let mut program_test = ProgramTest::default();
+    add_upgradeable_program(&mut program_test, "passes", &passes::ID, &admin.pubkey());
+    program_test.add_program("gated", crate::ID, None);
+
+    for user in [admin].iter().chain(users) {
+        program_test.add_account(
+            user.pubkey(),
+            Account {
+                lamports: 100 * ONE_SOL,
+                ..Account::default()
+            },
+        );
+    }
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        mint_authority: COption::Some(admin.pubkey()),
+        supply: 1_000 * ONE_USDC,
+        decimals: 6,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    program_test.add_account(
+        *mint,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: spl_token::id(),
+            ..Account::default()
+        },
+    );
+
+    program_test.start_with_context().await
+}
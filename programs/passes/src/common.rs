@@ -1,67 +1,83 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
 use anchor_spl::token::Transfer;
 
-use crate::{math, ONE_SOL, ONE_USDC};
+use crate::{error::PassesError, math, state, ONE_SOL, ONE_USDC};
 
-pub fn calc_price(supply: u64, amount: u64) -> u64 {
-    let sum1 = if supply == 0 {
-        0
-    } else {
-        (supply - 1) * supply * (2 * (supply - 1) + 1) / 6
-    };
-
-    let sum2 = if supply == 0 && amount == 1 {
-        0
-    } else {
-        (supply - 1 + amount) * (supply + amount) * (2 * (supply - 1 + amount) + 1) / 6
-    };
+// Curve prices are exact: the sums of squares divide exactly and both curve units are whole
+// multiples of their divisors, so buying and selling the same passes at the same supply moves
+// the same amount and a market's reserve is exactly the buyback price of its bought passes.
+// Only fees round, up, and they never come out of the reserve.
+const _: () = assert!(ONE_USDC / 160 * 160 == ONE_USDC && ONE_SOL / 1600 * 1600 == ONE_SOL);
 
-    let summation = sum2 - sum1;
-    let price = (summation * ONE_USDC) / 160;
+// The curve math is checked without the logging `math` helpers, so clients can quote off-chain
 
-    if cfg!(feature = "local-testing") {
-        msg!(
-            "Calc: sum1 {}, sum2 {}, summation {}, price {}, amount {}, supply {}",
-            sum1,
-            sum2,
-            summation,
-            price,
-            amount,
-            supply
-        );
-    } else {
-        msg!(
-            "Calc: price {}, amount {}, supply {}",
-            price,
-            amount,
-            supply
-        );
+// Sum of the squares of the curve positions below `n`, (n - 1)n(2n - 1) / 6.
+// The product is always divisible by 6, so the sum is exact.
+fn sum_of_squares(n: u64) -> Result<u128> {
+    if n == 0 {
+        return Ok(0);
     }
-    price
+    let n = n as u128;
+    let product = ((n - 1) * n)
+        .checked_mul(2 * n - 1)
+        .ok_or(PassesError::MathOverflow)?;
+    Ok(product / 6)
 }
 
-pub fn calc_price_sol(supply: u64, amount: u64) -> u64 {
-    let sum1 = if supply == 0 {
-        0
-    } else {
-        (supply - 1) * supply * (2 * (supply - 1) + 1) / 6
-    };
+// Sums of squares of the curve positions before and after buying `amount` passes at `supply`
+fn curve_sums(supply: u64, amount: u64) -> Result<(u128, u128)> {
+    let sum1 = sum_of_squares(supply)?;
+    let sum2 = sum_of_squares(
+        supply
+            .checked_add(amount)
+            .ok_or(PassesError::MathOverflow)?,
+    )?;
+    Ok((sum1, sum2))
+}
 
-    let sum2 = if supply == 0 && amount == 1 {
-        0
-    } else {
-        (supply - 1 + amount) * (supply + amount) * (2 * (supply - 1 + amount) + 1) / 6
-    };
+// Price of `summation` curve units at `unit / divisor` each
+fn scale_price(summation: u128, unit: u64, divisor: u64) -> Result<u64> {
+    let price = summation
+        .checked_mul((unit / divisor) as u128)
+        .ok_or(PassesError::MathOverflow)?;
+    u64::try_from(price).map_err(|_| PassesError::MathOverflow.into())
+}
 
-    let summation = sum2 - sum1;
-    let price = (summation * ONE_SOL) / 1600;
+/// Token price of `amount` passes at `supply`, without logging so clients can quote off-chain
+pub fn curve_price(supply: u64, amount: u64) -> Result<u64> {
+    let (sum1, sum2) = curve_sums(supply, amount)?;
+    scale_price(sum2 - sum1, ONE_USDC, 160)
+}
 
+/// SOL price of `amount` passes at `supply`, without logging so clients can quote off-chain
+pub fn curve_price_sol(supply: u64, amount: u64) -> Result<u64> {
+    let (sum1, sum2) = curve_sums(supply, amount)?;
+    scale_price(sum2 - sum1, ONE_SOL, 1600)
+}
+
+/// Token price of `amount` passes at `supply`, fails with `MathOverflow` past the curve's range
+pub fn calc_price(supply: u64, amount: u64) -> Result<u64> {
+    let price = curve_price(supply, amount)?;
+    log_price(supply, amount, price);
+    Ok(price)
+}
+
+/// SOL price of `amount` passes at `supply`, fails with `MathOverflow` past the curve's range
+pub fn calc_price_sol(supply: u64, amount: u64) -> Result<u64> {
+    let price = curve_price_sol(supply, amount)?;
+    log_price(supply, amount, price);
+    Ok(price)
+}
+
+fn log_price(supply: u64, amount: u64, price: u64) {
     if cfg!(feature = "local-testing") {
+        let (sum1, sum2) = curve_sums(supply, amount).unwrap_or_default();
         msg!(
             "Calc: sum1 {}, sum2 {}, summation {}, price {}, amount {}, supply {}",
             sum1,
             sum2,
-            summation,
+            sum2 - sum1,
             price,
             amount,
             supply
@@ -74,9 +90,10 @@ pub fn calc_price_sol(supply: u64, amount: u64) -> u64 {
             supply
         );
     }
-    price
 }
 
+/// `fee` bps of `amount`, rounded up: buyers pay it on top of the price and sellers have it
+/// taken out of their proceeds
 pub fn calc_fee(fee: u64, amount: u64) -> Result<u64> {
     if fee == 0 || amount == 0 {
         return Ok(0);
@@ -87,6 +104,139 @@ pub fn calc_fee(fee: u64, amount: u64) -> Result<u64> {
     )?)
 }
 
+/// Split a sell's `price` between the seller and the fees, returns what the seller receives with
+/// the owner and early exit fees. Every fee rounds up, so fees adding up to the whole price can go
+/// a unit or two over it: the excess comes off the early exit fee, then the owner fees. Fees
+/// raised past the price after the owner set an early exit fee fail the sell.
+pub fn split_sell_price(
+    config: &state::Config,
+    passes_supply: &state::PassesSupply,
+    price: u64,
+    protocol_fees: u64,
+    owner_fees: u64,
+    holder_fees: u64,
+    early_exit_fees: u64,
+) -> Result<(u64, u64, u64)> {
+    let mut fee_bps = config
+        .protocol_fee_bps
+        .checked_add(config.owner_fee_bps)
+        .ok_or(PassesError::MathOverflow)?;
+    if early_exit_fees > 0 {
+        fee_bps = fee_bps
+            .checked_add(passes_supply.early_exit_fee_bps)
+            .ok_or(PassesError::MathOverflow)?;
+    }
+    require!(
+        fee_bps as u128 <= math::BPS_POWER,
+        PassesError::FeesExceedPrice
+    );
+
+    let fees = [protocol_fees, owner_fees, holder_fees, early_exit_fees]
+        .iter()
+        .try_fold(0u64, |total, fees| total.checked_add(*fees))
+        .ok_or(PassesError::MathOverflow)?;
+    let excess = fees.saturating_sub(price);
+    let early_exit_cut = excess.min(early_exit_fees);
+    let owner_cut = (excess - early_exit_cut).min(owner_fees);
+    require!(
+        early_exit_cut + owner_cut == excess,
+        PassesError::FeesExceedPrice
+    );
+    Ok((
+        price - (fees - excess),
+        owner_fees - owner_cut,
+        early_exit_fees - early_exit_cut,
+    ))
+}
+
+/// The market's reward pool if its holders share rewards, the pool account must then be passed
+pub fn get_reward_pool<'a>(
+    passes_supply: &state::PassesSupply,
+    reward_pool: &'a mut Option<Box<Account<'_, state::RewardPool>>>,
+) -> Result<Option<&'a mut state::RewardPool>> {
+    if !passes_supply.rewards {
+        return Ok(None);
+    }
+    let reward_pool = reward_pool.as_mut().ok_or(PassesError::RewardPoolMissing)?;
+    Ok(Some(reward_pool))
+}
+
+/// The market's reserve wallet if its reserve is isolated, the wallet account must then be passed.
+/// Markets without an isolated reserve keep it in the marketplace escrow.
+pub fn get_reserve_wallet<'info, T: AccountSerialize + AccountDeserialize + Owner + Clone>(
+    passes_supply: &state::PassesSupply,
+    reserve_wallet: &Option<Box<Account<'info, T>>>,
+) -> Result<Option<AccountInfo<'info>>> {
+    if !passes_supply.isolated_reserve {
+        return Ok(None);
+    }
+    let reserve_wallet = reserve_wallet
+        .as_ref()
+        .ok_or(PassesError::ReserveWalletMissing)?;
+    Ok(Some(reserve_wallet.to_account_info()))
+}
+
+/// Passes the buyer holds where the buy credits them: their balance account, or their entry in
+/// a holder page which saves new holders the balance account's rent. Exactly one must be passed,
+/// and the page only while the market has no wallet cap.
+pub fn get_buyer_holding(
+    passes_supply: &state::PassesSupply,
+    passes_balance: &Option<Box<Account<'_, state::PassesBalance>>>,
+    holder_page: &Option<AccountLoader<'_, state::HolderPage>>,
+    buyer: &Pubkey,
+    now: i64,
+) -> Result<u64> {
+    match (passes_balance, holder_page) {
+        (Some(passes_balance), None) => Ok(passes_balance.amount),
+        (None, Some(holder_page)) => {
+            passes_supply.check_holder_page(now)?;
+            let holder_page = holder_page.load()?;
+            Ok(holder_page
+                .find(buyer)
+                .map_or(0, |index| holder_page.amounts[index]))
+        }
+        _ => err!(PassesError::InvalidBuyerHolding),
+    }
+}
+
+/// Credit bought passes where [`get_buyer_holding`] found the buyer's, returns the new holding.
+/// Paged passes don't earn rewards nor keep a buy time.
+#[allow(clippy::too_many_arguments)]
+pub fn credit_bought_passes(
+    passes_supply: &mut state::PassesSupply,
+    passes_balance: &mut Option<Box<Account<'_, state::PassesBalance>>>,
+    balance_bump: u8,
+    holder_page: &Option<AccountLoader<'_, state::HolderPage>>,
+    reward_pool: Option<&state::RewardPool>,
+    buyer: &Pubkey,
+    amount: u64,
+    now: i64,
+) -> Result<u64> {
+    if let Some(holder_page) = holder_page {
+        passes_supply.paged_amount = passes_supply
+            .paged_amount
+            .checked_add(amount)
+            .ok_or(PassesError::MathOverflow)?;
+        return holder_page.load_mut()?.deposit(buyer, amount);
+    }
+
+    let passes_balance = passes_balance
+        .as_mut()
+        .ok_or(PassesError::InvalidBuyerHolding)?;
+    // settle rewards before the balance changes
+    if let Some(reward_pool) = reward_pool {
+        passes_balance.settle_rewards(reward_pool)?;
+    }
+    passes_balance.amount = passes_balance
+        .amount
+        .checked_add(amount)
+        .ok_or(PassesError::MathOverflow)?;
+    passes_balance.last_buy_ts = now;
+    passes_balance.version = state::PassesBalance::VERSION;
+    passes_balance.bump = balance_bump;
+    Ok(passes_balance.amount)
+}
+
 pub fn transfer_tokens<'info>(
     from: AccountInfo<'info>,
     to: AccountInfo<'info>,
@@ -126,6 +276,83 @@ pub fn transfer_tokens_from_user<'info>(
     anchor_spl::token::transfer(ctx, amount)
 }
 
+pub fn realloc_account<'info>(
+    account: AccountInfo<'info>,
+    payer: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    new_len: usize,
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(new_len);
+    let lamports = rent.saturating_sub(account.lamports());
+    if lamports > 0 {
+        let ctx = CpiContext::new(
+            system_program,
+            system_program::Transfer {
+                from: payer,
+                to: account.clone(),
+            },
+        );
+        system_program::transfer(ctx, lamports)?;
+    }
+
+    account.realloc(new_len, true)?;
+    Ok(())
+}
+
+/// Create a program owned PDA the way Anchor's `init` does. Anyone can send lamports to the
+/// address beforehand, which makes `create_account` fail, so a funded address is topped up to
+/// rent exemption, allocated and assigned instead.
+pub fn create_pda_account<'info>(
+    account: AccountInfo<'info>,
+    payer: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    space: usize,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let signer_seeds = &[seeds];
+    let rent = Rent::get()?.minimum_balance(space);
+    let current_lamports = account.lamports();
+    if current_lamports == 0 {
+        let ctx = CpiContext::new_with_signer(
+            system_program,
+            system_program::CreateAccount {
+                from: payer,
+                to: account,
+            },
+            signer_seeds,
+        );
+        return system_program::create_account(ctx, rent, space as u64, &crate::ID);
+    }
+
+    let lamports = rent.saturating_sub(current_lamports);
+    if lamports > 0 {
+        let ctx = CpiContext::new(
+            system_program.clone(),
+            system_program::Transfer {
+                from: payer,
+                to: account.clone(),
+            },
+        );
+        system_program::transfer(ctx, lamports)?;
+    }
+    let ctx = CpiContext::new_with_signer(
+        system_program.clone(),
+        system_program::Allocate {
+            account_to_allocate: account.clone(),
+        },
+        signer_seeds,
+    );
+    system_program::allocate(ctx, space as u64)?;
+    let ctx = CpiContext::new_with_signer(
+        system_program,
+        system_program::Assign {
+            account_to_assign: account,
+        },
+        signer_seeds,
+    );
+    system_program::assign(ctx, &crate::ID)
+}
+
 /* pub fn calc_fees(
     price: u64,
     protocol_fee_pct: u64,
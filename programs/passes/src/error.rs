@@ -22,4 +22,84 @@ pub enum PassesError {
     InvalidFee,
     #[msg("Price less than 10000")]
     PriceTooSmall,
+    #[msg("Trading is paused")]
+    Paused,
+    #[msg("Config changes require an approved proposal")]
+    MultisigRequired,
+    #[msg("Signer is not an admin")]
+    NotAnAdmin,
+    #[msg("Invalid admin keys or threshold")]
+    InvalidAdmins,
+    #[msg("Proposal is already approved by this admin")]
+    AlreadyApproved,
+    #[msg("Not enough approvals to execute the proposal")]
+    NotEnoughApprovals,
+    #[msg("Proposal is already executed")]
+    ProposalExecuted,
+    #[msg("Invalid protocol fee wallet")]
+    InvalidFeeWallet,
+    #[msg("Signer is not the program upgrade authority")]
+    NotUpgradeAuthority,
+    #[msg("Account layout cannot be migrated")]
+    InvalidAccountVersion,
+    #[msg("Holder page is full")]
+    HolderPageFull,
+    #[msg("Holder is not in the holder page")]
+    HolderNotInPage,
+    #[msg("Metadata field is too long")]
+    MetadataTooLong,
+    #[msg("Passes are not launched yet")]
+    NotLaunched,
+    #[msg("Max supply exceeded")]
+    MaxSupplyExceeded,
+    #[msg("Invalid issuance parameters")]
+    InvalidIssuanceParams,
+    #[msg("Buyer is not on the presale allowlist")]
+    NotAllowlisted,
+    #[msg("Presale wallet cap exceeded")]
+    PresaleCapExceeded,
+    #[msg("Wallet cap exceeded")]
+    WalletCapExceeded,
+    #[msg("Invalid wallet cap")]
+    InvalidWalletCap,
+    #[msg("Holding period is not over")]
+    HoldingPeriodNotOver,
+    #[msg("Invalid holding period")]
+    InvalidHoldingPeriod,
+    #[msg("Owner vesting account is missing")]
+    VestingAccountMissing,
+    #[msg("Passes are still vesting")]
+    PassesVesting,
+    #[msg("Too many airdrop recipients")]
+    TooManyRecipients,
+    #[msg("Invalid airdrop recipient accounts")]
+    InvalidRecipients,
+    #[msg("Reward pool account is missing")]
+    RewardPoolMissing,
+    #[msg("Claim rewards before moving passes to a holder page")]
+    UnclaimedRewards,
+    #[msg("Staked passes can't be sold or moved")]
+    PassesStaked,
+    #[msg("Stake rewards must pay a nonzero rate over a positive duration")]
+    InvalidStakeRewards,
+    #[msg("Reserve wallet accounts are missing")]
+    ReserveWalletMissing,
+    #[msg("The market's reserve is already isolated")]
+    ReserveAlreadyIsolated,
+    #[msg("The market's trade stats don't cover its trades since issuance")]
+    IncompleteTradeStats,
+    #[msg("Proposal expired")]
+    ProposalExpired,
+    #[msg("Pass either the buyer's balance account or a holder page")]
+    InvalidBuyerHolding,
+    #[msg("The proposal isn't executed or doesn't match the instruction")]
+    InvalidProposal,
+    #[msg("Passes can't go to a holder page under a wallet cap or presale")]
+    HolderPageCapped,
+    #[msg("The fees exceed the sell price")]
+    FeesExceedPrice,
+    #[msg("The owner's issued passes can't be sold or gifted")]
+    IssuedPassesLocked,
+    #[msg("The market trades in the other currency")]
+    CurrencyMismatch,
 }
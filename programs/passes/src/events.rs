@@ -0,0 +1,34 @@
+//! Events emitted by the program
+
+use anchor_lang::prelude::*;
+
+use crate::state::{TradeSide, TradeStats};
+
+#[event]
+pub struct PassesMetadataUpdated {
+    pub marketplace_id: u64,
+    pub owner: Pubkey,
+    pub name: String,
+    pub description_uri: String,
+    pub image_uri: String,
+    pub social_handle: String,
+}
+
+/// Emitted by every buy and sell, with the market's stats after the trade
+#[event]
+pub struct PassesTraded {
+    pub marketplace_id: u64,
+    pub owner: Pubkey,
+    pub trader: Pubkey,
+    pub side: TradeSide,
+    pub sol: bool,
+    pub amount: u64,
+    pub price: u64,
+    pub protocol_fees: u64,
+    pub owner_fees: u64,
+    pub holder_fees: u64,
+    /// Paid to the owner on top of the owner fees by sells within the holding period
+    pub early_exit_fees: u64,
+    pub supply: u64,
+    pub stats: TradeStats,
+}
@@ -0,0 +1,84 @@
+//! Helpers for other programs to gate their instructions on pass ownership.
+//!
+//! Add the passes crate with the `cpi` feature and pass the passes program, the marketplace
+//! config (see [`config_address`]), the holder, the passes owner, the holder's balance
+//! account (see [`passes_balance_address`]) and, when some of the holder's passes are in a
+//! holder page, that page (see [`holder_page_address`]).
+
+use anchor_lang::prelude::*;
+
+#[cfg(feature = "cpi")]
+use crate::error::PassesError;
+use crate::state;
+
+/// The config account of the marketplace `marketplace_id`, 0 for the default marketplace
+pub fn config_address(marketplace_id: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            state::Config::SEED,
+            state::marketplace_seed(marketplace_id).as_ref(),
+        ],
+        &crate::id(),
+    )
+    .0
+}
+
+/// The balance account of `holder` in the market of `passes_owner` in the marketplace `marketplace_id`
+pub fn passes_balance_address(
+    marketplace_id: u64,
+    passes_owner: &Pubkey,
+    holder: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            state::PassesBalance::SEED,
+            state::marketplace_seed(marketplace_id).as_ref(),
+            passes_owner.as_ref(),
+            holder.as_ref(),
+        ],
+        &crate::id(),
+    )
+    .0
+}
+
+/// The holder page `page` of the market of `passes_owner` in the marketplace `marketplace_id`
+pub fn holder_page_address(marketplace_id: u64, passes_owner: &Pubkey, page: u32) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            state::HolderPage::SEED,
+            state::marketplace_seed(marketplace_id).as_ref(),
+            passes_owner.as_ref(),
+            page.to_le_bytes().as_ref(),
+        ],
+        &crate::id(),
+    )
+    .0
+}
+
+/// Fail with `InsufficientPasses` unless `holder` holds at least `min_amount` passes of `passes_owner`,
+/// counting their entry in `holder_page` when given
+#[cfg(feature = "cpi")]
+pub fn require_holding<'info>(
+    passes_program: AccountInfo<'info>,
+    config: AccountInfo<'info>,
+    passes_balance: AccountInfo<'info>,
+    holder_page: Option<AccountInfo<'info>>,
+    holder: AccountInfo<'info>,
+    passes_owner: AccountInfo<'info>,
+    min_amount: u64,
+) -> Result<()> {
+    require_keys_eq!(passes_program.key(), crate::id());
+    let ctx = CpiContext::new(
+        passes_program,
+        crate::cpi::accounts::VerifyHolding {
+            config,
+            passes_balance,
+            holder_page,
+            holder,
+            passes_owner,
+        },
+    );
+    let holds = crate::cpi::verify_holding(ctx, min_amount)?.get();
+    require!(holds, PassesError::InsufficientPasses);
+    Ok(())
+}
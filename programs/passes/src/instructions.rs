@@ -1,14 +1,45 @@
+pub mod airdrop_passes;
+pub mod approve_proposal;
 pub mod buy_passes;
 pub mod buy_passes_sol;
+pub mod claim_rewards;
+pub mod claim_stake_rewards;
+pub mod convert_holder_page;
+pub mod create_holder_page;
+pub mod deposit_rewards;
+pub mod execute_proposal;
+pub mod fund_stake_rewards;
 pub mod get_price;
+pub mod get_trade_stats;
 pub mod init;
+pub mod isolate_reserve;
 pub mod issue_passes;
+pub mod migrate_balance;
+pub mod migrate_config;
+pub mod migrate_supply;
+pub mod propose_config_change;
 pub mod sell_passes;
 pub mod sell_passes_sol;
+pub mod set_admins;
 pub mod set_fee_pct;
+pub mod set_guardian;
+pub mod set_holding_period;
+pub mod set_passes_paused;
+pub mod set_pause;
 pub mod set_protocol_fee_dst;
+pub mod set_reward_share;
+pub mod set_wallet_cap;
+pub mod stake_passes;
+pub mod update_passes_metadata;
+pub mod verify_holding;
 
 pub use {
-    buy_passes::*, buy_passes_sol::*, get_price::*, init::*, issue_passes::*, sell_passes::*,
-    sell_passes_sol::*, set_fee_pct::*, set_protocol_fee_dst::*,
+    airdrop_passes::*, approve_proposal::*, buy_passes::*, buy_passes_sol::*, claim_rewards::*,
+    claim_stake_rewards::*, convert_holder_page::*, create_holder_page::*, deposit_rewards::*,
+    execute_proposal::*, fund_stake_rewards::*, get_price::*, get_trade_stats::*, init::*,
+    isolate_reserve::*, issue_passes::*, migrate_balance::*, migrate_config::*, migrate_supply::*,
+    propose_config_change::*, sell_passes::*, sell_passes_sol::*, set_admins::*, set_fee_pct::*,
+    set_guardian::*, set_holding_period::*, set_passes_paused::*, set_pause::*,
+    set_protocol_fee_dst::*, set_reward_share::*, set_wallet_cap::*, stake_passes::*,
+    update_passes_metadata::*, verify_holding::*,
 };
@@ -0,0 +1,199 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    common::{create_pda_account, get_reward_pool},
+    error::PassesError,
+    state,
+};
+
+// Gift passes from the owner's balance to many holders in one transaction.
+// Remaining accounts hold a (recipient, recipient passes balance) pair per amount,
+// the balance accounts are created when needed.
+
+#[derive(Accounts)]
+pub struct AirdropPasses<'info> {
+    // signer
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    // derived PDAs
+    #[account(
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, state::Config>>,
+
+    #[account{
+        seeds = [state::PassesSupply::SEED, config.marketplace_seed().as_ref(), owner.key.as_ref()],
+        bump = passes_supply.bump
+    }]
+    passes_supply: Box<Account<'info, state::PassesSupply>>,
+
+    #[account{
+        mut,
+        seeds = [state::PassesBalance::SEED, config.marketplace_seed().as_ref(), owner.key.as_ref(), owner.key.as_ref()],
+        bump = passes_balance.bump
+    }]
+    passes_balance: Box<Account<'info, state::PassesBalance>>,
+
+    // required when the owner's issued passes vest
+    #[account{
+        seeds = [state::OwnerVesting::SEED, config.marketplace_seed().as_ref(), owner.key.as_ref()],
+        bump = owner_vesting.bump
+    }]
+    owner_vesting: Option<Box<Account<'info, state::OwnerVesting>>>,
+
+    // required when holders share rewards
+    #[account{
+        seeds = [state::RewardPool::SEED, config.marketplace_seed().as_ref(), owner.key.as_ref()],
+        bump = reward_pool.bump
+    }]
+    reward_pool: Option<Box<Account<'info, state::RewardPool>>>,
+
+    // programs
+    pub system_program: Program<'info, System>,
+}
+
+pub fn airdrop_passes<'info>(
+    ctx: Context<'_, '_, 'info, 'info, AirdropPasses<'info>>,
+    amounts: Vec<u64>,
+) -> Result<()> {
+    let owner = ctx.accounts.owner.key();
+    let marketplace_id = ctx.accounts.config.marketplace_id;
+    let passes_supply = &ctx.accounts.passes_supply;
+    let reward_pool = get_reward_pool(passes_supply, &mut ctx.accounts.reward_pool)?;
+    let recipients = ctx.remaining_accounts;
+    let marketplace_seed = ctx.accounts.config.marketplace_seed();
+
+    require!(!amounts.is_empty(), PassesError::ZeroAmount);
+    require!(
+        amounts.len() <= state::PassesBalance::MAX_AIRDROP_RECIPIENTS,
+        PassesError::TooManyRecipients
+    );
+    require!(
+        recipients.len() == amounts.len() * 2,
+        PassesError::InvalidRecipients
+    );
+
+    let total = amounts
+        .iter()
+        .try_fold(0u64, |total, amount| total.checked_add(*amount))
+        .ok_or(PassesError::MathOverflow)?;
+    let balance = ctx.accounts.passes_balance.amount;
+    require!(balance >= total, PassesError::InsufficientPasses);
+    require!(
+        ctx.accounts.passes_balance.unstaked() >= total,
+        PassesError::PassesStaked
+    );
+
+    // vesting passes can't be gifted
+    if passes_supply.vesting {
+        let owner_vesting = ctx
+            .accounts
+            .owner_vesting
+            .as_ref()
+            .ok_or(PassesError::VestingAccountMissing)?;
+        let locked = owner_vesting.locked(Clock::get()?.unix_timestamp)?;
+        require!(balance - total >= locked, PassesError::PassesVesting);
+    }
+    passes_supply.check_issued(balance, total)?;
+
+    for (amount, accounts) in amounts.iter().zip(recipients.chunks(2)) {
+        let recipient = &accounts[0];
+        let recipient_balance = &accounts[1];
+        require!(*amount > 0, PassesError::ZeroAmount);
+        require_keys_neq!(recipient.key(), owner, PassesError::InvalidRecipients);
+
+        let (balance_key, bump) = Pubkey::find_program_address(
+            &[
+                state::PassesBalance::SEED,
+                marketplace_seed.as_ref(),
+                owner.as_ref(),
+                recipient.key.as_ref(),
+            ],
+            ctx.program_id,
+        );
+        require_keys_eq!(
+            recipient_balance.key(),
+            balance_key,
+            PassesError::InvalidRecipients
+        );
+
+        let new_balance = if recipient_balance.data_is_empty() {
+            let bump_bytes = [bump];
+            create_pda_account(
+                recipient_balance.clone(),
+                ctx.accounts.owner.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                state::PassesBalance::LEN,
+                &[
+                    state::PassesBalance::SEED,
+                    marketplace_seed.as_ref(),
+                    owner.as_ref(),
+                    recipient.key.as_ref(),
+                    &bump_bytes,
+                ],
+            )?;
+
+            // new holders only earn the rewards distributed from now on
+            let (token_reward_index, sol_reward_index) = reward_pool
+                .as_deref()
+                .map_or((0, 0), |pool| (pool.token_index, pool.sol_index));
+            let passes_balance = state::PassesBalance {
+                version: state::PassesBalance::VERSION,
+                amount: *amount,
+                bump,
+                last_buy_ts: 0,
+                token_reward_index,
+                sol_reward_index,
+                pending_token_rewards: 0,
+                pending_sol_rewards: 0,
+                staked_amount: 0,
+                reserved: [0; 32],
+            };
+            passes_balance.try_serialize(&mut &mut recipient_balance.try_borrow_mut_data()?[..])?;
+            passes_balance.amount
+        } else {
+            let mut passes_balance = Account::<state::PassesBalance>::try_from(recipient_balance)?;
+            if let Some(reward_pool) = reward_pool.as_deref() {
+                passes_balance.settle_rewards(reward_pool)?;
+            }
+            passes_balance.amount = passes_balance
+                .amount
+                .checked_add(*amount)
+                .ok_or(PassesError::MathOverflow)?;
+            passes_balance.exit(ctx.program_id)?;
+            passes_balance.amount
+        };
+        passes_supply.check_wallet_cap(new_balance, passes_supply.amount)?;
+
+        msg!(
+            "Airdrop passes: marketplace_id {}, owner {}, recipient {}, amount {}, balance {}",
+            marketplace_id,
+            owner,
+            recipient.key(),
+            amount,
+            new_balance
+        );
+    }
+
+    let passes_balance = &mut ctx.accounts.passes_balance;
+    if let Some(reward_pool) = reward_pool.as_deref() {
+        passes_balance.settle_rewards(reward_pool)?;
+    }
+    passes_balance.amount = passes_balance
+        .amount
+        .checked_sub(total)
+        .ok_or(PassesError::MathOverflow)?;
+
+    msg!(
+        "Airdrop passes: marketplace_id {}, owner {}, recipients {}, total {}, balance {}",
+        marketplace_id,
+        owner,
+        amounts.len(),
+        total,
+        passes_balance.amount
+    );
+
+    Ok(())
+}
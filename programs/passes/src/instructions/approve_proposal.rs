@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::PassesError, state};
+
+// Approve a config change proposal
+
+#[derive(Accounts)]
+pub struct ApproveProposal<'info> {
+    // signer
+    #[account(
+        constraint = config.is_admin(approver.key) @ PassesError::NotAnAdmin
+    )]
+    pub approver: Signer<'info>,
+
+    // derived PDAs
+    #[account(
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, state::Config>>,
+
+    #[account(
+        mut,
+        seeds = [state::Proposal::SEED, config.marketplace_seed().as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+        constraint = !proposal.executed @ PassesError::ProposalExecuted
+    )]
+    pub proposal: Box<Account<'info, state::Proposal>>,
+}
+
+pub fn approve_proposal(ctx: Context<ApproveProposal>) -> Result<()> {
+    let proposal = &mut ctx.accounts.proposal;
+    let approver = ctx.accounts.approver.key();
+
+    require!(
+        !proposal.is_expired(Clock::get()?.unix_timestamp),
+        PassesError::ProposalExpired
+    );
+    require!(
+        !proposal.approvals().contains(&approver),
+        PassesError::AlreadyApproved
+    );
+
+    // drop approvals of keys removed from the admins to make room
+    let config = &ctx.accounts.config;
+    let mut approvals = [Pubkey::default(); state::MAX_ADMINS];
+    let mut approvals_len = 0;
+    for key in proposal
+        .approvals()
+        .iter()
+        .filter(|key| config.is_admin(key))
+    {
+        approvals[approvals_len] = *key;
+        approvals_len += 1;
+    }
+    approvals[approvals_len] = approver;
+    proposal.approvals = approvals;
+    proposal.approvals_len = (approvals_len + 1) as u8;
+
+    msg!(
+        "Approve proposal: id {}, approver {}, approvals {}",
+        proposal.id,
+        approver,
+        proposal.approvals_len
+    );
+
+    Ok(())
+}
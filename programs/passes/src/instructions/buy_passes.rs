@@ -8,8 +8,12 @@ use anchor_spl::{
 };
 
 use crate::{
-    common::{calc_fee, calc_price, transfer_tokens_from_user},
+    common::{
+        calc_fee, calc_price, credit_bought_passes, get_buyer_holding, get_reserve_wallet,
+        get_reward_pool, transfer_tokens_from_user,
+    },
     error::PassesError,
+    events::PassesTraded,
     state, ONE_USDC,
 };
 
@@ -24,7 +28,7 @@ pub struct BuyPasses<'info> {
     // derived PDAs
     #[account{
         mut,
-        seeds = [b"supply", passes_owner.key.as_ref(),],
+        seeds = [state::PassesSupply::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref(),],
         bump = passes_supply.bump
     }]
     passes_supply: Box<Account<'info, state::PassesSupply>>,
@@ -34,13 +38,29 @@ pub struct BuyPasses<'info> {
         init_if_needed,
         payer = buyer,
         space = state::PassesBalance::LEN,
-        seeds = [b"balance", passes_owner.key.as_ref(), buyer.key.as_ref()],
+        seeds = [state::PassesBalance::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref(), buyer.key.as_ref()],
         bump,
     }]
-    passes_balance: Box<Account<'info, state::PassesBalance>>,
+    passes_balance: Option<Box<Account<'info, state::PassesBalance>>>,
 
+    // instead of the balance account, a holder page to credit the passes to
     #[account(
-        seeds = [state::Config::SEED],
+        mut,
+        seeds = [state::HolderPage::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref(), holder_page.load()?.page.to_le_bytes().as_ref()],
+        bump = holder_page.load()?.bump
+    )]
+    pub holder_page: Option<AccountLoader<'info, state::HolderPage>>,
+
+    // required when holders share rewards
+    #[account{
+        mut,
+        seeds = [state::RewardPool::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
+        bump = reward_pool.bump
+    }]
+    reward_pool: Option<Box<Account<'info, state::RewardPool>>>,
+
+    #[account(
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
         bump = config.bump
     )]
     pub config: Box<Account<'info, state::Config>>,
@@ -54,13 +74,23 @@ pub struct BuyPasses<'info> {
 
     #[account(
         mut,
-        seeds = [b"escrow", payment_mint.key().as_ref()],
+        seeds = [state::Config::ESCROW_TOKEN_SEED, config.marketplace_seed().as_ref(), payment_mint.key().as_ref()],
         bump,
         token::mint = payment_mint,
         token::authority = config
     )]
     pub escrow_wallet: Box<Account<'info, TokenAccount>>, // escrow wallet (associated token account) to store buyer payments
 
+    // required when the market's reserve is isolated
+    #[account(
+        mut,
+        seeds = [state::PassesSupply::RESERVE_TOKEN_SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
+        bump,
+        token::mint = payment_mint,
+        token::authority = config
+    )]
+    pub reserve_wallet: Option<Box<Account<'info, TokenAccount>>>,
+
     // accounts
     /// CHECK: This is not dangerous because we don't read or write from this account
     pub passes_owner: AccountInfo<'info>, // buy passes for the specified passes owner
@@ -69,7 +99,6 @@ pub struct BuyPasses<'info> {
 
     #[account(
         mut,
-        constraint = protocol_fee_wallet.owner == config.admin.key(),
         constraint = protocol_fee_wallet.mint == payment_mint.key(),
         constraint = protocol_fee_wallet.key() == config.protocol_fee_token_wallet
     )]
@@ -89,37 +118,89 @@ pub struct BuyPasses<'info> {
 
 // Buy passes
 
-pub fn buy_passes(ctx: Context<BuyPasses>, amount: u64) -> Result<()> {
+pub fn buy_passes(ctx: Context<BuyPasses>, amount: u64, proof: Vec<[u8; 32]>) -> Result<()> {
     let supply = ctx.accounts.passes_supply.amount;
     let owner = ctx.accounts.passes_owner.key();
+    let marketplace_id = ctx.accounts.config.marketplace_id;
     let buyer = ctx.accounts.buyer.key();
     let config = &ctx.accounts.config;
-    let passes_balance = &mut ctx.accounts.passes_balance;
     let passes_supply = &mut ctx.accounts.passes_supply;
 
+    require!(
+        !config.buys_paused && !passes_supply.paused,
+        PassesError::Paused
+    );
     require!(supply > 0, PassesError::ZeroSupply);
+    let now = Clock::get()?.unix_timestamp;
+    passes_supply.check_buy(amount, now)?;
+    passes_supply.check_currency(false)?;
+    let holding = get_buyer_holding(
+        passes_supply,
+        &ctx.accounts.passes_balance,
+        &ctx.accounts.holder_page,
+        &buyer,
+        now,
+    )?;
+    let new_balance = holding
+        .checked_add(amount)
+        .ok_or(PassesError::MathOverflow)?;
+    passes_supply.check_presale(&buyer, &proof, new_balance, now)?;
+    if buyer != owner {
+        let new_supply = supply
+            .checked_add(amount)
+            .ok_or(PassesError::MathOverflow)?;
+        passes_supply.check_wallet_cap(new_balance, new_supply)?;
+    }
 
-    let price = calc_price(supply, amount);
+    let price = calc_price(passes_supply.curve_supply(supply)?, amount)?;
     require!(price > 0, PassesError::ZeroPrice);
 
     // calc fees
     let protocol_fees = calc_fee(config.protocol_fee_bps, price)?;
     let owner_fees = calc_fee(config.owner_fee_bps, price)?;
 
-    // send buyer's token to escrow wallet
+    // the holders' share of the owner fees stays in escrow as rewards
+    let mut reward_pool = get_reward_pool(passes_supply, &mut ctx.accounts.reward_pool)?;
+    let holder_fees = match reward_pool.as_deref_mut() {
+        Some(reward_pool) => {
+            reward_pool.share_token_fees(owner_fees, passes_supply.rewarded_supply())?
+        }
+        None => 0,
+    };
+    let owner_fees = owner_fees
+        .checked_sub(holder_fees)
+        .ok_or(PassesError::MathOverflow)?;
+
+    // send buyer's token to escrow wallet, with the holder fees
+    let mut escrow_amount = price
+        .checked_add(holder_fees)
+        .ok_or(PassesError::MathOverflow)?;
     let from = ctx.accounts.buyer_wallet.to_account_info();
     let to = ctx.accounts.escrow_wallet.to_account_info();
     let authority = ctx.accounts.buyer.to_account_info();
     let token_program = ctx.accounts.token_program.to_account_info();
+
+    // an isolated reserve takes the price, only the holder fees go to escrow
+    if let Some(reserve_wallet) = get_reserve_wallet(passes_supply, &ctx.accounts.reserve_wallet)? {
+        transfer_tokens_from_user(
+            from.clone(),
+            reserve_wallet,
+            authority.clone(),
+            token_program.clone(),
+            price,
+        )?;
+        msg!("Send buyer payment to reserve wallet: {}", price);
+        escrow_amount = holder_fees;
+    }
     // msg!("Buyer wallet: {:#?}", ctx.accounts.buyer_wallet);
     transfer_tokens_from_user(
         from.clone(),
         to,
         authority.clone(),
         token_program.clone(),
-        price,
+        escrow_amount,
     )?;
-    msg!("Send buyer payment to escrow wallet: {}", price);
+    msg!("Send buyer payment to escrow wallet: {}", escrow_amount);
 
     // send protocol fees
     let to = ctx.accounts.protocol_fee_wallet.to_account_info();
@@ -137,26 +218,49 @@ pub fn buy_passes(ctx: Context<BuyPasses>, amount: u64) -> Result<()> {
     transfer_tokens_from_user(from, to, authority, token_program, owner_fees)?;
     msg!("Send owner fees: {}", owner_fees);
 
-    passes_balance.amount = passes_balance
-        .amount
-        .checked_add(amount)
-        .ok_or(PassesError::MathOverflow)?;
+    let balance = credit_bought_passes(
+        passes_supply,
+        &mut ctx.accounts.passes_balance,
+        ctx.bumps.passes_balance,
+        &ctx.accounts.holder_page,
+        reward_pool.as_deref(),
+        &buyer,
+        amount,
+        now,
+    )?;
     passes_supply.amount = passes_supply
         .amount
         .checked_add(amount)
         .ok_or(PassesError::MathOverflow)?;
 
-    passes_balance.bump = ctx.bumps.passes_balance;
+    passes_supply.record_trade(state::TradeSide::Buy, false, amount, price, now)?;
+    emit!(PassesTraded {
+        marketplace_id,
+        owner,
+        trader: buyer,
+        side: state::TradeSide::Buy,
+        sol: false,
+        amount,
+        price,
+        protocol_fees,
+        owner_fees,
+        holder_fees,
+        early_exit_fees: 0,
+        supply: passes_supply.amount,
+        stats: passes_supply.stats.clone(),
+    });
 
     msg!(
-        "Buy passes: owner {}, buyer {}, amount {}, price {}, protocol_fees {}, owner_fees {}, balance {}, supply {}",
+        "Buy passes: marketplace_id {}, owner {}, buyer {}, amount {}, price {}, protocol_fees {}, owner_fees {}, holder_fees {}, balance {}, supply {}",
+        marketplace_id,
         owner,
         buyer,
         amount,
         price,
         protocol_fees,
         owner_fees,
-        passes_balance.amount,
+        holder_fees,
+        balance,
         passes_supply.amount
     );
 
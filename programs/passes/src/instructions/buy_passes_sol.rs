@@ -2,8 +2,12 @@ use anchor_lang::prelude::*;
 use solana_program::system_instruction;
 
 use crate::{
-    common::{calc_fee, calc_price_sol},
+    common::{
+        calc_fee, calc_price_sol, credit_bought_passes, get_buyer_holding, get_reserve_wallet,
+        get_reward_pool,
+    },
     error::PassesError,
+    events::PassesTraded,
     state,
 };
 
@@ -18,7 +22,7 @@ pub struct BuyPassesSol<'info> {
     // derived PDAs
     #[account{
         mut,
-        seeds = [b"supply", passes_owner.key.as_ref(),],
+        seeds = [state::PassesSupply::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref(),],
         bump = passes_supply.bump,
     }]
     passes_supply: Box<Account<'info, state::PassesSupply>>,
@@ -28,24 +32,48 @@ pub struct BuyPassesSol<'info> {
         init_if_needed,
         payer = buyer,
         space = state::PassesBalance::LEN,
-        seeds = [b"balance", passes_owner.key.as_ref(), buyer.key.as_ref()],
+        seeds = [state::PassesBalance::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref(), buyer.key.as_ref()],
         bump
     }]
-    passes_balance: Box<Account<'info, state::PassesBalance>>,
+    passes_balance: Option<Box<Account<'info, state::PassesBalance>>>,
 
+    // instead of the balance account, a holder page to credit the passes to
     #[account(
-        seeds = [state::Config::SEED],
+        mut,
+        seeds = [state::HolderPage::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref(), holder_page.load()?.page.to_le_bytes().as_ref()],
+        bump = holder_page.load()?.bump
+    )]
+    pub holder_page: Option<AccountLoader<'info, state::HolderPage>>,
+
+    // required when holders share rewards
+    #[account{
+        mut,
+        seeds = [state::RewardPool::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
+        bump = reward_pool.bump
+    }]
+    reward_pool: Option<Box<Account<'info, state::RewardPool>>>,
+
+    #[account(
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
         bump = config.bump
     )]
     pub config: Box<Account<'info, state::Config>>,
 
     #[account(
         mut,
-        seeds = [state::EscrowSOL::SEED],
+        seeds = [state::EscrowSOL::SEED, config.marketplace_seed().as_ref()],
         bump = escrow_wallet.bump
     )]
     pub escrow_wallet: Box<Account<'info, state::EscrowSOL>>,
 
+    // required when the market's reserve is isolated
+    #[account(
+        mut,
+        seeds = [state::PassesSupply::RESERVE_SOL_SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
+        bump = reserve_wallet.bump
+    )]
+    pub reserve_wallet: Option<Box<Account<'info, state::EscrowSOL>>>,
+
     // accounts
     /// CHECK: This is not dangerous because we don't read or write from this account
     #[account(mut)]
@@ -54,7 +82,7 @@ pub struct BuyPassesSol<'info> {
     /// CHECK: This is not dangerous because we don't read or write from this account
     #[account(
         mut,
-        constraint = protocol_fee_wallet.key() == config.admin
+        constraint = protocol_fee_wallet.key() == config.protocol_fee_sol_wallet
     )]
     pub protocol_fee_wallet: AccountInfo<'info>,
 
@@ -64,35 +92,88 @@ pub struct BuyPassesSol<'info> {
 
 // Buy passes with SOL
 
-pub fn buy_passes_sol(ctx: Context<BuyPassesSol>, amount: u64) -> Result<()> {
+pub fn buy_passes_sol(ctx: Context<BuyPassesSol>, amount: u64, proof: Vec<[u8; 32]>) -> Result<()> {
     let supply = ctx.accounts.passes_supply.amount;
     let owner = ctx.accounts.passes_owner.key();
+    let marketplace_id = ctx.accounts.config.marketplace_id;
     let buyer = ctx.accounts.buyer.key();
     let config = &ctx.accounts.config;
-    let passes_balance = &mut ctx.accounts.passes_balance;
     let passes_supply = &mut ctx.accounts.passes_supply;
 
+    require!(
+        !config.buys_paused && !passes_supply.paused,
+        PassesError::Paused
+    );
     require!(supply > 0, PassesError::ZeroSupply);
-
-    let price = calc_price_sol(supply, amount);
+    let now = Clock::get()?.unix_timestamp;
+    passes_supply.check_buy(amount, now)?;
+    passes_supply.check_currency(true)?;
+    let holding = get_buyer_holding(
+        passes_supply,
+        &ctx.accounts.passes_balance,
+        &ctx.accounts.holder_page,
+        &buyer,
+        now,
+    )?;
+    let new_balance = holding
+        .checked_add(amount)
+        .ok_or(PassesError::MathOverflow)?;
+    passes_supply.check_presale(&buyer, &proof, new_balance, now)?;
+    if buyer != owner {
+        let new_supply = supply
+            .checked_add(amount)
+            .ok_or(PassesError::MathOverflow)?;
+        passes_supply.check_wallet_cap(new_balance, new_supply)?;
+    }
+
+    let price = calc_price_sol(passes_supply.curve_supply(supply)?, amount)?;
     require!(price > 0, PassesError::ZeroPrice);
 
     // calc fees
     let protocol_fees = calc_fee(config.protocol_fee_bps, price)?;
     let owner_fees = calc_fee(config.owner_fee_bps, price)?;
 
-    // send buyer's token to escrow wallet
+    // the holders' share of the owner fees stays in escrow as rewards
+    let mut reward_pool = get_reward_pool(passes_supply, &mut ctx.accounts.reward_pool)?;
+    let holder_fees = match reward_pool.as_deref_mut() {
+        Some(reward_pool) => {
+            reward_pool.share_sol_fees(owner_fees, passes_supply.rewarded_supply())?
+        }
+        None => 0,
+    };
+    let owner_fees = owner_fees
+        .checked_sub(holder_fees)
+        .ok_or(PassesError::MathOverflow)?;
+
+    // send buyer's token to escrow wallet, with the holder fees
+    let mut escrow_amount = price
+        .checked_add(holder_fees)
+        .ok_or(PassesError::MathOverflow)?;
     let from = ctx.accounts.buyer.to_account_info();
     let to = ctx.accounts.escrow_wallet.to_account_info();
+
+    // an isolated reserve takes the price, only the holder fees go to escrow
+    if let Some(reserve_wallet) = get_reserve_wallet(passes_supply, &ctx.accounts.reserve_wallet)? {
+        anchor_lang::solana_program::program::invoke(
+            &system_instruction::transfer(from.key, reserve_wallet.key, price),
+            &[
+                from.clone(),
+                reserve_wallet,
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+        msg!("Send buyer payment to reserve wallet: {}", price);
+        escrow_amount = holder_fees;
+    }
     anchor_lang::solana_program::program::invoke(
-        &system_instruction::transfer(from.key, to.key, price),
+        &system_instruction::transfer(from.key, to.key, escrow_amount),
         &[
             from.clone(),
             to,
             ctx.accounts.system_program.to_account_info(),
         ],
     )?;
-    msg!("Send buyer payment to escrow wallet: {}", price);
+    msg!("Send buyer payment to escrow wallet: {}", escrow_amount);
 
     // send protocol fees
     let to = ctx.accounts.protocol_fee_wallet.to_account_info();
@@ -115,26 +196,49 @@ pub fn buy_passes_sol(ctx: Context<BuyPassesSol>, amount: u64) -> Result<()> {
     anchor_lang::system_program::transfer(cpi_context, owner_fees)?;
     msg!("Send owner fees: {}", owner_fees);
 
-    passes_balance.amount = passes_balance
-        .amount
-        .checked_add(amount)
-        .ok_or(PassesError::MathOverflow)?;
+    let balance = credit_bought_passes(
+        passes_supply,
+        &mut ctx.accounts.passes_balance,
+        ctx.bumps.passes_balance,
+        &ctx.accounts.holder_page,
+        reward_pool.as_deref(),
+        &buyer,
+        amount,
+        now,
+    )?;
     passes_supply.amount = passes_supply
         .amount
         .checked_add(amount)
         .ok_or(PassesError::MathOverflow)?;
 
-    passes_balance.bump = ctx.bumps.passes_balance;
+    passes_supply.record_trade(state::TradeSide::Buy, true, amount, price, now)?;
+    emit!(PassesTraded {
+        marketplace_id,
+        owner,
+        trader: buyer,
+        side: state::TradeSide::Buy,
+        sol: true,
+        amount,
+        price,
+        protocol_fees,
+        owner_fees,
+        holder_fees,
+        early_exit_fees: 0,
+        supply: passes_supply.amount,
+        stats: passes_supply.stats.clone(),
+    });
 
     msg!(
-        "Buy passes: owner {}, buyer {}, amount {}, price {}, protocol_fees {}, owner_fees {}, balance {}, supply {}",
+        "Buy passes: marketplace_id {}, owner {}, buyer {}, amount {}, price {}, protocol_fees {}, owner_fees {}, holder_fees {}, balance {}, supply {}",
+        marketplace_id,
         owner,
         buyer,
         amount,
         price,
         protocol_fees,
         owner_fees,
-        passes_balance.amount,
+        holder_fees,
+        balance,
         passes_supply.amount
     );
 
@@ -0,0 +1,153 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{common::transfer_tokens, error::PassesError, state};
+
+// Pay a holder the rewards their passes earned in the owner's market
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    // signer
+    pub holder: Signer<'info>,
+
+    // derived PDAs
+    #[account{
+        mut,
+        seeds = [state::PassesBalance::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref(), holder.key.as_ref()],
+        bump = passes_balance.bump
+    }]
+    passes_balance: Box<Account<'info, state::PassesBalance>>,
+
+    #[account{
+        seeds = [state::RewardPool::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
+        bump = reward_pool.bump
+    }]
+    reward_pool: Box<Account<'info, state::RewardPool>>,
+
+    #[account(
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, state::Config>>,
+
+    #[account(
+        mut,
+        seeds = [state::Config::ESCROW_TOKEN_SEED, config.marketplace_seed().as_ref(), payment_mint.key().as_ref()],
+        bump,
+        token::mint = payment_mint,
+        token::authority = config
+    )]
+    pub escrow_wallet: Box<Account<'info, TokenAccount>>,
+
+    // accounts
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub passes_owner: AccountInfo<'info>,
+
+    #[account(address = config.payment_mint)]
+    pub payment_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        constraint = holder_wallet.owner == holder.key(),
+        constraint = holder_wallet.mint == payment_mint.key()
+    )]
+    holder_wallet: Box<Account<'info, TokenAccount>>,
+
+    // programs
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewardsSol<'info> {
+    // signer
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    // derived PDAs
+    #[account(
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, state::Config>>,
+
+    #[account{
+        mut,
+        seeds = [state::PassesBalance::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref(), holder.key.as_ref()],
+        bump = passes_balance.bump
+    }]
+    passes_balance: Box<Account<'info, state::PassesBalance>>,
+
+    #[account{
+        seeds = [state::RewardPool::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
+        bump = reward_pool.bump
+    }]
+    reward_pool: Box<Account<'info, state::RewardPool>>,
+
+    #[account(
+        mut,
+        seeds = [state::EscrowSOL::SEED, config.marketplace_seed().as_ref()],
+        bump = escrow_wallet.bump
+    )]
+    pub escrow_wallet: Box<Account<'info, state::EscrowSOL>>,
+
+    // accounts
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub passes_owner: AccountInfo<'info>,
+}
+
+// Claim token rewards
+
+pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+    let passes_balance = &mut ctx.accounts.passes_balance;
+    passes_balance.settle_rewards(&ctx.accounts.reward_pool)?;
+    let amount = passes_balance.pending_token_rewards;
+    require!(amount > 0, PassesError::ZeroAmount);
+    passes_balance.pending_token_rewards = 0;
+
+    let marketplace_seed = ctx.accounts.config.marketplace_seed();
+    let bump_vector = ctx.accounts.config.bump.to_le_bytes();
+    let authority_seeds: &[&[&[u8]]] = &[&[
+        state::Config::SEED,
+        marketplace_seed.as_ref(),
+        bump_vector.as_ref(),
+    ]];
+    transfer_tokens(
+        ctx.accounts.escrow_wallet.to_account_info(),
+        ctx.accounts.holder_wallet.to_account_info(),
+        ctx.accounts.config.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        amount,
+        authority_seeds,
+    )?;
+
+    msg!(
+        "Claim rewards: owner {}, holder {}, amount {}",
+        ctx.accounts.passes_owner.key(),
+        ctx.accounts.holder.key(),
+        amount
+    );
+
+    Ok(())
+}
+
+// Claim SOL rewards
+
+pub fn claim_rewards_sol(ctx: Context<ClaimRewardsSol>) -> Result<()> {
+    let passes_balance = &mut ctx.accounts.passes_balance;
+    passes_balance.settle_rewards(&ctx.accounts.reward_pool)?;
+    let amount = passes_balance.pending_sol_rewards;
+    require!(amount > 0, PassesError::ZeroAmount);
+    passes_balance.pending_sol_rewards = 0;
+
+    ctx.accounts.escrow_wallet.sub_lamports(amount)?;
+    ctx.accounts.holder.add_lamports(amount)?;
+
+    msg!(
+        "Claim SOL rewards: owner {}, holder {}, amount {}",
+        ctx.accounts.passes_owner.key(),
+        ctx.accounts.holder.key(),
+        amount
+    );
+
+    Ok(())
+}
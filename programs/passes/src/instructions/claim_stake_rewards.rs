@@ -0,0 +1,97 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{common::transfer_tokens, error::PassesError, state};
+
+// Pay a staker the token rewards their stake earned
+
+#[derive(Accounts)]
+pub struct ClaimStakeRewards<'info> {
+    // signer
+    pub holder: Signer<'info>,
+
+    // derived PDAs
+    #[account{
+        mut,
+        seeds = [state::StakePool::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
+        bump = stake_pool.bump
+    }]
+    stake_pool: Box<Account<'info, state::StakePool>>,
+
+    #[account{
+        mut,
+        seeds = [state::PassesStake::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref(), holder.key.as_ref()],
+        bump = passes_stake.bump
+    }]
+    passes_stake: Box<Account<'info, state::PassesStake>>,
+
+    #[account(
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, state::Config>>,
+
+    #[account(
+        mut,
+        seeds = [state::Config::ESCROW_TOKEN_SEED, config.marketplace_seed().as_ref(), payment_mint.key().as_ref()],
+        bump,
+        token::mint = payment_mint,
+        token::authority = config
+    )]
+    pub escrow_wallet: Box<Account<'info, TokenAccount>>,
+
+    // accounts
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub passes_owner: AccountInfo<'info>,
+
+    #[account(address = config.payment_mint)]
+    pub payment_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        constraint = holder_wallet.owner == holder.key(),
+        constraint = holder_wallet.mint == payment_mint.key()
+    )]
+    holder_wallet: Box<Account<'info, TokenAccount>>,
+
+    // programs
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn claim_stake_rewards(ctx: Context<ClaimStakeRewards>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let stake_pool = &mut ctx.accounts.stake_pool;
+    let passes_stake = &mut ctx.accounts.passes_stake;
+    stake_pool.update(now)?;
+    passes_stake.settle(stake_pool, now)?;
+
+    let amount = passes_stake.pending_rewards;
+    require!(amount > 0, PassesError::ZeroAmount);
+    passes_stake.pending_rewards = 0;
+
+    let marketplace_seed = ctx.accounts.config.marketplace_seed();
+    let bump_vector = ctx.accounts.config.bump.to_le_bytes();
+    let authority_seeds: &[&[&[u8]]] = &[&[
+        state::Config::SEED,
+        marketplace_seed.as_ref(),
+        bump_vector.as_ref(),
+    ]];
+    transfer_tokens(
+        ctx.accounts.escrow_wallet.to_account_info(),
+        ctx.accounts.holder_wallet.to_account_info(),
+        ctx.accounts.config.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        amount,
+        authority_seeds,
+    )?;
+
+    msg!(
+        "Claim stake rewards: owner {}, holder {}, amount {}, points {}",
+        ctx.accounts.passes_owner.key(),
+        ctx.accounts.holder.key(),
+        amount,
+        passes_stake.points
+    );
+
+    Ok(())
+}
@@ -0,0 +1,185 @@
+use anchor_lang::prelude::*;
+
+use crate::{common::get_reward_pool, error::PassesError, state};
+
+// Move passes between a holder's balance account and a holder page
+
+#[derive(Accounts)]
+pub struct ConvertToHolderPage<'info> {
+    // signer
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    // derived PDAs
+    #[account(
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, state::Config>>,
+
+    #[account{
+        mut,
+        close = holder,
+        seeds = [state::PassesBalance::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref(), holder.key.as_ref()],
+        bump = passes_balance.bump
+    }]
+    passes_balance: Box<Account<'info, state::PassesBalance>>,
+
+    #[account{
+        mut,
+        seeds = [state::PassesSupply::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
+        bump = passes_supply.bump
+    }]
+    passes_supply: Box<Account<'info, state::PassesSupply>>,
+
+    #[account(
+        mut,
+        seeds = [state::HolderPage::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref(), holder_page.load()?.page.to_le_bytes().as_ref()],
+        bump = holder_page.load()?.bump
+    )]
+    pub holder_page: AccountLoader<'info, state::HolderPage>,
+
+    // required when holders share rewards
+    #[account{
+        seeds = [state::RewardPool::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
+        bump = reward_pool.bump
+    }]
+    reward_pool: Option<Box<Account<'info, state::RewardPool>>>,
+
+    // accounts
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub passes_owner: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConvertFromHolderPage<'info> {
+    // signer
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    // derived PDAs
+    #[account(
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, state::Config>>,
+
+    #[account{
+        init_if_needed,
+        payer = holder,
+        space = state::PassesBalance::LEN,
+        seeds = [state::PassesBalance::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref(), holder.key.as_ref()],
+        bump,
+    }]
+    passes_balance: Box<Account<'info, state::PassesBalance>>,
+
+    #[account{
+        mut,
+        seeds = [state::PassesSupply::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
+        bump = passes_supply.bump
+    }]
+    passes_supply: Box<Account<'info, state::PassesSupply>>,
+
+    #[account(
+        mut,
+        seeds = [state::HolderPage::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref(), holder_page.load()?.page.to_le_bytes().as_ref()],
+        bump = holder_page.load()?.bump
+    )]
+    pub holder_page: AccountLoader<'info, state::HolderPage>,
+
+    // required when holders share rewards
+    #[account{
+        seeds = [state::RewardPool::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
+        bump = reward_pool.bump
+    }]
+    reward_pool: Option<Box<Account<'info, state::RewardPool>>>,
+
+    // accounts
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub passes_owner: AccountInfo<'info>,
+
+    // programs
+    pub system_program: Program<'info, System>,
+}
+
+// Move the whole balance into the page and close the balance account, refunding its rent
+
+pub fn convert_to_holder_page(ctx: Context<ConvertToHolderPage>) -> Result<()> {
+    let amount = ctx.accounts.passes_balance.amount;
+    let holder = ctx.accounts.holder.key();
+    require!(amount > 0, PassesError::ZeroAmount);
+    require!(
+        ctx.accounts.passes_balance.staked_amount == 0,
+        PassesError::PassesStaked
+    );
+    ctx.accounts
+        .passes_supply
+        .check_holder_page(Clock::get()?.unix_timestamp)?;
+
+    // the balance account closes, so its rewards must be claimed first
+    let passes_supply = &mut ctx.accounts.passes_supply;
+    if let Some(reward_pool) = get_reward_pool(passes_supply, &mut ctx.accounts.reward_pool)? {
+        let passes_balance = &mut ctx.accounts.passes_balance;
+        passes_balance.settle_rewards(reward_pool)?;
+        require!(
+            passes_balance.pending_token_rewards == 0 && passes_balance.pending_sol_rewards == 0,
+            PassesError::UnclaimedRewards
+        );
+    }
+    passes_supply.paged_amount = passes_supply
+        .paged_amount
+        .checked_add(amount)
+        .ok_or(PassesError::MathOverflow)?;
+
+    let holder_page = &mut ctx.accounts.holder_page.load_mut()?;
+    let page_amount = holder_page.deposit(&holder, amount)?;
+
+    msg!(
+        "Convert to holder page: owner {}, holder {}, page {}, amount {}, page balance {}",
+        ctx.accounts.passes_owner.key(),
+        holder,
+        holder_page.page,
+        amount,
+        page_amount
+    );
+
+    Ok(())
+}
+
+// Move the holder's passes from the page back to their balance account
+
+pub fn convert_from_holder_page(ctx: Context<ConvertFromHolderPage>) -> Result<()> {
+    let holder = ctx.accounts.holder.key();
+    let holder_page = &mut ctx.accounts.holder_page.load_mut()?;
+    let amount = holder_page.withdraw(&holder)?;
+
+    let passes_supply = &mut ctx.accounts.passes_supply;
+    passes_supply.paged_amount = passes_supply.paged_amount.saturating_sub(amount);
+
+    let passes_balance = &mut ctx.accounts.passes_balance;
+    if let Some(reward_pool) = get_reward_pool(passes_supply, &mut ctx.accounts.reward_pool)? {
+        passes_balance.settle_rewards(reward_pool)?;
+    }
+    passes_balance.amount = passes_balance
+        .amount
+        .checked_add(amount)
+        .ok_or(PassesError::MathOverflow)?;
+    if holder != ctx.accounts.passes_owner.key() {
+        passes_supply.check_wallet_cap(passes_balance.amount, passes_supply.amount)?;
+    }
+    // pages don't keep buy times, so paged passes start a new holding period
+    passes_balance.last_buy_ts = Clock::get()?.unix_timestamp;
+    passes_balance.version = state::PassesBalance::VERSION;
+    passes_balance.bump = ctx.bumps.passes_balance;
+
+    msg!(
+        "Convert from holder page: owner {}, holder {}, page {}, amount {}, balance {}",
+        ctx.accounts.passes_owner.key(),
+        holder,
+        holder_page.page,
+        amount,
+        passes_balance.amount
+    );
+
+    Ok(())
+}
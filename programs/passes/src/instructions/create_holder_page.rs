@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+
+use crate::state;
+
+// Create a page of compact passes balances for the owner's holders
+
+#[derive(Accounts)]
+#[instruction(page: u32)]
+pub struct CreateHolderPage<'info> {
+    // signer
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    // derived PDAs
+    #[account(
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, state::Config>>,
+
+    #[account{
+        seeds = [state::PassesSupply::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
+        bump = passes_supply.bump
+    }]
+    passes_supply: Box<Account<'info, state::PassesSupply>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = state::HolderPage::LEN,
+        seeds = [state::HolderPage::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref(), page.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub holder_page: AccountLoader<'info, state::HolderPage>,
+
+    // accounts
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub passes_owner: AccountInfo<'info>,
+
+    // programs
+    pub system_program: Program<'info, System>,
+}
+
+pub fn create_holder_page(ctx: Context<CreateHolderPage>, page: u32) -> Result<()> {
+    let holder_page = &mut ctx.accounts.holder_page.load_init()?;
+    holder_page.passes_owner = ctx.accounts.passes_owner.key();
+    holder_page.page = page;
+    holder_page.bump = ctx.bumps.holder_page;
+
+    msg!(
+        "Create holder page: owner {}, page {}, payer {}",
+        holder_page.passes_owner,
+        page,
+        ctx.accounts.payer.key()
+    );
+
+    Ok(())
+}
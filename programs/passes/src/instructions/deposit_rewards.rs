@@ -0,0 +1,157 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use solana_program::system_instruction;
+
+use crate::{common::transfer_tokens_from_user, error::PassesError, state};
+
+// Fund the rewards of the holders of an owner's passes, the deposit waits in escrow until claimed
+
+#[derive(Accounts)]
+pub struct DepositRewards<'info> {
+    // signer
+    pub depositor: Signer<'info>,
+
+    // derived PDAs
+    #[account{
+        seeds = [state::PassesSupply::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
+        bump = passes_supply.bump
+    }]
+    passes_supply: Box<Account<'info, state::PassesSupply>>,
+
+    #[account{
+        mut,
+        seeds = [state::RewardPool::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
+        bump = reward_pool.bump
+    }]
+    reward_pool: Box<Account<'info, state::RewardPool>>,
+
+    #[account(
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, state::Config>>,
+
+    #[account(
+        mut,
+        seeds = [state::Config::ESCROW_TOKEN_SEED, config.marketplace_seed().as_ref(), payment_mint.key().as_ref()],
+        bump,
+        token::mint = payment_mint,
+        token::authority = config
+    )]
+    pub escrow_wallet: Box<Account<'info, TokenAccount>>,
+
+    // accounts
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub passes_owner: AccountInfo<'info>,
+
+    #[account(address = config.payment_mint)]
+    pub payment_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        constraint = depositor_wallet.owner == depositor.key(),
+        constraint = depositor_wallet.mint == payment_mint.key()
+    )]
+    depositor_wallet: Box<Account<'info, TokenAccount>>,
+
+    // programs
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct DepositRewardsSol<'info> {
+    // signer
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    // derived PDAs
+    #[account(
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, state::Config>>,
+
+    #[account{
+        seeds = [state::PassesSupply::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
+        bump = passes_supply.bump
+    }]
+    passes_supply: Box<Account<'info, state::PassesSupply>>,
+
+    #[account{
+        mut,
+        seeds = [state::RewardPool::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
+        bump = reward_pool.bump
+    }]
+    reward_pool: Box<Account<'info, state::RewardPool>>,
+
+    #[account(
+        mut,
+        seeds = [state::EscrowSOL::SEED, config.marketplace_seed().as_ref()],
+        bump = escrow_wallet.bump
+    )]
+    pub escrow_wallet: Box<Account<'info, state::EscrowSOL>>,
+
+    // accounts
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub passes_owner: AccountInfo<'info>,
+
+    // programs
+    pub system_program: Program<'info, System>,
+}
+
+// Deposit token rewards
+
+pub fn deposit_rewards(ctx: Context<DepositRewards>, amount: u64) -> Result<()> {
+    require!(amount > 0, PassesError::ZeroAmount);
+    let supply = ctx.accounts.passes_supply.rewarded_supply();
+    require!(supply > 0, PassesError::ZeroSupply);
+
+    transfer_tokens_from_user(
+        ctx.accounts.depositor_wallet.to_account_info(),
+        ctx.accounts.escrow_wallet.to_account_info(),
+        ctx.accounts.depositor.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        amount,
+    )?;
+    ctx.accounts.reward_pool.distribute_token(amount, supply)?;
+
+    msg!(
+        "Deposit rewards: owner {}, depositor {}, amount {}, supply {}",
+        ctx.accounts.passes_owner.key(),
+        ctx.accounts.depositor.key(),
+        amount,
+        supply
+    );
+
+    Ok(())
+}
+
+// Deposit SOL rewards
+
+pub fn deposit_rewards_sol(ctx: Context<DepositRewardsSol>, amount: u64) -> Result<()> {
+    require!(amount > 0, PassesError::ZeroAmount);
+    let supply = ctx.accounts.passes_supply.rewarded_supply();
+    require!(supply > 0, PassesError::ZeroSupply);
+
+    let from = ctx.accounts.depositor.to_account_info();
+    let to = ctx.accounts.escrow_wallet.to_account_info();
+    anchor_lang::solana_program::program::invoke(
+        &system_instruction::transfer(from.key, to.key, amount),
+        &[
+            from.clone(),
+            to,
+            ctx.accounts.system_program.to_account_info(),
+        ],
+    )?;
+    ctx.accounts.reward_pool.distribute_sol(amount, supply)?;
+
+    msg!(
+        "Deposit SOL rewards: owner {}, depositor {}, amount {}, supply {}",
+        ctx.accounts.passes_owner.key(),
+        ctx.accounts.depositor.key(),
+        amount,
+        supply
+    );
+
+    Ok(())
+}
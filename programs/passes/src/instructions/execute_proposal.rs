@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+use crate::{
+    error::PassesError,
+    state::{self, ConfigChange},
+};
+
+// Apply a config change once the proposal has enough approvals
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    // signer
+    #[account(
+        constraint = config.is_admin(executor.key) @ PassesError::NotAnAdmin
+    )]
+    pub executor: Signer<'info>,
+
+    // derived PDAs
+    #[account(
+        mut,
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, state::Config>>,
+
+    #[account(
+        mut,
+        seeds = [state::Proposal::SEED, config.marketplace_seed().as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+        constraint = !proposal.executed @ PassesError::ProposalExecuted
+    )]
+    pub proposal: Box<Account<'info, state::Proposal>>,
+
+    // accounts
+    #[account(
+        token::mint = config.payment_mint
+    )]
+    pub protocol_fee_token_wallet: Option<Box<Account<'info, TokenAccount>>>, // required to change the token fee destination
+}
+
+pub fn execute_proposal(ctx: Context<ExecuteProposal>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let proposal = &mut ctx.accounts.proposal;
+
+    require!(
+        !proposal.is_expired(Clock::get()?.unix_timestamp),
+        PassesError::ProposalExpired
+    );
+
+    let approvals = proposal.valid_approvals(config);
+    require!(
+        approvals >= config.admin_threshold as usize,
+        PassesError::NotEnoughApprovals
+    );
+
+    match &proposal.change {
+        ConfigChange::ProtocolFeeBps { fee_bps } => {
+            let owner_fee_bps = config.owner_fee_bps;
+            config.set_fees(*fee_bps, owner_fee_bps)?
+        }
+        ConfigChange::OwnerFeeBps { fee_bps } => {
+            let protocol_fee_bps = config.protocol_fee_bps;
+            config.set_fees(protocol_fee_bps, *fee_bps)?
+        }
+        ConfigChange::ProtocolFeeTokenWallet { wallet } => {
+            let token_wallet = ctx
+                .accounts
+                .protocol_fee_token_wallet
+                .as_ref()
+                .ok_or(PassesError::InvalidFeeWallet)?;
+            require_keys_eq!(token_wallet.key(), *wallet, PassesError::InvalidFeeWallet);
+            config.protocol_fee_token_wallet = *wallet;
+        }
+        ConfigChange::ProtocolFeeSolWallet { wallet } => config.protocol_fee_sol_wallet = *wallet,
+        ConfigChange::Pause {
+            buys,
+            sells,
+            issuance,
+        } => {
+            config.buys_paused = *buys;
+            config.sells_paused = *sells;
+            config.issuance_paused = *issuance;
+        }
+        ConfigChange::Guardian { guardian } => config.guardian = *guardian,
+        ConfigChange::Admins { admins, threshold } => config.set_admins(admins, *threshold)?,
+        // admins then run isolate_reserve with the executed proposal
+        ConfigChange::IsolateReserve { .. } => {}
+    }
+    proposal.executed = true;
+
+    msg!(
+        "Execute proposal: id {}, approvals {}, change {:?}",
+        proposal.id,
+        approvals,
+        proposal.change
+    );
+
+    Ok(())
+}
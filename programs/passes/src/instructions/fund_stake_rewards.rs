@@ -0,0 +1,88 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{common::transfer_tokens_from_user, error::PassesError, state};
+
+// Fund the token rewards of the stakers in the owner's market, paid out evenly over `duration` seconds.
+// Rewards still unpaid from an earlier funding roll into the new period.
+
+#[derive(Accounts)]
+pub struct FundStakeRewards<'info> {
+    // signer
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    // derived PDAs
+    #[account{
+        init_if_needed,
+        payer = owner,
+        space = state::StakePool::LEN,
+        seeds = [state::StakePool::SEED, config.marketplace_seed().as_ref(), owner.key.as_ref()],
+        bump,
+    }]
+    stake_pool: Box<Account<'info, state::StakePool>>,
+
+    #[account(
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, state::Config>>,
+
+    #[account(
+        mut,
+        seeds = [state::Config::ESCROW_TOKEN_SEED, config.marketplace_seed().as_ref(), payment_mint.key().as_ref()],
+        bump,
+        token::mint = payment_mint,
+        token::authority = config
+    )]
+    pub escrow_wallet: Box<Account<'info, TokenAccount>>,
+
+    // accounts
+    #[account(address = config.payment_mint)]
+    pub payment_mint: Box<Account<'info, Mint>>,
+
+    #[account(
+        mut,
+        constraint = owner_wallet.owner == owner.key(),
+        constraint = owner_wallet.mint == payment_mint.key()
+    )]
+    owner_wallet: Box<Account<'info, TokenAccount>>,
+
+    // programs
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+pub fn fund_stake_rewards(
+    ctx: Context<FundStakeRewards>,
+    amount: u64,
+    duration: i64,
+) -> Result<()> {
+    require!(amount > 0, PassesError::ZeroAmount);
+    let now = Clock::get()?.unix_timestamp;
+
+    let stake_pool = &mut ctx.accounts.stake_pool;
+    stake_pool.version = state::StakePool::VERSION;
+    stake_pool.owner = ctx.accounts.owner.key();
+    stake_pool.bump = ctx.bumps.stake_pool;
+    stake_pool.update(now)?;
+    stake_pool.fund(amount, duration, now)?;
+
+    transfer_tokens_from_user(
+        ctx.accounts.owner_wallet.to_account_info(),
+        ctx.accounts.escrow_wallet.to_account_info(),
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        amount,
+    )?;
+
+    msg!(
+        "Fund stake rewards: owner {}, amount {}, reward_rate {}, reward_end_ts {}",
+        stake_pool.owner,
+        amount,
+        stake_pool.reward_rate,
+        stake_pool.reward_end_ts
+    );
+
+    Ok(())
+}
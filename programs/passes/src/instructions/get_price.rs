@@ -12,9 +12,9 @@ pub struct GetPrice<'info> {
 }
 
 pub fn get_price(_ctx: Context<GetPrice>, supply: u64, amount: u64) -> Result<u64> {
-    Ok(calc_price(supply, amount))
+    calc_price(supply, amount)
 }
 
 pub fn get_price_sol(_ctx: Context<GetPrice>, supply: u64, amount: u64) -> Result<u64> {
-    Ok(calc_price_sol(supply, amount))
+    calc_price_sol(supply, amount)
 }
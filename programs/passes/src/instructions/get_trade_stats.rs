@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+use crate::state;
+
+// Return the cumulative trading stats of a market, for views and CPI
+
+#[derive(Accounts)]
+pub struct GetTradeStats<'info> {
+    // derived PDAs
+    #[account(
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, state::Config>>,
+
+    #[account(
+        seeds = [state::PassesSupply::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
+        bump = passes_supply.bump
+    )]
+    pub passes_supply: Box<Account<'info, state::PassesSupply>>,
+
+    // accounts
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub passes_owner: AccountInfo<'info>,
+}
+
+pub fn get_trade_stats(ctx: Context<GetTradeStats>) -> Result<state::TradeStats> {
+    let stats = ctx.accounts.passes_supply.stats.clone();
+
+    msg!(
+        "Trade stats: owner {}, trade_count {}, last_price {}, last_trade_ts {}, ath_supply {}",
+        ctx.accounts.passes_owner.key(),
+        stats.trade_count,
+        stats.last_price,
+        stats.last_trade_ts,
+        stats.ath_supply
+    );
+
+    Ok(stats)
+}
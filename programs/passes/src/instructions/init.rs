@@ -4,22 +4,27 @@ use anchor_spl::{
     token::{Mint, Token, TokenAccount},
 };
 
-use crate::state;
+use crate::{error::PassesError, program::Passes, state};
 
-// Initialize contract setting authority (admin)
+// Initialize a marketplace setting its authority (admin). Marketplace 0 is the default one,
+// other ids run separate marketplaces with their own config, escrows and markets.
 
 #[derive(Accounts)]
+#[instruction(marketplace_id: u64)]
 pub struct Init<'info> {
     // signer
     #[account(mut)]
     pub admin: Signer<'info>,
 
+    // approves new marketplaces, it can be the admin
+    pub authority: Signer<'info>,
+
     // derived PDAs
     #[account(
         init,
         payer = admin,
         space = state::Config::LEN,
-        seeds = [state::Config::SEED],
+        seeds = [state::Config::SEED, state::marketplace_seed(marketplace_id).as_ref()],
         bump
     )]
     pub config: Account<'info, state::Config>,
@@ -27,7 +32,7 @@ pub struct Init<'info> {
     #[account(
         init,
         payer = admin,
-        seeds = [b"escrow", payment_mint.key().as_ref()],
+        seeds = [state::Config::ESCROW_TOKEN_SEED, state::marketplace_seed(marketplace_id).as_ref(), payment_mint.key().as_ref()],
         bump,
         token::mint = payment_mint,
         token::authority = config
@@ -38,7 +43,7 @@ pub struct Init<'info> {
         init,
         payer = admin,
         space = state::EscrowSOL::LEN,
-        seeds = [state::EscrowSOL::SEED],
+        seeds = [state::EscrowSOL::SEED, state::marketplace_seed(marketplace_id).as_ref()],
         bump
     )]
     pub escrow_sol_wallet: Account<'info, state::EscrowSOL>, // escrow wallet for SOL payment
@@ -51,10 +56,13 @@ pub struct Init<'info> {
     )]
     pub protocol_fee_wallet: Account<'info, TokenAccount>, // protocol's ATA to get fees
 
-    // #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
-    // pub program: Program<'info, Passes>,
-    // #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()))]
-    // pub program_data: Account<'info, ProgramData>,
+    // only the program upgrade authority can initialize a marketplace
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, Passes>,
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key()) @ PassesError::NotUpgradeAuthority
+    )]
+    pub program_data: Account<'info, ProgramData>,
 
     // accounts
     pub payment_mint: Account<'info, Mint>, // e.g. USDC mint account
@@ -65,27 +73,36 @@ pub struct Init<'info> {
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
-pub fn init(ctx: Context<Init>, protocol_fee_bps: u64, owner_fee_bps: u64) -> Result<()> {
+pub fn init(
+    ctx: Context<Init>,
+    marketplace_id: u64,
+    protocol_fee_bps: u64,
+    owner_fee_bps: u64,
+) -> Result<()> {
     let config = &mut ctx.accounts.config;
+    config.version = state::Config::VERSION;
+    config.marketplace_id = marketplace_id;
     config.admin = *ctx.accounts.admin.key;
     config.payment_mint = ctx.accounts.payment_mint.key();
     config.escrow_token_wallet = ctx.accounts.escrow_token_wallet.key();
     config.escrow_sol_wallet = ctx.accounts.escrow_sol_wallet.key();
     config.protocol_fee_token_wallet = ctx.accounts.protocol_fee_wallet.key();
-    config.protocol_fee_bps = protocol_fee_bps;
-    config.owner_fee_bps = owner_fee_bps;
+    config.protocol_fee_sol_wallet = *ctx.accounts.admin.key;
+    config.set_fees(protocol_fee_bps, owner_fee_bps)?;
     config.bump = ctx.bumps.config;
 
     ctx.accounts.escrow_sol_wallet.bump = ctx.bumps.escrow_sol_wallet;
 
     msg!(
-            "Init: program admin {}, config {}, payment mint {}, escrow token wallet {}, escrow sol wallet {}, protocol fee token wallet {}, protocol fee bps {}, owner fee bps {}",
+            "Init: marketplace {}, program admin {}, config {}, payment mint {}, escrow token wallet {}, escrow sol wallet {}, protocol fee token wallet {}, protocol fee sol wallet {}, protocol fee bps {}, owner fee bps {}",
+            config.marketplace_id,
             config.admin,
             config.key(),
             config.payment_mint,
             config.escrow_token_wallet,
             config.escrow_sol_wallet,
             config.protocol_fee_token_wallet,
+            config.protocol_fee_sol_wallet,
             config.protocol_fee_bps,
             config.owner_fee_bps
         );
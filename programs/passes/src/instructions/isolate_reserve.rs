@@ -0,0 +1,167 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+use crate::{
+    common::transfer_tokens,
+    error::PassesError,
+    state::{self, ConfigChange},
+};
+
+// Migrate an existing market to an isolated reserve, moving its share of the marketplace
+// escrows to its own reserve wallets. Markets opt in at issuance with `isolated_reserve`.
+// A multisig config isolates a market once admins executed a proposal for it.
+
+#[derive(Accounts)]
+pub struct IsolateReserve<'info> {
+    // signer
+    #[account(
+        mut,
+        constraint = admin.key() == config.admin || config.is_admin(admin.key) @ PassesError::NotAnAdmin
+    )]
+    pub admin: Signer<'info>,
+
+    // derived PDAs
+    #[account(
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, state::Config>>,
+
+    // required when the config is a multisig
+    #[account(
+        seeds = [state::Proposal::SEED, config.marketplace_seed().as_ref(), proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Option<Box<Account<'info, state::Proposal>>>,
+
+    #[account{
+        mut,
+        seeds = [state::PassesSupply::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
+        bump = passes_supply.bump
+    }]
+    passes_supply: Box<Account<'info, state::PassesSupply>>,
+
+    #[account(
+        init,
+        payer = admin,
+        seeds = [state::PassesSupply::RESERVE_TOKEN_SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
+        bump,
+        token::mint = payment_mint,
+        token::authority = config
+    )]
+    pub reserve_token_wallet: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = state::EscrowSOL::LEN,
+        seeds = [state::PassesSupply::RESERVE_SOL_SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
+        bump
+    )]
+    pub reserve_sol_wallet: Box<Account<'info, state::EscrowSOL>>,
+
+    #[account(
+        mut,
+        seeds = [state::Config::ESCROW_TOKEN_SEED, config.marketplace_seed().as_ref(), payment_mint.key().as_ref()],
+        bump,
+        token::mint = payment_mint,
+        token::authority = config
+    )]
+    pub escrow_token_wallet: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [state::EscrowSOL::SEED, config.marketplace_seed().as_ref()],
+        bump = escrow_sol_wallet.bump
+    )]
+    pub escrow_sol_wallet: Box<Account<'info, state::EscrowSOL>>,
+
+    // accounts
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub passes_owner: AccountInfo<'info>,
+
+    #[account(
+        constraint = payment_mint.key() == config.payment_mint,
+    )]
+    pub payment_mint: Box<Account<'info, Mint>>,
+
+    // programs
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+// The escrows don't record which market their funds belong to. Every buy adds its price to the
+// reserve and every sell takes its price out, so a market whose stats tracked it since issuance
+// owns its net trade volume in each currency. Markets migrated from before the stats can't tell
+// their share and keep the escrows.
+
+pub fn isolate_reserve(ctx: Context<IsolateReserve>) -> Result<()> {
+    let owner = ctx.accounts.passes_owner.key();
+    let config = &ctx.accounts.config;
+    let passes_supply = &mut ctx.accounts.passes_supply;
+
+    if config.is_multisig() {
+        let proposal = ctx
+            .accounts
+            .proposal
+            .as_ref()
+            .ok_or(PassesError::MultisigRequired)?;
+        require!(
+            proposal.executed && proposal.change == ConfigChange::IsolateReserve { owner },
+            PassesError::InvalidProposal
+        );
+    } else {
+        require_keys_eq!(
+            ctx.accounts.admin.key(),
+            config.admin,
+            PassesError::NotAnAdmin
+        );
+    }
+
+    require!(
+        !passes_supply.isolated_reserve,
+        PassesError::ReserveAlreadyIsolated
+    );
+    require!(
+        passes_supply.stats_complete,
+        PassesError::IncompleteTradeStats
+    );
+
+    // a market that paid out more in a currency than it took in keeps nothing of it
+    let stats = &passes_supply.stats;
+    let token_reserve = stats.buy_volume.saturating_sub(stats.sell_volume);
+    let sol_reserve = stats.buy_volume_sol.saturating_sub(stats.sell_volume_sol);
+
+    // move the token reserve
+    let marketplace_seed = ctx.accounts.config.marketplace_seed();
+    let bump_vector = ctx.accounts.config.bump.to_le_bytes();
+    let authority_seeds: &[&[&[u8]]] = &[&[
+        state::Config::SEED,
+        marketplace_seed.as_ref(),
+        bump_vector.as_ref(),
+    ]];
+    transfer_tokens(
+        ctx.accounts.escrow_token_wallet.to_account_info(),
+        ctx.accounts.reserve_token_wallet.to_account_info(),
+        ctx.accounts.config.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        token_reserve,
+        authority_seeds,
+    )?;
+
+    // move the SOL reserve
+    ctx.accounts.escrow_sol_wallet.sub_lamports(sol_reserve)?;
+    ctx.accounts.reserve_sol_wallet.add_lamports(sol_reserve)?;
+
+    ctx.accounts.reserve_sol_wallet.bump = ctx.bumps.reserve_sol_wallet;
+    passes_supply.isolated_reserve = true;
+
+    msg!(
+        "Isolate reserve: owner {}, token reserve {}, sol reserve {}",
+        owner,
+        token_reserve,
+        sol_reserve
+    );
+
+    Ok(())
+}
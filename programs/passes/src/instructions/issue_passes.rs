@@ -21,7 +21,7 @@ pub struct IssuePasses<'info> {
         init,
         payer = owner,
         space = state::PassesSupply::LEN,
-        seeds = [b"supply", owner.key.as_ref(),],
+        seeds = [state::PassesSupply::SEED, config.marketplace_seed().as_ref(), owner.key.as_ref(),],
         bump,
     }]
     passes_supply: Box<Account<'info, state::PassesSupply>>,
@@ -30,17 +30,56 @@ pub struct IssuePasses<'info> {
         init,
         payer = owner,
         space = state::PassesBalance::LEN,
-        seeds = [b"balance", owner.key.as_ref(), owner.key.as_ref()],
+        seeds = [state::PassesBalance::SEED, config.marketplace_seed().as_ref(), owner.key.as_ref(), owner.key.as_ref()],
         bump,
     }]
     passes_balance: Box<Account<'info, state::PassesBalance>>,
 
+    #[account{
+        init,
+        payer = owner,
+        space = state::PassesMetadata::LEN,
+        seeds = [state::PassesMetadata::SEED, config.marketplace_seed().as_ref(), owner.key.as_ref()],
+        bump,
+    }]
+    passes_metadata: Box<Account<'info, state::PassesMetadata>>,
+
+    // only needed when the issued passes vest
+    #[account{
+        init,
+        payer = owner,
+        space = state::OwnerVesting::LEN,
+        seeds = [state::OwnerVesting::SEED, config.marketplace_seed().as_ref(), owner.key.as_ref()],
+        bump,
+    }]
+    owner_vesting: Option<Box<Account<'info, state::OwnerVesting>>>,
+
     #[account(
-        seeds = [state::Config::SEED],
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
         bump = config.bump
     )]
     pub config: Box<Account<'info, state::Config>>,
 
+    // only needed when the market's reserve is isolated
+    #[account(
+        init,
+        payer = owner,
+        seeds = [state::PassesSupply::RESERVE_TOKEN_SEED, config.marketplace_seed().as_ref(), owner.key.as_ref()],
+        bump,
+        token::mint = payment_mint,
+        token::authority = config
+    )]
+    pub reserve_token_wallet: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = state::EscrowSOL::LEN,
+        seeds = [state::PassesSupply::RESERVE_SOL_SEED, config.marketplace_seed().as_ref(), owner.key.as_ref()],
+        bump
+    )]
+    pub reserve_sol_wallet: Option<Box<Account<'info, state::EscrowSOL>>>,
+
     #[account(
         init_if_needed,
         payer = owner,
@@ -61,12 +100,35 @@ pub struct IssuePasses<'info> {
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
-pub fn issue_passes(ctx: Context<IssuePasses>, amount: u64) -> Result<()> {
+pub fn issue_passes(
+    ctx: Context<IssuePasses>,
+    amount: u64,
+    params: state::IssuanceParams,
+) -> Result<()> {
     let supply = ctx.accounts.passes_supply.amount;
     let owner = ctx.accounts.owner.key();
+    let marketplace_id = ctx.accounts.config.marketplace_id;
 
+    require!(!ctx.accounts.config.issuance_paused, PassesError::Paused);
     require!(supply == 0, PassesError::PassesAlreadyIssued);
     require!(amount > 0, PassesError::ZeroAmount);
+    require!(
+        params.max_supply == 0 || params.max_supply >= amount,
+        PassesError::InvalidIssuanceParams
+    );
+    require!(
+        params.price_offset <= state::PassesSupply::MAX_PRICE_OFFSET,
+        PassesError::InvalidIssuanceParams
+    );
+    require!(
+        params.vesting_duration >= 0,
+        PassesError::InvalidIssuanceParams
+    );
+    require!(
+        params.presale_end_ts == 0
+            || (params.presale_end_ts > params.launch_ts && params.presale_wallet_cap > 0),
+        PassesError::InvalidIssuanceParams
+    );
 
     let passes_balance = &mut ctx.accounts.passes_balance;
     let passes_supply = &mut ctx.accounts.passes_supply;
@@ -79,10 +141,71 @@ pub fn issue_passes(ctx: Context<IssuePasses>, amount: u64) -> Result<()> {
         .checked_add(amount)
         .ok_or(PassesError::MathOverflow)?;
 
+    passes_balance.version = state::PassesBalance::VERSION;
     passes_balance.bump = ctx.bumps.passes_balance;
+    passes_supply.version = state::PassesSupply::VERSION;
     passes_supply.bump = ctx.bumps.passes_supply;
+    passes_supply.max_supply = params.max_supply;
+    passes_supply.launch_ts = params.launch_ts;
+    passes_supply.price_offset = params.price_offset;
+    passes_supply.presale_merkle_root = params.presale_merkle_root;
+    passes_supply.presale_end_ts = params.presale_end_ts;
+    passes_supply.presale_wallet_cap = params.presale_wallet_cap;
+    passes_supply.stats.ath_supply = passes_supply.amount;
+    passes_supply.stats_complete = true;
+    passes_supply.issued_amount = amount;
+
+    if params.vesting_duration > 0 {
+        let owner_vesting = ctx
+            .accounts
+            .owner_vesting
+            .as_mut()
+            .ok_or(PassesError::VestingAccountMissing)?;
+        let start_ts = params.launch_ts.max(Clock::get()?.unix_timestamp);
+        owner_vesting.version = state::OwnerVesting::VERSION;
+        owner_vesting.owner = owner;
+        owner_vesting.amount = amount;
+        owner_vesting.start_ts = start_ts;
+        owner_vesting.end_ts = start_ts
+            .checked_add(params.vesting_duration)
+            .ok_or(PassesError::MathOverflow)?;
+        owner_vesting.bump = ctx.bumps.owner_vesting;
+        passes_supply.vesting = true;
+    }
+
+    // trades move the market's reserve in and out of its own wallets
+    if params.isolated_reserve {
+        require!(
+            ctx.accounts.reserve_token_wallet.is_some(),
+            PassesError::ReserveWalletMissing
+        );
+        let reserve_sol_wallet = ctx
+            .accounts
+            .reserve_sol_wallet
+            .as_mut()
+            .ok_or(PassesError::ReserveWalletMissing)?;
+        reserve_sol_wallet.bump = ctx.bumps.reserve_sol_wallet;
+        passes_supply.isolated_reserve = true;
+    }
+
+    // the owner fills in the metadata with update_passes_metadata
+    let passes_metadata = &mut ctx.accounts.passes_metadata;
+    passes_metadata.version = state::PassesMetadata::VERSION;
+    passes_metadata.owner = owner;
+    passes_metadata.bump = ctx.bumps.passes_metadata;
 
-    msg!("Issue passes: owner {}, amount {}", owner, amount);
+    msg!(
+        "Issue passes: marketplace_id {}, owner {}, amount {}, max_supply {}, launch_ts {}, price_offset {}, presale_end_ts {}, vesting_duration {}, isolated_reserve {}",
+        marketplace_id,
+        owner,
+        amount,
+        params.max_supply,
+        params.launch_ts,
+        params.price_offset,
+        params.presale_end_ts,
+        params.vesting_duration,
+        params.isolated_reserve
+    );
 
     Ok(())
 }
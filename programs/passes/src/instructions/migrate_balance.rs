@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+
+use crate::{common::realloc_account, state};
+
+// Upgrade a passes balance account from the unversioned or version 1 layout to the current one.
+// Version 1 balances exist in every marketplace, so they are found under the marketplace seed.
+
+#[derive(Accounts)]
+pub struct MigrateBalance<'info> {
+    // signer
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    // derived PDAs
+    #[account(
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, state::Config>>,
+
+    /// CHECK: The old layout cannot be deserialized as `PassesBalance`, it is checked in the handler
+    #[account(
+        mut,
+        seeds = [state::PassesBalance::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref(), holder.key.as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub passes_balance: UncheckedAccount<'info>,
+
+    // accounts
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub passes_owner: AccountInfo<'info>,
+
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub holder: AccountInfo<'info>,
+
+    // programs
+    pub system_program: Program<'info, System>,
+}
+
+pub fn migrate_balance(ctx: Context<MigrateBalance>) -> Result<()> {
+    let balance_info = ctx.accounts.passes_balance.to_account_info();
+
+    let mut passes_balance =
+        state::PassesBalance::try_deserialize_unmigrated(&balance_info.try_borrow_data()?)?;
+    passes_balance.version = state::PassesBalance::VERSION;
+
+    realloc_account(
+        balance_info.clone(),
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        state::PassesBalance::LEN,
+    )?;
+    passes_balance.try_serialize(&mut &mut balance_info.try_borrow_mut_data()?[..])?;
+
+    msg!(
+        "Migrate passes balance: marketplace_id {}, owner {}, holder {}, version {}",
+        ctx.accounts.config.marketplace_id,
+        ctx.accounts.passes_owner.key(),
+        ctx.accounts.holder.key(),
+        passes_balance.version
+    );
+
+    Ok(())
+}
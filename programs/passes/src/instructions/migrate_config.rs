@@ -0,0 +1,84 @@
+use anchor_lang::{prelude::*, Discriminator};
+
+use crate::{
+    common::realloc_account,
+    error::PassesError,
+    state::{self, v0, MAX_ADMINS},
+};
+
+// Upgrade the config account from the unversioned layout to the current one
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    // signer
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    // derived PDAs
+    /// CHECK: The old layout cannot be deserialized as `Config`, it is checked in the handler
+    #[account(
+        mut,
+        seeds = [state::Config::SEED],
+        bump,
+        owner = crate::ID
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    // programs
+    pub system_program: Program<'info, System>,
+}
+
+pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+    let config_info = ctx.accounts.config.to_account_info();
+
+    let old = {
+        let data = config_info.try_borrow_data()?;
+        require!(
+            data.len() == v0::Config::LEN && data[..8] == state::Config::DISCRIMINATOR,
+            PassesError::InvalidAccountVersion
+        );
+        v0::Config::deserialize(&mut &data[8..])?
+    };
+    require_keys_eq!(old.admin, ctx.accounts.admin.key(), PassesError::NotAnAdmin);
+
+    let config = state::Config {
+        version: state::Config::VERSION,
+        admin: old.admin,
+        guardian: Pubkey::default(),
+        payment_mint: old.payment_mint,
+        escrow_token_wallet: old.escrow_token_wallet,
+        escrow_sol_wallet: old.escrow_sol_wallet,
+        protocol_fee_bps: old.protocol_fee_bps,
+        owner_fee_bps: old.owner_fee_bps,
+        protocol_fee_token_wallet: old.protocol_fee_token_wallet,
+        // SOL fees used to go to the admin
+        protocol_fee_sol_wallet: old.admin,
+        buys_paused: false,
+        sells_paused: false,
+        issuance_paused: false,
+        admins: [Pubkey::default(); MAX_ADMINS],
+        admins_len: 0,
+        admin_threshold: 0,
+        proposal_count: 0,
+        bump: old.bump,
+        // accounts before versioning belong to the default marketplace
+        marketplace_id: 0,
+        reserved: [0; 120],
+    };
+
+    realloc_account(
+        config_info.clone(),
+        ctx.accounts.admin.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        state::Config::LEN,
+    )?;
+    config.try_serialize(&mut &mut config_info.try_borrow_mut_data()?[..])?;
+
+    msg!(
+        "Migrate config: version {}, len {}",
+        config.version,
+        state::Config::LEN
+    );
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use anchor_lang::{prelude::*, Discriminator};
+
+use crate::{
+    common::realloc_account,
+    error::PassesError,
+    state::{self, v0},
+};
+
+// Upgrade a passes supply account from the unversioned or version 1 layout to the current one
+
+#[derive(Accounts)]
+pub struct MigrateSupply<'info> {
+    // signer
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    // derived PDAs
+    /// CHECK: The old layout cannot be deserialized as `PassesSupply`, it is checked in the handler
+    #[account(
+        mut,
+        seeds = [b"supply", passes_owner.key.as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub passes_supply: UncheckedAccount<'info>,
+
+    // accounts
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub passes_owner: AccountInfo<'info>,
+
+    // programs
+    pub system_program: Program<'info, System>,
+}
+
+pub fn migrate_supply(ctx: Context<MigrateSupply>) -> Result<()> {
+    let supply_info = ctx.accounts.passes_supply.to_account_info();
+
+    let passes_supply = {
+        let data = supply_info.try_borrow_data()?;
+        require!(
+            data[..8] == state::PassesSupply::DISCRIMINATOR,
+            PassesError::InvalidAccountVersion
+        );
+        match data.len() {
+            v0::PassesSupply::LEN => {
+                let old = v0::PassesSupply::deserialize(&mut &data[8..])?;
+                state::PassesSupply {
+                    version: state::PassesSupply::VERSION,
+                    amount: old.amount,
+                    paused: false,
+                    bump: old.bump,
+                    max_supply: 0,
+                    launch_ts: 0,
+                    price_offset: 0,
+                    presale_merkle_root: [0; 32],
+                    presale_end_ts: 0,
+                    presale_wallet_cap: 0,
+                    max_wallet_amount: 0,
+                    max_wallet_bps: 0,
+                    min_hold_secs: 0,
+                    early_exit_fee_bps: 0,
+                    vesting: false,
+                    rewards: false,
+                    paged_amount: 0,
+                    stats: state::TradeStats {
+                        ath_supply: old.amount,
+                        ..Default::default()
+                    },
+                    isolated_reserve: false,
+                    stats_complete: false,
+                    issued_amount: 0,
+                    currency: state::MarketCurrency::Unset,
+                    reserved: [0; 53],
+                }
+            }
+            // the trade stats start in the zeroed reserved space of version 1
+            state::PassesSupply::V1_LEN => {
+                let mut data = data.to_vec();
+                data.resize(state::PassesSupply::LEN, 0);
+                let mut passes_supply = state::PassesSupply::try_deserialize(&mut &data[..])?;
+                require!(
+                    passes_supply.version == 1,
+                    PassesError::InvalidAccountVersion
+                );
+                passes_supply.version = state::PassesSupply::VERSION;
+                passes_supply.stats.ath_supply = passes_supply.amount;
+                passes_supply
+            }
+            _ => return err!(PassesError::InvalidAccountVersion),
+        }
+    };
+
+    realloc_account(
+        supply_info.clone(),
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        state::PassesSupply::LEN,
+    )?;
+    passes_supply.try_serialize(&mut &mut supply_info.try_borrow_mut_data()?[..])?;
+
+    msg!(
+        "Migrate passes supply: owner {}, version {}",
+        ctx.accounts.passes_owner.key(),
+        passes_supply.version
+    );
+
+    Ok(())
+}
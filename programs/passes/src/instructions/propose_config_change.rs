@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::PassesError, state};
+
+// Propose a config change to be approved by the admin multisig
+
+#[derive(Accounts)]
+pub struct ProposeConfigChange<'info> {
+    // signer
+    #[account(
+        mut,
+        constraint = config.is_admin(proposer.key) @ PassesError::NotAnAdmin
+    )]
+    pub proposer: Signer<'info>,
+
+    // derived PDAs
+    #[account(
+        mut,
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, state::Config>>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = state::Proposal::LEN,
+        seeds = [state::Proposal::SEED, config.marketplace_seed().as_ref(), config.proposal_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Box<Account<'info, state::Proposal>>,
+
+    // programs
+    pub system_program: Program<'info, System>,
+}
+
+pub fn propose_config_change(
+    ctx: Context<ProposeConfigChange>,
+    change: state::ConfigChange,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let proposal = &mut ctx.accounts.proposal;
+    let proposer = ctx.accounts.proposer.key();
+
+    proposal.id = config.proposal_count;
+    proposal.proposer = proposer;
+    proposal.change = change;
+    // the proposer approves their own proposal
+    proposal.approvals[0] = proposer;
+    proposal.approvals_len = 1;
+    proposal.bump = ctx.bumps.proposal;
+    proposal.expires_ts = Clock::get()?
+        .unix_timestamp
+        .checked_add(state::Proposal::EXPIRY_SECS)
+        .ok_or(PassesError::MathOverflow)?;
+
+    config.proposal_count = config
+        .proposal_count
+        .checked_add(1)
+        .ok_or(PassesError::MathOverflow)?;
+
+    msg!(
+        "Propose config change: id {}, proposer {}, change {:?}, expires_ts {}",
+        proposal.id,
+        proposer,
+        proposal.change,
+        proposal.expires_ts
+    );
+
+    Ok(())
+}
@@ -8,8 +8,12 @@ use anchor_spl::{
 };
 
 use crate::{
-    common::{calc_fee, calc_price, transfer_tokens, transfer_tokens_from_user},
+    common::{
+        calc_fee, calc_price, get_reserve_wallet, get_reward_pool, split_sell_price,
+        transfer_tokens, transfer_tokens_from_user,
+    },
     error::PassesError,
+    events::PassesTraded,
     state, ONE_USDC,
 };
 
@@ -24,20 +28,35 @@ pub struct SellPasses<'info> {
     // derived PDAs
     #[account{
         mut,
-        seeds = [b"supply", passes_owner.key.as_ref()],
+        seeds = [state::PassesSupply::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
         bump = passes_supply.bump
     }]
     passes_supply: Box<Account<'info, state::PassesSupply>>,
 
     #[account{
         mut,
-        seeds = [b"balance", passes_owner.key.as_ref(), seller.key.as_ref()],
+        seeds = [state::PassesBalance::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref(), seller.key.as_ref()],
         bump = passes_balance.bump
     }]
     passes_balance: Box<Account<'info, state::PassesBalance>>,
 
+    // required when the owner sells and their issued passes vest
+    #[account{
+        seeds = [state::OwnerVesting::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
+        bump = owner_vesting.bump
+    }]
+    owner_vesting: Option<Box<Account<'info, state::OwnerVesting>>>,
+
+    // required when holders share rewards
+    #[account{
+        mut,
+        seeds = [state::RewardPool::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
+        bump = reward_pool.bump
+    }]
+    reward_pool: Option<Box<Account<'info, state::RewardPool>>>,
+
     #[account(
-        seeds = [state::Config::SEED],
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
         bump = config.bump
     )]
     pub config: Box<Account<'info, state::Config>>,
@@ -51,13 +70,23 @@ pub struct SellPasses<'info> {
 
     #[account(
         mut,
-        seeds = [b"escrow", payment_mint.key().as_ref()],
+        seeds = [state::Config::ESCROW_TOKEN_SEED, config.marketplace_seed().as_ref(), payment_mint.key().as_ref()],
         bump,
         token::mint = payment_mint,
         token::authority = config
     )]
     pub escrow_wallet: Box<Account<'info, TokenAccount>>, // escrow wallet (associated token account) to store buyer payments
 
+    // required when the market's reserve is isolated
+    #[account(
+        mut,
+        seeds = [state::PassesSupply::RESERVE_TOKEN_SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
+        bump,
+        token::mint = payment_mint,
+        token::authority = config
+    )]
+    pub reserve_wallet: Option<Box<Account<'info, TokenAccount>>>,
+
     // accounts
     /// CHECK: This is not dangerous because we don't read or write from this account
     pub passes_owner: AccountInfo<'info>, // sell passes for the specified passes owner
@@ -66,7 +95,6 @@ pub struct SellPasses<'info> {
 
     #[account(
         mut,
-        constraint = protocol_fee_wallet.owner == config.admin.key(),
         constraint = protocol_fee_wallet.mint == payment_mint.key(),
         constraint = protocol_fee_wallet.key() == config.protocol_fee_token_wallet
     )]
@@ -91,6 +119,7 @@ pub fn sell_passes(ctx: Context<SellPasses>, amount: u64) -> Result<()> {
     let supply = ctx.accounts.passes_supply.amount;
     let balance = ctx.accounts.passes_balance.amount;
     let owner = ctx.accounts.passes_owner.key();
+    let marketplace_id = ctx.accounts.config.marketplace_id;
     let seller = ctx.accounts.seller.key();
     let mint = ctx.accounts.payment_mint.key();
     let config = &ctx.accounts.config;
@@ -98,28 +127,74 @@ pub fn sell_passes(ctx: Context<SellPasses>, amount: u64) -> Result<()> {
     let passes_balance = &mut ctx.accounts.passes_balance;
     let passes_supply = &mut ctx.accounts.passes_supply;
 
+    require!(
+        !config.sells_paused && !passes_supply.paused,
+        PassesError::Paused
+    );
     require!(supply > amount, PassesError::LastPass);
+    passes_supply.check_currency(false)?;
     require!(balance >= amount, PassesError::InsufficientPasses);
+    require!(
+        passes_balance.unstaked() >= amount,
+        PassesError::PassesStaked
+    );
+    if seller == owner && passes_supply.vesting {
+        let owner_vesting = ctx
+            .accounts
+            .owner_vesting
+            .as_ref()
+            .ok_or(PassesError::VestingAccountMissing)?;
+        let locked = owner_vesting.locked(Clock::get()?.unix_timestamp)?;
+        require!(balance - amount >= locked, PassesError::PassesVesting);
+    }
+    if seller == owner {
+        passes_supply.check_issued(balance, amount)?;
+    }
 
-    let price = calc_price(supply - amount, amount);
+    let price = calc_price(passes_supply.curve_supply(supply - amount)?, amount)?;
     require!(price > 0, PassesError::ZeroPrice);
 
     // calc fees
     let protocol_fees = calc_fee(config.protocol_fee_bps, price)?;
     let owner_fees = calc_fee(config.owner_fee_bps, price)?;
 
-    // send seller token for sold passes
-    let from = ctx.accounts.escrow_wallet.to_account_info();
+    // the holders' share of the owner fees stays in escrow as rewards
+    let mut reward_pool = get_reward_pool(passes_supply, &mut ctx.accounts.reward_pool)?;
+    let holder_fees = match reward_pool.as_deref_mut() {
+        Some(reward_pool) => {
+            reward_pool.share_token_fees(owner_fees, passes_supply.rewarded_supply())?
+        }
+        None => 0,
+    };
+    let owner_fees = owner_fees
+        .checked_sub(holder_fees)
+        .ok_or(PassesError::MathOverflow)?;
+    let now = Clock::get()?.unix_timestamp;
+    let early_exit_fees = passes_supply.early_exit_fee(passes_balance.last_buy_ts, now, price)?;
+    let (sent_amount, owner_fees, early_exit_fees) = split_sell_price(
+        config,
+        passes_supply,
+        price,
+        protocol_fees,
+        owner_fees,
+        holder_fees,
+        early_exit_fees,
+    )?;
+
+    // send seller token for sold passes, from the market's reserve wallet when it's isolated
+    let escrow_wallet = ctx.accounts.escrow_wallet.to_account_info();
+    let reserve_wallet = get_reserve_wallet(passes_supply, &ctx.accounts.reserve_wallet)?;
+    let from = reserve_wallet.clone().unwrap_or(escrow_wallet.clone());
     let to = ctx.accounts.seller_wallet.to_account_info();
     let authority = ctx.accounts.config.to_account_info();
+    let marketplace_seed = ctx.accounts.config.marketplace_seed();
     let bump_vector = ctx.accounts.config.bump.to_le_bytes();
-    let authority_seeds: &[&[&[u8]]] = &[&[b"config", bump_vector.as_ref()]];
+    let authority_seeds: &[&[&[u8]]] = &[&[
+        state::Config::SEED,
+        marketplace_seed.as_ref(),
+        bump_vector.as_ref(),
+    ]];
     let token_program = ctx.accounts.token_program.to_account_info();
-    let sent_amount = price
-        .checked_sub(protocol_fees)
-        .ok_or(PassesError::MathOverflow)?
-        .checked_sub(owner_fees)
-        .ok_or(PassesError::MathOverflow)?;
     transfer_tokens(
         from.clone(),
         to,
@@ -145,18 +220,41 @@ pub fn sell_passes(ctx: Context<SellPasses>, amount: u64) -> Result<()> {
     )?;
     msg!("Send protocol fees: {}", protocol_fees);
 
-    // send owner fees
+    // send owner fees, including the early exit fees
     let to = ctx.accounts.owner_fee_wallet.to_account_info();
     transfer_tokens(
-        from,
+        from.clone(),
         to,
-        authority,
-        token_program,
-        owner_fees,
+        authority.clone(),
+        token_program.clone(),
+        owner_fees
+            .checked_add(early_exit_fees)
+            .ok_or(PassesError::MathOverflow)?,
         authority_seeds,
     )?;
-    msg!("Send owner fees: {}", owner_fees);
+    msg!(
+        "Send owner fees: {}, early exit fees: {}",
+        owner_fees,
+        early_exit_fees
+    );
+
+    // the holder fees leave an isolated reserve for the escrow, where rewards are claimed
+    if reserve_wallet.is_some() {
+        transfer_tokens(
+            from,
+            escrow_wallet,
+            authority,
+            token_program,
+            holder_fees,
+            authority_seeds,
+        )?;
+        msg!("Send holder fees to escrow wallet: {}", holder_fees);
+    }
 
+    // settle rewards before the balance changes
+    if let Some(reward_pool) = reward_pool.as_deref() {
+        passes_balance.settle_rewards(reward_pool)?;
+    }
     passes_balance.amount = passes_balance
         .amount
         .checked_sub(amount)
@@ -166,14 +264,33 @@ pub fn sell_passes(ctx: Context<SellPasses>, amount: u64) -> Result<()> {
         .checked_sub(amount)
         .ok_or(PassesError::MathOverflow)?;
 
+    passes_supply.record_trade(state::TradeSide::Sell, false, amount, price, now)?;
+    emit!(PassesTraded {
+        marketplace_id,
+        owner,
+        trader: seller,
+        side: state::TradeSide::Sell,
+        sol: false,
+        amount,
+        price,
+        protocol_fees,
+        owner_fees,
+        holder_fees,
+        early_exit_fees,
+        supply: passes_supply.amount,
+        stats: passes_supply.stats.clone(),
+    });
+
     msg!(
-        "Sell passes: owner {}, seller {}, amount {}, price {}, protocol_fees {}, owner_fees {}, balance {}, supply {}",
+        "Sell passes: marketplace_id {}, owner {}, seller {}, amount {}, price {}, protocol_fees {}, owner_fees {}, holder_fees {}, balance {}, supply {}",
+        marketplace_id,
         owner,
         seller,
         amount,
         price,
         protocol_fees,
         owner_fees,
+        holder_fees,
         passes_balance.amount,
         passes_supply.amount
     );
@@ -1,8 +1,9 @@
 use anchor_lang::prelude::*;
 
 use crate::{
-    common::{calc_fee, calc_price_sol},
+    common::{calc_fee, calc_price_sol, get_reserve_wallet, get_reward_pool, split_sell_price},
     error::PassesError,
+    events::PassesTraded,
     state,
 };
 
@@ -17,31 +18,54 @@ pub struct SellPassesSol<'info> {
     // derived PDAs
     #[account{
         mut,
-        seeds = [b"supply", passes_owner.key.as_ref(),],
+        seeds = [state::PassesSupply::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref(),],
         bump = passes_supply.bump
     }]
     passes_supply: Box<Account<'info, state::PassesSupply>>,
 
     #[account{
         mut,
-        seeds = [b"balance", passes_owner.key.as_ref(), seller.key.as_ref()],
+        seeds = [state::PassesBalance::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref(), seller.key.as_ref()],
         bump = passes_balance.bump
     }]
     passes_balance: Box<Account<'info, state::PassesBalance>>,
 
+    // required when the owner sells and their issued passes vest
+    #[account{
+        seeds = [state::OwnerVesting::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
+        bump = owner_vesting.bump
+    }]
+    owner_vesting: Option<Box<Account<'info, state::OwnerVesting>>>,
+
+    // required when holders share rewards
+    #[account{
+        mut,
+        seeds = [state::RewardPool::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
+        bump = reward_pool.bump
+    }]
+    reward_pool: Option<Box<Account<'info, state::RewardPool>>>,
+
     #[account(
-        seeds = [state::Config::SEED],
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
         bump = config.bump
     )]
     pub config: Box<Account<'info, state::Config>>,
 
     #[account(
         mut,
-        seeds = [state::EscrowSOL::SEED],
+        seeds = [state::EscrowSOL::SEED, config.marketplace_seed().as_ref()],
         bump = escrow_wallet.bump
     )]
     pub escrow_wallet: Box<Account<'info, state::EscrowSOL>>,
 
+    // required when the market's reserve is isolated
+    #[account(
+        mut,
+        seeds = [state::PassesSupply::RESERVE_SOL_SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
+        bump = reserve_wallet.bump
+    )]
+    pub reserve_wallet: Option<Box<Account<'info, state::EscrowSOL>>>,
+
     // accounts
     /// CHECK: This is not dangerous because we don't read or write from this account
     #[account(mut)]
@@ -50,7 +74,7 @@ pub struct SellPassesSol<'info> {
     /// CHECK: This is not dangerous because we don't read or write from this account
     #[account(
         mut,
-        constraint = protocol_fee_wallet.key() == config.admin
+        constraint = protocol_fee_wallet.key() == config.protocol_fee_sol_wallet
     )]
     pub protocol_fee_wallet: AccountInfo<'info>,
 
@@ -64,40 +88,102 @@ pub fn sell_passes_sol(ctx: Context<SellPassesSol>, amount: u64) -> Result<()> {
     let supply = ctx.accounts.passes_supply.amount;
     let balance = ctx.accounts.passes_balance.amount;
     let owner = ctx.accounts.passes_owner.key();
+    let marketplace_id = ctx.accounts.config.marketplace_id;
     let seller = ctx.accounts.seller.key();
     let config = &ctx.accounts.config;
     let passes_balance = &mut ctx.accounts.passes_balance;
     let passes_supply = &mut ctx.accounts.passes_supply;
 
+    require!(
+        !config.sells_paused && !passes_supply.paused,
+        PassesError::Paused
+    );
     require!(supply > amount, PassesError::LastPass);
+    passes_supply.check_currency(true)?;
     require!(balance >= amount, PassesError::InsufficientPasses);
-
-    let price = calc_price_sol(supply - amount, amount);
+    require!(
+        passes_balance.unstaked() >= amount,
+        PassesError::PassesStaked
+    );
+    if seller == owner && passes_supply.vesting {
+        let owner_vesting = ctx
+            .accounts
+            .owner_vesting
+            .as_ref()
+            .ok_or(PassesError::VestingAccountMissing)?;
+        let locked = owner_vesting.locked(Clock::get()?.unix_timestamp)?;
+        require!(balance - amount >= locked, PassesError::PassesVesting);
+    }
+    if seller == owner {
+        passes_supply.check_issued(balance, amount)?;
+    }
+
+    let price = calc_price_sol(passes_supply.curve_supply(supply - amount)?, amount)?;
     require!(price > 0, PassesError::ZeroPrice);
 
     // calc fees
     let protocol_fees = calc_fee(config.protocol_fee_bps, price)?;
     let owner_fees = calc_fee(config.owner_fee_bps, price)?;
 
-    // send SOL to seller for sold passes
-    let sent_amount = price
-        .checked_sub(protocol_fees)
-        .ok_or(PassesError::MathOverflow)?
-        .checked_sub(owner_fees)
+    // the holders' share of the owner fees stays in escrow as rewards
+    let mut reward_pool = get_reward_pool(passes_supply, &mut ctx.accounts.reward_pool)?;
+    let holder_fees = match reward_pool.as_deref_mut() {
+        Some(reward_pool) => {
+            reward_pool.share_sol_fees(owner_fees, passes_supply.rewarded_supply())?
+        }
+        None => 0,
+    };
+    let owner_fees = owner_fees
+        .checked_sub(holder_fees)
         .ok_or(PassesError::MathOverflow)?;
-    ctx.accounts.escrow_wallet.sub_lamports(sent_amount)?;
+    let now = Clock::get()?.unix_timestamp;
+    let early_exit_fees = passes_supply.early_exit_fee(passes_balance.last_buy_ts, now, price)?;
+    let (sent_amount, owner_fees, early_exit_fees) = split_sell_price(
+        config,
+        passes_supply,
+        price,
+        protocol_fees,
+        owner_fees,
+        holder_fees,
+        early_exit_fees,
+    )?;
+
+    // send SOL to seller for sold passes, from the market's reserve wallet when it's isolated
+    let escrow_wallet = ctx.accounts.escrow_wallet.to_account_info();
+    let reserve_wallet = get_reserve_wallet(passes_supply, &ctx.accounts.reserve_wallet)?;
+    let from = reserve_wallet.clone().unwrap_or(escrow_wallet.clone());
+    from.sub_lamports(sent_amount)?;
     ctx.accounts.seller.add_lamports(sent_amount)?;
 
     // send protocol fees
-    ctx.accounts.escrow_wallet.sub_lamports(protocol_fees)?;
+    from.sub_lamports(protocol_fees)?;
     ctx.accounts
         .protocol_fee_wallet
         .add_lamports(protocol_fees)?;
 
-    // send owner fees
-    ctx.accounts.escrow_wallet.sub_lamports(owner_fees)?;
-    ctx.accounts.passes_owner.add_lamports(owner_fees)?;
-
+    // send owner fees, including the early exit fees
+    let owner_amount = owner_fees
+        .checked_add(early_exit_fees)
+        .ok_or(PassesError::MathOverflow)?;
+    from.sub_lamports(owner_amount)?;
+    ctx.accounts.passes_owner.add_lamports(owner_amount)?;
+    msg!(
+        "Send owner fees: {}, early exit fees: {}",
+        owner_fees,
+        early_exit_fees
+    );
+
+    // the holder fees leave an isolated reserve for the escrow, where rewards are claimed
+    if reserve_wallet.is_some() {
+        from.sub_lamports(holder_fees)?;
+        escrow_wallet.add_lamports(holder_fees)?;
+        msg!("Send holder fees to escrow wallet: {}", holder_fees);
+    }
+
+    // settle rewards before the balance changes
+    if let Some(reward_pool) = reward_pool.as_deref() {
+        passes_balance.settle_rewards(reward_pool)?;
+    }
     passes_balance.amount = passes_balance
         .amount
         .checked_sub(amount)
@@ -107,14 +193,33 @@ pub fn sell_passes_sol(ctx: Context<SellPassesSol>, amount: u64) -> Result<()> {
         .checked_sub(amount)
         .ok_or(PassesError::MathOverflow)?;
 
+    passes_supply.record_trade(state::TradeSide::Sell, true, amount, price, now)?;
+    emit!(PassesTraded {
+        marketplace_id,
+        owner,
+        trader: seller,
+        side: state::TradeSide::Sell,
+        sol: true,
+        amount,
+        price,
+        protocol_fees,
+        owner_fees,
+        holder_fees,
+        early_exit_fees,
+        supply: passes_supply.amount,
+        stats: passes_supply.stats.clone(),
+    });
+
     msg!(
-        "Sell passes: owner {}, seller {}, amount {}, price {}, protocol_fees {}, owner_fees {}, balance {}, supply {}",
+        "Sell passes: marketplace_id {}, owner {}, seller {}, amount {}, price {}, protocol_fees {}, owner_fees {}, holder_fees {}, balance {}, supply {}",
+        marketplace_id,
         owner,
         seller,
         amount,
         price,
         protocol_fees,
         owner_fees,
+        holder_fees,
         passes_balance.amount,
         passes_supply.amount
         );
@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::PassesError, state};
+
+// Set up the admin multisig: the admin keys and the number of approvals required for config changes
+
+#[derive(Accounts)]
+pub struct SetAdmins<'info> {
+    // signer
+    #[account(
+        constraint = admin.key() == config.admin
+    )]
+    pub admin: Signer<'info>,
+
+    // derived PDAs
+    #[account(
+        mut,
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
+        bump = config.bump,
+        constraint = !config.is_multisig() @ PassesError::MultisigRequired,
+        has_one = admin
+    )]
+    pub config: Account<'info, state::Config>,
+}
+
+pub fn set_admins(ctx: Context<SetAdmins>, admins: Vec<Pubkey>, threshold: u8) -> Result<()> {
+    ctx.accounts.config.set_admins(&admins, threshold)?;
+    msg!("Admins: {:?}, threshold {}", admins, threshold);
+
+    Ok(())
+}
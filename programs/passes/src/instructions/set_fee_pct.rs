@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 
-use crate::state;
+use crate::{error::PassesError, state};
 
 // Set protocol and owner fee percent
 
@@ -16,11 +16,9 @@ pub struct SetFeePercent<'info> {
     // derived PDAs
     #[account(
         mut,
-        seeds = [state::Config::SEED],
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
         bump = config.bump,
-        // realloc = state::Config::LEN,
-        // realloc::payer = admin,
-        // realloc::zero = true,
+        constraint = !config.is_multisig() @ PassesError::MultisigRequired,
         has_one = admin
     )]
     pub config: Account<'info, state::Config>,
@@ -30,13 +28,13 @@ pub struct SetFeePercent<'info> {
 }
 
 pub fn set_protocol_fee_bps(ctx: Context<SetFeePercent>, fee_bps: u64) -> Result<()> {
-    ctx.accounts.config.protocol_fee_bps = fee_bps;
-
-    Ok(())
+    let config = &mut ctx.accounts.config;
+    let owner_fee_bps = config.owner_fee_bps;
+    config.set_fees(fee_bps, owner_fee_bps)
 }
 
 pub fn set_owner_fee_bps(ctx: Context<SetFeePercent>, fee_bps: u64) -> Result<()> {
-    ctx.accounts.config.owner_fee_bps = fee_bps;
-
-    Ok(())
+    let config = &mut ctx.accounts.config;
+    let protocol_fee_bps = config.protocol_fee_bps;
+    config.set_fees(protocol_fee_bps, fee_bps)
 }
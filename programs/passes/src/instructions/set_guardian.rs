@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::PassesError, state};
+
+// Set the guardian key that can pause and unpause trading
+
+#[derive(Accounts)]
+pub struct SetGuardian<'info> {
+    // signer
+    #[account(
+        constraint = admin.key() == config.admin
+    )]
+    pub admin: Signer<'info>,
+
+    // derived PDAs
+    #[account(
+        mut,
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
+        bump = config.bump,
+        constraint = !config.is_multisig() @ PassesError::MultisigRequired,
+        has_one = admin
+    )]
+    pub config: Account<'info, state::Config>,
+}
+
+pub fn set_guardian(ctx: Context<SetGuardian>, guardian: Pubkey) -> Result<()> {
+    ctx.accounts.config.guardian = guardian;
+    msg!("Guardian: {}", guardian);
+
+    Ok(())
+}
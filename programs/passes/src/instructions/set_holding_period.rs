@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::PassesError, math, state};
+
+// Set how long holders must wait after a buy before selling, and the owner's fee on earlier sells
+
+#[derive(Accounts)]
+pub struct SetHoldingPeriod<'info> {
+    // signer
+    pub owner: Signer<'info>,
+
+    // derived PDAs
+    #[account(
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, state::Config>>,
+
+    #[account{
+        mut,
+        seeds = [state::PassesSupply::SEED, config.marketplace_seed().as_ref(), owner.key.as_ref()],
+        bump = passes_supply.bump
+    }]
+    passes_supply: Box<Account<'info, state::PassesSupply>>,
+}
+
+pub fn set_holding_period(
+    ctx: Context<SetHoldingPeriod>,
+    min_hold_secs: i64,
+    early_exit_fee_bps: u64,
+) -> Result<()> {
+    require!(min_hold_secs >= 0, PassesError::InvalidHoldingPeriod);
+    require!(
+        early_exit_fee_bps <= state::PassesSupply::MAX_EARLY_EXIT_FEE_BPS,
+        PassesError::InvalidFee
+    );
+    // early sells pay the marketplace fees too, together they can't take more than the price
+    let config = &ctx.accounts.config;
+    let total_fee_bps = [config.protocol_fee_bps, config.owner_fee_bps]
+        .iter()
+        .try_fold(early_exit_fee_bps, |total, fee_bps| {
+            total.checked_add(*fee_bps)
+        })
+        .ok_or(PassesError::InvalidFee)?;
+    require!(
+        total_fee_bps as u128 <= math::BPS_POWER,
+        PassesError::InvalidFee
+    );
+
+    let passes_supply = &mut ctx.accounts.passes_supply;
+    passes_supply.min_hold_secs = min_hold_secs;
+    passes_supply.early_exit_fee_bps = early_exit_fee_bps;
+    msg!(
+        "Set holding period: owner {}, min_hold_secs {}, early_exit_fee_bps {}",
+        ctx.accounts.owner.key(),
+        min_hold_secs,
+        early_exit_fee_bps
+    );
+
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use crate::state;
+
+// Pause or unpause trading in the owner's market
+
+#[derive(Accounts)]
+pub struct SetPassesPaused<'info> {
+    // signer
+    pub owner: Signer<'info>,
+
+    // derived PDAs
+    #[account(
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, state::Config>>,
+
+    #[account{
+        mut,
+        seeds = [state::PassesSupply::SEED, config.marketplace_seed().as_ref(), owner.key.as_ref()],
+        bump = passes_supply.bump
+    }]
+    passes_supply: Box<Account<'info, state::PassesSupply>>,
+}
+
+pub fn set_passes_paused(ctx: Context<SetPassesPaused>, paused: bool) -> Result<()> {
+    ctx.accounts.passes_supply.paused = paused;
+    msg!(
+        "Passes paused: owner {}, paused {}",
+        ctx.accounts.owner.key(),
+        paused
+    );
+
+    Ok(())
+}
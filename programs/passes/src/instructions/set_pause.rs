@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+
+use crate::state;
+
+// Pause or unpause buys, sells and issuance in all markets (admin or guardian). Under a
+// multisig the admins pause through `ConfigChange::Pause` proposals, the guardian still alone.
+
+#[derive(Accounts)]
+pub struct SetPause<'info> {
+    // signer
+    #[account(
+        constraint = (authority.key() == config.admin && !config.is_multisig())
+            || authority.key() == config.guardian
+    )]
+    pub authority: Signer<'info>,
+
+    // derived PDAs
+    #[account(
+        mut,
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, state::Config>,
+}
+
+pub fn set_buys_paused(ctx: Context<SetPause>, paused: bool) -> Result<()> {
+    ctx.accounts.config.buys_paused = paused;
+    msg!(
+        "Buys paused: {}, authority {}",
+        paused,
+        ctx.accounts.authority.key()
+    );
+
+    Ok(())
+}
+
+pub fn set_sells_paused(ctx: Context<SetPause>, paused: bool) -> Result<()> {
+    ctx.accounts.config.sells_paused = paused;
+    msg!(
+        "Sells paused: {}, authority {}",
+        paused,
+        ctx.accounts.authority.key()
+    );
+
+    Ok(())
+}
+
+pub fn set_issuance_paused(ctx: Context<SetPause>, paused: bool) -> Result<()> {
+    ctx.accounts.config.issuance_paused = paused;
+    msg!(
+        "Issuance paused: {}, authority {}",
+        paused,
+        ctx.accounts.authority.key()
+    );
+
+    Ok(())
+}
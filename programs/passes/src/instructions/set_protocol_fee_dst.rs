@@ -1,9 +1,9 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::TokenAccount;
 
-use crate::state;
+use crate::{error::PassesError, state};
 
-// Set the destination address for receiving protocol fees
+// Set the destination addresses for receiving protocol fees
 
 #[derive(Accounts)]
 pub struct SetProtocolFeeDst<'info> {
@@ -14,20 +14,17 @@ pub struct SetProtocolFeeDst<'info> {
     // derived PDAs
     #[account(
         mut,
-        seeds = [state::Config::SEED],
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
         bump = config.bump,
-        // realloc = state::Config::LEN,
-        // realloc::payer = admin,
-        // realloc::zero = true,
+        constraint = !config.is_multisig() @ PassesError::MultisigRequired,
         constraint = admin.key() == config.admin
     )]
     pub config: Account<'info, state::Config>,
 
     #[account(
-        associated_token::mint = config.payment_mint,
-        associated_token::authority = admin
+        token::mint = config.payment_mint
     )]
-    protocol_fee_wallet: Account<'info, TokenAccount>, // token account to send fee
+    protocol_fee_wallet: Account<'info, TokenAccount>, // token account to send fee, not necessarily owned by the admin
 
     // programs
     pub system_program: Program<'info, System>,
@@ -42,3 +39,36 @@ pub fn set_protocol_fee_dst(ctx: Context<SetProtocolFeeDst>) -> Result<()> {
 
     Ok(())
 }
+
+#[derive(Accounts)]
+pub struct SetProtocolFeeSolDst<'info> {
+    // signer
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    // derived PDAs
+    #[account(
+        mut,
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
+        bump = config.bump,
+        constraint = !config.is_multisig() @ PassesError::MultisigRequired,
+        constraint = admin.key() == config.admin
+    )]
+    pub config: Account<'info, state::Config>,
+
+    /// CHECK: This is not dangerous because we only store the address to send SOL fees to
+    protocol_fee_wallet: AccountInfo<'info>, // account to send fee in SOL, e.g. a multisig vault
+
+    // programs
+    pub system_program: Program<'info, System>,
+}
+
+pub fn set_protocol_fee_sol_dst(ctx: Context<SetProtocolFeeSolDst>) -> Result<()> {
+    ctx.accounts.config.protocol_fee_sol_wallet = ctx.accounts.protocol_fee_wallet.key();
+    msg!(
+        "Protocol fee sol: {}",
+        ctx.accounts.config.protocol_fee_sol_wallet
+    );
+
+    Ok(())
+}
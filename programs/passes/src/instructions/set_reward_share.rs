@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::PassesError, math, state};
+
+// Share part of the owner fees with the holders of the owner's passes.
+// Once the reward pool exists the market keeps it, a zero share still lets anyone deposit rewards.
+
+#[derive(Accounts)]
+pub struct SetRewardShare<'info> {
+    // signer
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    // derived PDAs
+    #[account(
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, state::Config>>,
+
+    #[account{
+        mut,
+        seeds = [state::PassesSupply::SEED, config.marketplace_seed().as_ref(), owner.key.as_ref()],
+        bump = passes_supply.bump
+    }]
+    passes_supply: Box<Account<'info, state::PassesSupply>>,
+
+    #[account{
+        init_if_needed,
+        payer = owner,
+        space = state::RewardPool::LEN,
+        seeds = [state::RewardPool::SEED, config.marketplace_seed().as_ref(), owner.key.as_ref()],
+        bump,
+    }]
+    reward_pool: Box<Account<'info, state::RewardPool>>,
+
+    // programs
+    pub system_program: Program<'info, System>,
+}
+
+pub fn set_reward_share(ctx: Context<SetRewardShare>, holder_fee_bps: u64) -> Result<()> {
+    require!(
+        holder_fee_bps as u128 <= math::BPS_POWER,
+        PassesError::InvalidFee
+    );
+
+    let reward_pool = &mut ctx.accounts.reward_pool;
+    reward_pool.version = state::RewardPool::VERSION;
+    reward_pool.owner = ctx.accounts.owner.key();
+    reward_pool.holder_fee_bps = holder_fee_bps;
+    reward_pool.bump = ctx.bumps.reward_pool;
+    ctx.accounts.passes_supply.rewards = true;
+
+    msg!(
+        "Set reward share: owner {}, holder_fee_bps {}",
+        reward_pool.owner,
+        holder_fee_bps
+    );
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::PassesError, math, state};
+
+// Cap the balance a holder can reach in the owner's market, as an amount and/or bps of the supply
+
+#[derive(Accounts)]
+pub struct SetWalletCap<'info> {
+    // signer
+    pub owner: Signer<'info>,
+
+    // derived PDAs
+    #[account(
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, state::Config>>,
+
+    #[account{
+        mut,
+        seeds = [state::PassesSupply::SEED, config.marketplace_seed().as_ref(), owner.key.as_ref()],
+        bump = passes_supply.bump
+    }]
+    passes_supply: Box<Account<'info, state::PassesSupply>>,
+}
+
+pub fn set_wallet_cap(
+    ctx: Context<SetWalletCap>,
+    max_wallet_amount: u64,
+    max_wallet_bps: u64,
+) -> Result<()> {
+    require!(
+        max_wallet_bps as u128 <= math::BPS_POWER,
+        PassesError::InvalidWalletCap
+    );
+
+    let passes_supply = &mut ctx.accounts.passes_supply;
+    passes_supply.max_wallet_amount = max_wallet_amount;
+    passes_supply.max_wallet_bps = max_wallet_bps;
+    msg!(
+        "Set wallet cap: owner {}, max_wallet_amount {}, max_wallet_bps {}",
+        ctx.accounts.owner.key(),
+        max_wallet_amount,
+        max_wallet_bps
+    );
+
+    Ok(())
+}
@@ -0,0 +1,178 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::PassesError, state};
+
+// Lock passes from a holder's balance into their stake to earn points and the owner's stake rewards
+
+#[derive(Accounts)]
+pub struct StakePasses<'info> {
+    // signer
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    // derived PDAs
+    #[account(
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, state::Config>>,
+
+    #[account{
+        mut,
+        seeds = [state::PassesBalance::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref(), holder.key.as_ref()],
+        bump = passes_balance.bump
+    }]
+    passes_balance: Box<Account<'info, state::PassesBalance>>,
+
+    #[account{
+        init_if_needed,
+        payer = holder,
+        space = state::StakePool::LEN,
+        seeds = [state::StakePool::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
+        bump,
+    }]
+    stake_pool: Box<Account<'info, state::StakePool>>,
+
+    #[account{
+        init_if_needed,
+        payer = holder,
+        space = state::PassesStake::LEN,
+        seeds = [state::PassesStake::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref(), holder.key.as_ref()],
+        bump,
+    }]
+    passes_stake: Box<Account<'info, state::PassesStake>>,
+
+    // accounts
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub passes_owner: AccountInfo<'info>,
+
+    // programs
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UnstakePasses<'info> {
+    // signer
+    pub holder: Signer<'info>,
+
+    // derived PDAs
+    #[account(
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, state::Config>>,
+
+    #[account{
+        mut,
+        seeds = [state::PassesBalance::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref(), holder.key.as_ref()],
+        bump = passes_balance.bump
+    }]
+    passes_balance: Box<Account<'info, state::PassesBalance>>,
+
+    #[account{
+        mut,
+        seeds = [state::StakePool::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref()],
+        bump = stake_pool.bump
+    }]
+    stake_pool: Box<Account<'info, state::StakePool>>,
+
+    #[account{
+        mut,
+        seeds = [state::PassesStake::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref(), holder.key.as_ref()],
+        bump = passes_stake.bump
+    }]
+    passes_stake: Box<Account<'info, state::PassesStake>>,
+
+    // accounts
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub passes_owner: AccountInfo<'info>,
+}
+
+// Stake passes
+
+pub fn stake_passes(ctx: Context<StakePasses>, amount: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let passes_balance = &mut ctx.accounts.passes_balance;
+    let stake_pool = &mut ctx.accounts.stake_pool;
+    let passes_stake = &mut ctx.accounts.passes_stake;
+
+    require!(amount > 0, PassesError::ZeroAmount);
+    require!(
+        passes_balance.unstaked() >= amount,
+        PassesError::InsufficientPasses
+    );
+
+    stake_pool.version = state::StakePool::VERSION;
+    stake_pool.owner = ctx.accounts.passes_owner.key();
+    stake_pool.bump = ctx.bumps.stake_pool;
+    stake_pool.update(now)?;
+
+    passes_stake.version = state::PassesStake::VERSION;
+    passes_stake.owner = ctx.accounts.passes_owner.key();
+    passes_stake.holder = ctx.accounts.holder.key();
+    passes_stake.bump = ctx.bumps.passes_stake;
+    passes_stake.settle(stake_pool, now)?;
+
+    passes_stake.amount = passes_stake
+        .amount
+        .checked_add(amount)
+        .ok_or(PassesError::MathOverflow)?;
+    passes_balance.staked_amount = passes_balance
+        .staked_amount
+        .checked_add(amount)
+        .ok_or(PassesError::MathOverflow)?;
+    stake_pool.total_staked = stake_pool
+        .total_staked
+        .checked_add(amount)
+        .ok_or(PassesError::MathOverflow)?;
+
+    msg!(
+        "Stake passes: owner {}, holder {}, amount {}, staked {}, total staked {}",
+        stake_pool.owner,
+        passes_stake.holder,
+        amount,
+        passes_stake.amount,
+        stake_pool.total_staked
+    );
+
+    Ok(())
+}
+
+// Unstake passes, the earned points and rewards stay with the stake
+
+pub fn unstake_passes(ctx: Context<UnstakePasses>, amount: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let passes_balance = &mut ctx.accounts.passes_balance;
+    let stake_pool = &mut ctx.accounts.stake_pool;
+    let passes_stake = &mut ctx.accounts.passes_stake;
+
+    require!(amount > 0, PassesError::ZeroAmount);
+    require!(
+        passes_stake.amount >= amount,
+        PassesError::InsufficientPasses
+    );
+
+    stake_pool.update(now)?;
+    passes_stake.settle(stake_pool, now)?;
+
+    passes_stake.amount -= amount;
+    passes_balance.staked_amount = passes_balance
+        .staked_amount
+        .checked_sub(amount)
+        .ok_or(PassesError::MathOverflow)?;
+    stake_pool.total_staked = stake_pool
+        .total_staked
+        .checked_sub(amount)
+        .ok_or(PassesError::MathOverflow)?;
+
+    msg!(
+        "Unstake passes: owner {}, holder {}, amount {}, staked {}, total staked {}",
+        stake_pool.owner,
+        passes_stake.holder,
+        amount,
+        passes_stake.amount,
+        stake_pool.total_staked
+    );
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::PassesError, events::PassesMetadataUpdated, state};
+
+// Set the market name, description, image and social handle of the owner's passes
+
+#[derive(Accounts)]
+pub struct UpdatePassesMetadata<'info> {
+    // signer
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    // derived PDAs
+    #[account(
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, state::Config>>,
+
+    #[account{
+        seeds = [state::PassesSupply::SEED, config.marketplace_seed().as_ref(), owner.key.as_ref()],
+        bump = passes_supply.bump
+    }]
+    passes_supply: Box<Account<'info, state::PassesSupply>>,
+
+    // markets issued before metadata existed get it on the first update
+    #[account{
+        init_if_needed,
+        payer = owner,
+        space = state::PassesMetadata::LEN,
+        seeds = [state::PassesMetadata::SEED, config.marketplace_seed().as_ref(), owner.key.as_ref()],
+        bump,
+    }]
+    passes_metadata: Box<Account<'info, state::PassesMetadata>>,
+
+    // programs
+    pub system_program: Program<'info, System>,
+}
+
+pub fn update_passes_metadata(
+    ctx: Context<UpdatePassesMetadata>,
+    name: String,
+    description_uri: String,
+    image_uri: String,
+    social_handle: String,
+) -> Result<()> {
+    require!(
+        name.len() <= state::PassesMetadata::MAX_NAME_LEN,
+        PassesError::MetadataTooLong
+    );
+    require!(
+        description_uri.len() <= state::PassesMetadata::MAX_URI_LEN,
+        PassesError::MetadataTooLong
+    );
+    require!(
+        image_uri.len() <= state::PassesMetadata::MAX_URI_LEN,
+        PassesError::MetadataTooLong
+    );
+    require!(
+        social_handle.len() <= state::PassesMetadata::MAX_SOCIAL_HANDLE_LEN,
+        PassesError::MetadataTooLong
+    );
+
+    let owner = ctx.accounts.owner.key();
+    let marketplace_id = ctx.accounts.config.marketplace_id;
+    let passes_metadata = &mut ctx.accounts.passes_metadata;
+    passes_metadata.version = state::PassesMetadata::VERSION;
+    passes_metadata.owner = owner;
+    passes_metadata.name = name;
+    passes_metadata.description_uri = description_uri;
+    passes_metadata.image_uri = image_uri;
+    passes_metadata.social_handle = social_handle;
+    passes_metadata.bump = ctx.bumps.passes_metadata;
+
+    emit!(PassesMetadataUpdated {
+        marketplace_id,
+        owner,
+        name: passes_metadata.name.clone(),
+        description_uri: passes_metadata.description_uri.clone(),
+        image_uri: passes_metadata.image_uri.clone(),
+        social_handle: passes_metadata.social_handle.clone(),
+    });
+
+    msg!(
+        "Update passes metadata: owner {}, name {}",
+        owner,
+        passes_metadata.name
+    );
+
+    Ok(())
+}
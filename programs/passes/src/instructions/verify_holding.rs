@@ -0,0 +1,78 @@
+use anchor_lang::prelude::*;
+
+use crate::{error::PassesError, state};
+
+// Tell whether a wallet holds at least `min_amount` passes of an owner, for views and CPI.
+// Staked passes count as held, and so do the holder's passes in the given holder page and in a
+// balance account that isn't migrated yet.
+
+#[derive(Accounts)]
+pub struct VerifyHolding<'info> {
+    // derived PDAs
+    #[account(
+        seeds = [state::Config::SEED, config.marketplace_seed().as_ref()],
+        bump = config.bump
+    )]
+    pub config: Box<Account<'info, state::Config>>,
+
+    /// CHECK: Address checked by the seeds, the account may not exist
+    #[account(
+        seeds = [state::PassesBalance::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref(), holder.key.as_ref()],
+        bump
+    )]
+    pub passes_balance: UncheckedAccount<'info>,
+
+    // a holder page with the holder's entry, when some of their passes are paged
+    #[account(
+        seeds = [state::HolderPage::SEED, config.marketplace_seed().as_ref(), passes_owner.key.as_ref(), holder_page.load()?.page.to_le_bytes().as_ref()],
+        bump = holder_page.load()?.bump
+    )]
+    pub holder_page: Option<AccountLoader<'info, state::HolderPage>>,
+
+    // accounts
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub holder: AccountInfo<'info>,
+
+    /// CHECK: This is not dangerous because we don't read or write from this account
+    pub passes_owner: AccountInfo<'info>,
+}
+
+pub fn verify_holding(ctx: Context<VerifyHolding>, min_amount: u64) -> Result<bool> {
+    let passes_balance = &ctx.accounts.passes_balance;
+    let holder = ctx.accounts.holder.key();
+    // a holder that never held passes has no balance account
+    let balance = if passes_balance.owner == ctx.program_id && !passes_balance.data_is_empty() {
+        let data = passes_balance.try_borrow_data()?;
+        if data.len() == state::PassesBalance::LEN {
+            state::PassesBalance::try_deserialize(&mut &data[..])?.amount
+        } else {
+            state::PassesBalance::try_deserialize_unmigrated(&data)?.amount
+        }
+    } else {
+        0
+    };
+    let paged = match &ctx.accounts.holder_page {
+        Some(holder_page) => {
+            let holder_page = holder_page.load()?;
+            holder_page
+                .find(&holder)
+                .map_or(0, |index| holder_page.amounts[index])
+        }
+        None => 0,
+    };
+    let amount = balance
+        .checked_add(paged)
+        .ok_or(PassesError::MathOverflow)?;
+    let holds = amount >= min_amount;
+
+    msg!(
+        "Verify holding: owner {}, holder {}, min_amount {}, amount {}, holds {}",
+        ctx.accounts.passes_owner.key(),
+        holder,
+        min_amount,
+        amount,
+        holds
+    );
+
+    Ok(holds)
+}
@@ -3,12 +3,19 @@ use anchor_lang::prelude::*;
 use instructions::*;
 
 pub mod error;
+pub mod events;
+pub mod gate;
 pub mod instructions;
+pub mod merkle;
 pub mod state;
 
 mod common;
 mod math;
 
+pub use common::{
+    calc_fee, calc_price, calc_price_sol, curve_price, curve_price_sol, split_sell_price,
+};
+
 #[cfg(test)]
 mod tests;
 
@@ -29,8 +36,29 @@ declare_id!("8j5vzygvZzkmFAQ186yPbr4vgVGFtSvmFyzE7KVXmB8Q");
 pub mod passes {
     use super::*;
 
-    pub fn init(ctx: Context<Init>, protocol_fee_bps: u64, owner_fee_bps: u64) -> Result<()> {
-        instructions::init(ctx, protocol_fee_bps, owner_fee_bps)
+    pub fn init(
+        ctx: Context<Init>,
+        marketplace_id: u64,
+        protocol_fee_bps: u64,
+        owner_fee_bps: u64,
+    ) -> Result<()> {
+        instructions::init(ctx, marketplace_id, protocol_fee_bps, owner_fee_bps)
+    }
+
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        instructions::migrate_config(ctx)
+    }
+
+    pub fn migrate_supply(ctx: Context<MigrateSupply>) -> Result<()> {
+        instructions::migrate_supply(ctx)
+    }
+
+    pub fn migrate_balance(ctx: Context<MigrateBalance>) -> Result<()> {
+        instructions::migrate_balance(ctx)
+    }
+
+    pub fn isolate_reserve(ctx: Context<IsolateReserve>) -> Result<()> {
+        instructions::isolate_reserve(ctx)
     }
 
     pub fn set_protocol_fee_bps(ctx: Context<SetFeePercent>, fee_bps: u64) -> Result<()> {
@@ -45,8 +73,144 @@ pub mod passes {
         instructions::set_protocol_fee_dst(ctx)
     }
 
-    pub fn issue_passes(ctx: Context<IssuePasses>, amount: u64) -> Result<()> {
-        instructions::issue_passes(ctx, amount)
+    pub fn set_protocol_fee_sol_dst(ctx: Context<SetProtocolFeeSolDst>) -> Result<()> {
+        instructions::set_protocol_fee_sol_dst(ctx)
+    }
+
+    pub fn set_guardian(ctx: Context<SetGuardian>, guardian: Pubkey) -> Result<()> {
+        instructions::set_guardian(ctx, guardian)
+    }
+
+    pub fn set_admins(ctx: Context<SetAdmins>, admins: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        instructions::set_admins(ctx, admins, threshold)
+    }
+
+    pub fn propose_config_change(
+        ctx: Context<ProposeConfigChange>,
+        change: state::ConfigChange,
+    ) -> Result<()> {
+        instructions::propose_config_change(ctx, change)
+    }
+
+    pub fn approve_proposal(ctx: Context<ApproveProposal>) -> Result<()> {
+        instructions::approve_proposal(ctx)
+    }
+
+    pub fn execute_proposal(ctx: Context<ExecuteProposal>) -> Result<()> {
+        instructions::execute_proposal(ctx)
+    }
+
+    pub fn set_buys_paused(ctx: Context<SetPause>, paused: bool) -> Result<()> {
+        instructions::set_buys_paused(ctx, paused)
+    }
+
+    pub fn set_sells_paused(ctx: Context<SetPause>, paused: bool) -> Result<()> {
+        instructions::set_sells_paused(ctx, paused)
+    }
+
+    pub fn set_issuance_paused(ctx: Context<SetPause>, paused: bool) -> Result<()> {
+        instructions::set_issuance_paused(ctx, paused)
+    }
+
+    pub fn set_passes_paused(ctx: Context<SetPassesPaused>, paused: bool) -> Result<()> {
+        instructions::set_passes_paused(ctx, paused)
+    }
+
+    pub fn set_wallet_cap(
+        ctx: Context<SetWalletCap>,
+        max_wallet_amount: u64,
+        max_wallet_bps: u64,
+    ) -> Result<()> {
+        instructions::set_wallet_cap(ctx, max_wallet_amount, max_wallet_bps)
+    }
+
+    pub fn set_holding_period(
+        ctx: Context<SetHoldingPeriod>,
+        min_hold_secs: i64,
+        early_exit_fee_bps: u64,
+    ) -> Result<()> {
+        instructions::set_holding_period(ctx, min_hold_secs, early_exit_fee_bps)
+    }
+
+    pub fn set_reward_share(ctx: Context<SetRewardShare>, holder_fee_bps: u64) -> Result<()> {
+        instructions::set_reward_share(ctx, holder_fee_bps)
+    }
+
+    pub fn deposit_rewards(ctx: Context<DepositRewards>, amount: u64) -> Result<()> {
+        instructions::deposit_rewards(ctx, amount)
+    }
+
+    pub fn deposit_rewards_sol(ctx: Context<DepositRewardsSol>, amount: u64) -> Result<()> {
+        instructions::deposit_rewards_sol(ctx, amount)
+    }
+
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        instructions::claim_rewards(ctx)
+    }
+
+    pub fn claim_rewards_sol(ctx: Context<ClaimRewardsSol>) -> Result<()> {
+        instructions::claim_rewards_sol(ctx)
+    }
+
+    pub fn fund_stake_rewards(
+        ctx: Context<FundStakeRewards>,
+        amount: u64,
+        duration: i64,
+    ) -> Result<()> {
+        instructions::fund_stake_rewards(ctx, amount, duration)
+    }
+
+    pub fn stake_passes(ctx: Context<StakePasses>, amount: u64) -> Result<()> {
+        instructions::stake_passes(ctx, amount)
+    }
+
+    pub fn unstake_passes(ctx: Context<UnstakePasses>, amount: u64) -> Result<()> {
+        instructions::unstake_passes(ctx, amount)
+    }
+
+    pub fn claim_stake_rewards(ctx: Context<ClaimStakeRewards>) -> Result<()> {
+        instructions::claim_stake_rewards(ctx)
+    }
+
+    pub fn issue_passes(
+        ctx: Context<IssuePasses>,
+        amount: u64,
+        params: state::IssuanceParams,
+    ) -> Result<()> {
+        instructions::issue_passes(ctx, amount, params)
+    }
+
+    pub fn create_holder_page(ctx: Context<CreateHolderPage>, page: u32) -> Result<()> {
+        instructions::create_holder_page(ctx, page)
+    }
+
+    pub fn convert_to_holder_page(ctx: Context<ConvertToHolderPage>) -> Result<()> {
+        instructions::convert_to_holder_page(ctx)
+    }
+
+    pub fn convert_from_holder_page(ctx: Context<ConvertFromHolderPage>) -> Result<()> {
+        instructions::convert_from_holder_page(ctx)
+    }
+
+    pub fn update_passes_metadata(
+        ctx: Context<UpdatePassesMetadata>,
+        name: String,
+        description_uri: String,
+        image_uri: String,
+        social_handle: String,
+    ) -> Result<()> {
+        instructions::update_passes_metadata(ctx, name, description_uri, image_uri, social_handle)
+    }
+
+    pub fn airdrop_passes<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AirdropPasses<'info>>,
+        amounts: Vec<u64>,
+    ) -> Result<()> {
+        instructions::airdrop_passes(ctx, amounts)
+    }
+
+    pub fn verify_holding(ctx: Context<VerifyHolding>, min_amount: u64) -> Result<bool> {
+        instructions::verify_holding(ctx, min_amount)
     }
 
     pub fn get_price(ctx: Context<GetPrice>, supply: u64, amount: u64) -> Result<u64> {
@@ -57,12 +221,20 @@ pub mod passes {
         instructions::get_price_sol(ctx, supply, amount)
     }
 
-    pub fn buy_passes(ctx: Context<BuyPasses>, amount: u64) -> Result<()> {
-        instructions::buy_passes(ctx, amount)
+    pub fn get_trade_stats(ctx: Context<GetTradeStats>) -> Result<state::TradeStats> {
+        instructions::get_trade_stats(ctx)
+    }
+
+    pub fn buy_passes(ctx: Context<BuyPasses>, amount: u64, proof: Vec<[u8; 32]>) -> Result<()> {
+        instructions::buy_passes(ctx, amount, proof)
     }
 
-    pub fn buy_passes_sol(ctx: Context<BuyPassesSol>, amount: u64) -> Result<()> {
-        instructions::buy_passes_sol(ctx, amount)
+    pub fn buy_passes_sol(
+        ctx: Context<BuyPassesSol>,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::buy_passes_sol(ctx, amount, proof)
     }
 
     pub fn sell_passes(ctx: Context<SellPasses>, amount: u64) -> Result<()> {
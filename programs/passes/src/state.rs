@@ -1,13 +1,35 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, Discriminator};
+
+use crate::{common::calc_fee, error::PassesError, math, merkle};
 
-// TODO replace size_of - manually calculate the account size
 const DISCRIMINATOR_LENGTH: usize = 8;
 
+/// The maximum number of keys in the admin multisig
+pub const MAX_ADMINS: usize = 5;
+
+/// The number of holder entries in a holder page
+pub const HOLDERS_PER_PAGE: usize = 128;
+
+/// Seed namespacing the PDAs of a marketplace, it follows the seed prefix of every PDA.
+/// The default marketplace has an empty seed, so its accounts keep the addresses they had
+/// before marketplaces were introduced.
+pub fn marketplace_seed(marketplace_id: u64) -> Vec<u8> {
+    if marketplace_id == 0 {
+        vec![]
+    } else {
+        marketplace_id.to_le_bytes().to_vec()
+    }
+}
+
 #[account]
-#[derive(Default, Debug)]
+#[derive(Debug)]
 pub struct Config {
+    /// Account layout version
+    pub version: u8,
     /// Contract admin
     pub admin: Pubkey,
+    /// Key allowed to pause and unpause trading besides the admin
+    pub guardian: Pubkey,
     /// The mint account for payments
     pub payment_mint: Pubkey,
     /// The escrow wallet (associated token account) to store buyer payments
@@ -20,41 +42,1027 @@ pub struct Config {
     pub owner_fee_bps: u64,
     /// The destination address (associated token account) for receiving protocol fees
     pub protocol_fee_token_wallet: Pubkey,
+    /// The destination address for receiving protocol fees in SOL
+    pub protocol_fee_sol_wallet: Pubkey,
+    /// Emergency switch to stop buying passes in all markets
+    pub buys_paused: bool,
+    /// Emergency switch to stop selling passes in all markets
+    pub sells_paused: bool,
+    /// Emergency switch to stop issuing new passes
+    pub issuance_paused: bool,
+    /// Keys that propose and approve config changes once the multisig is set up
+    pub admins: [Pubkey; MAX_ADMINS],
+    /// Number of used entries in `admins`
+    pub admins_len: u8,
+    /// Number of approvals required to execute a proposal, direct admin changes are disabled above 1
+    pub admin_threshold: u8,
+    /// Number of created proposals, used as the next proposal id
+    pub proposal_count: u64,
 
     pub bump: u8,
+    /// Marketplace the config belongs to, 0 is the default marketplace
+    pub marketplace_id: u64,
+    /// Reserved for future fields, new fields take their space from here
+    pub reserved: [u8; 120],
 }
 
 impl Config {
-    pub const LEN: usize = DISCRIMINATOR_LENGTH + std::mem::size_of::<Config>();
+    pub const LEN: usize = DISCRIMINATOR_LENGTH
+        + 1 // version
+        + 32 // admin
+        + 32 // guardian
+        + 32 // payment_mint
+        + 32 // escrow_token_wallet
+        + 32 // escrow_sol_wallet
+        + 8 // protocol_fee_bps
+        + 8 // owner_fee_bps
+        + 32 // protocol_fee_token_wallet
+        + 32 // protocol_fee_sol_wallet
+        + 1 // buys_paused
+        + 1 // sells_paused
+        + 1 // issuance_paused
+        + 32 * MAX_ADMINS // admins
+        + 1 // admins_len
+        + 1 // admin_threshold
+        + 8 // proposal_count
+        + 1 // bump
+        + 8 // marketplace_id
+        + 120; // reserved
     pub const SEED: &[u8] = b"config";
+    // Seed of the escrow token wallet, the payment mint follows the marketplace seed
+    pub const ESCROW_TOKEN_SEED: &[u8] = b"escrow";
+    pub const VERSION: u8 = 1;
+
+    /// Seed namespacing the PDAs of the config's marketplace, see [`marketplace_seed`]
+    pub fn marketplace_seed(&self) -> Vec<u8> {
+        marketplace_seed(self.marketplace_id)
+    }
+
+    pub fn admins(&self) -> &[Pubkey] {
+        &self.admins[..self.admins_len as usize]
+    }
+
+    pub fn is_admin(&self, key: &Pubkey) -> bool {
+        self.admins().contains(key)
+    }
+
+    pub fn is_multisig(&self) -> bool {
+        self.admin_threshold > 1
+    }
+
+    pub fn set_admins(&mut self, admins: &[Pubkey], threshold: u8) -> Result<()> {
+        require!(
+            !admins.is_empty() && admins.len() <= MAX_ADMINS,
+            PassesError::InvalidAdmins
+        );
+        require!(
+            threshold > 0 && threshold as usize <= admins.len(),
+            PassesError::InvalidAdmins
+        );
+        for (i, admin) in admins.iter().enumerate() {
+            require!(!admins[..i].contains(admin), PassesError::InvalidAdmins);
+        }
+
+        self.admins = [Pubkey::default(); MAX_ADMINS];
+        self.admins[..admins.len()].copy_from_slice(admins);
+        self.admins_len = admins.len() as u8;
+        self.admin_threshold = threshold;
+
+        Ok(())
+    }
+
+    /// Set the trade fees, together they can't take more than the whole price
+    pub fn set_fees(&mut self, protocol_fee_bps: u64, owner_fee_bps: u64) -> Result<()> {
+        let total_fee_bps = protocol_fee_bps
+            .checked_add(owner_fee_bps)
+            .ok_or(PassesError::InvalidFee)?;
+        require!(
+            total_fee_bps as u128 <= math::BPS_POWER,
+            PassesError::InvalidFee
+        );
+
+        self.protocol_fee_bps = protocol_fee_bps;
+        self.owner_fee_bps = owner_fee_bps;
+
+        Ok(())
+    }
+}
+
+/// Config change that takes effect once enough admins approve it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub enum ConfigChange {
+    ProtocolFeeBps {
+        fee_bps: u64,
+    },
+    OwnerFeeBps {
+        fee_bps: u64,
+    },
+    ProtocolFeeTokenWallet {
+        wallet: Pubkey,
+    },
+    ProtocolFeeSolWallet {
+        wallet: Pubkey,
+    },
+    Pause {
+        buys: bool,
+        sells: bool,
+        issuance: bool,
+    },
+    Guardian {
+        guardian: Pubkey,
+    },
+    Admins {
+        admins: Vec<Pubkey>,
+        threshold: u8,
+    },
+    IsolateReserve {
+        owner: Pubkey,
+    },
+}
+
+impl ConfigChange {
+    // tag + the largest variant (Admins)
+    pub const MAX_LEN: usize = 1 + 4 + 32 * MAX_ADMINS + 1;
 }
 
 #[account]
-#[derive(Default, Debug)]
+#[derive(Debug)]
+pub struct Proposal {
+    /// Proposal id, the config's proposal count at creation
+    pub id: u64,
+    /// Admin who created the proposal
+    pub proposer: Pubkey,
+    /// The config change to apply
+    pub change: ConfigChange,
+    /// Admins who approved the proposal
+    pub approvals: [Pubkey; MAX_ADMINS],
+    /// Number of used entries in `approvals`
+    pub approvals_len: u8,
+    /// Set once the change is applied
+    pub executed: bool,
+
+    pub bump: u8,
+    /// Unix timestamp from which the proposal can't be approved or executed.
+    /// Proposals created before expiry read 0 and are expired.
+    pub expires_ts: i64,
+}
+
+impl Proposal {
+    pub const LEN: usize =
+        DISCRIMINATOR_LENGTH + 8 + 32 + ConfigChange::MAX_LEN + 32 * MAX_ADMINS + 1 + 1 + 1 + 8;
+    pub const SEED: &[u8] = b"proposal";
+    /// How long admins have to approve and execute a proposal
+    pub const EXPIRY_SECS: i64 = 7 * 24 * 60 * 60;
+
+    pub fn is_expired(&self, now: i64) -> bool {
+        now >= self.expires_ts
+    }
+
+    pub fn approvals(&self) -> &[Pubkey] {
+        &self.approvals[..self.approvals_len as usize]
+    }
+
+    /// Count approvals from keys that are still admins
+    pub fn valid_approvals(&self, config: &Config) -> usize {
+        self.approvals()
+            .iter()
+            .filter(|approver| config.is_admin(approver))
+            .count()
+    }
+}
+
+#[account]
+#[derive(Debug)]
 pub struct PassesSupply {
+    // Account layout version
+    pub version: u8,
     // The supply associated with the  passes owner
     pub amount: u64,
+    // The owner's switch to stop trading in the market
+    pub paused: bool,
 
     pub bump: u8,
+    // Supply cap, 0 means no cap
+    pub max_supply: u64,
+    // Unix timestamp before which buys fail
+    pub launch_ts: i64,
+    // Supply added to the curve position, raises the starting price
+    pub price_offset: u64,
+    // Merkle root of the wallets allowed to buy during the presale
+    pub presale_merkle_root: [u8; 32],
+    // Unix timestamp the presale ends at, buys are open to everyone afterwards
+    pub presale_end_ts: i64,
+    // Max balance of an allowlisted wallet during the presale
+    pub presale_wallet_cap: u64,
+    // Max balance of a holder, 0 means no cap
+    pub max_wallet_amount: u64,
+    // Max balance of a holder in bps of the supply, 0 means no cap
+    pub max_wallet_bps: u64,
+    // Seconds a holder has to wait after a buy before selling, 0 means no wait
+    pub min_hold_secs: i64,
+    // Fee paid to the owner on sells within the holding period, 0 rejects early sells
+    pub early_exit_fee_bps: u64,
+    // The owner's issued passes vest, see OwnerVesting
+    pub vesting: bool,
+    // Holders share the owner's income, see RewardPool
+    pub rewards: bool,
+    // Passes held in holder pages, they don't earn rewards
+    pub paged_amount: u64,
+    // Cumulative trading stats, see TradeStats
+    pub stats: TradeStats,
+    // The market's reserve is kept in its own reserve wallets instead of the marketplace escrows
+    pub isolated_reserve: bool,
+    // The stats tracked every trade since issuance, markets migrated from before them didn't
+    pub stats_complete: bool,
+    // Passes issued to the owner, nobody paid for them so they stay in the owner's balance
+    pub issued_amount: u64,
+    // The currency of the market's first trade, which its later trades keep to
+    pub currency: MarketCurrency,
+    // Reserved for future fields, new fields take their space from here
+    pub reserved: [u8; 53],
 }
 
 impl PassesSupply {
-    pub const LEN: usize = DISCRIMINATOR_LENGTH + std::mem::size_of::<PassesSupply>();
+    pub const LEN: usize = DISCRIMINATOR_LENGTH
+        + 1 // version
+        + 8 // amount
+        + 1 // paused
+        + 1 // bump
+        + 8 // max_supply
+        + 8 // launch_ts
+        + 8 // price_offset
+        + 32 // presale_merkle_root
+        + 8 // presale_end_ts
+        + 8 // presale_wallet_cap
+        + 8 // max_wallet_amount
+        + 8 // max_wallet_bps
+        + 8 // min_hold_secs
+        + 8 // early_exit_fee_bps
+        + 1 // vesting
+        + 1 // rewards
+        + 8 // paged_amount
+        + TradeStats::LEN // stats
+        + 1 // isolated_reserve
+        + 1 // stats_complete
+        + 8 // issued_amount
+        + 1 // currency
+        + 53; // reserved
+    /// Size of the version 1 layout. The trade stats didn't fit its 14 reserved bytes, so version 1
+    /// accounts don't deserialize and their market can't trade until `migrate_supply` runs.
+    pub const V1_LEN: usize = DISCRIMINATOR_LENGTH
+        + 1 // version
+        + 8 // amount
+        + 1 // paused
+        + 1 // bump
+        + 8 // max_supply
+        + 8 // launch_ts
+        + 8 // price_offset
+        + 32 // presale_merkle_root
+        + 8 // presale_end_ts
+        + 8 // presale_wallet_cap
+        + 8 // max_wallet_amount
+        + 8 // max_wallet_bps
+        + 8 // min_hold_secs
+        + 8 // early_exit_fee_bps
+        + 1 // vesting
+        + 1 // rewards
+        + 8 // paged_amount
+        + 14; // reserved
     pub const SEED: &[u8] = b"supply";
+    // Seeds of the isolated reserve wallets, the owner follows the marketplace seed
+    pub const RESERVE_TOKEN_SEED: &[u8] = b"reserve";
+    pub const RESERVE_SOL_SEED: &[u8] = b"reserve_sol";
+    pub const VERSION: u8 = 2;
+    // Keeps the curve away from overflowing the price calculation
+    pub const MAX_PRICE_OFFSET: u64 = 1_000;
+    pub const MAX_EARLY_EXIT_FEE_BPS: u64 = 5_000;
+
+    /// Record a trade of `amount` passes for `price` in the stats, after the supply changed
+    pub fn record_trade(
+        &mut self,
+        side: TradeSide,
+        sol: bool,
+        amount: u64,
+        price: u64,
+        now: i64,
+    ) -> Result<()> {
+        let stats = &mut self.stats;
+        let volume = match (side, sol) {
+            (TradeSide::Buy, false) => &mut stats.buy_volume,
+            (TradeSide::Buy, true) => &mut stats.buy_volume_sol,
+            (TradeSide::Sell, false) => &mut stats.sell_volume,
+            (TradeSide::Sell, true) => &mut stats.sell_volume_sol,
+        };
+        *volume = volume.checked_add(price).ok_or(PassesError::MathOverflow)?;
+        stats.trade_count = stats
+            .trade_count
+            .checked_add(1)
+            .ok_or(PassesError::MathOverflow)?;
+        stats.last_price = price / amount.max(1);
+        stats.last_price_sol = sol;
+        stats.last_trade_ts = now;
+        stats.ath_supply = stats.ath_supply.max(self.amount);
+        Ok(())
+    }
+
+    /// Pin the market to the currency of its first trade. Both curves share the supply, but each
+    /// currency's reserve only backs the passes bought in it.
+    pub fn check_currency(&mut self, sol: bool) -> Result<()> {
+        let currency = if sol {
+            MarketCurrency::Sol
+        } else {
+            MarketCurrency::Token
+        };
+        if self.currency == MarketCurrency::Unset {
+            self.currency = currency;
+        }
+        require!(self.currency == currency, PassesError::CurrencyMismatch);
+        Ok(())
+    }
+
+    /// Check the owner keeps the issued passes after parting with `amount` of their `balance`.
+    /// The reserve only holds what buyers paid, so it doesn't back the issued passes.
+    pub fn check_issued(&self, balance: u64, amount: u64) -> Result<()> {
+        require!(
+            balance
+                .checked_sub(amount)
+                .is_some_and(|left| left >= self.issued_amount),
+            PassesError::IssuedPassesLocked
+        );
+        Ok(())
+    }
+
+    /// Position on the bonding curve for the given supply
+    pub fn curve_supply(&self, supply: u64) -> Result<u64> {
+        Ok(supply
+            .checked_add(self.price_offset)
+            .ok_or(PassesError::MathOverflow)?)
+    }
+
+    /// Passes held in balance accounts, which share the holder rewards
+    pub fn rewarded_supply(&self) -> u64 {
+        self.amount.saturating_sub(self.paged_amount)
+    }
+
+    /// Check the market is launched and `amount` more passes fit under the cap
+    pub fn check_buy(&self, amount: u64, now: i64) -> Result<()> {
+        require!(now >= self.launch_ts, PassesError::NotLaunched);
+        let new_supply = self
+            .amount
+            .checked_add(amount)
+            .ok_or(PassesError::MathOverflow)?;
+        require!(
+            self.max_supply == 0 || new_supply <= self.max_supply,
+            PassesError::MaxSupplyExceeded
+        );
+        Ok(())
+    }
+
+    /// Check a holder's balance against the wallet caps for the given supply.
+    /// The bps cap always allows at least one pass so small markets stay buyable.
+    pub fn check_wallet_cap(&self, balance: u64, supply: u64) -> Result<()> {
+        if self.max_wallet_amount > 0 {
+            require!(
+                balance <= self.max_wallet_amount,
+                PassesError::WalletCapExceeded
+            );
+        }
+        if self.max_wallet_bps > 0 {
+            let cap = math::checked_as_u64(
+                math::checked_mul(supply as u128, self.max_wallet_bps as u128)? / math::BPS_POWER,
+            )?;
+            require!(balance <= cap.max(1), PassesError::WalletCapExceeded);
+        }
+        Ok(())
+    }
+
+    /// Holder pages only hold part of a wallet's passes, so the caps can't see them. Passes can't
+    /// go into a page while a wallet cap or the presale is active.
+    pub fn check_holder_page(&self, now: i64) -> Result<()> {
+        let capped =
+            self.max_wallet_amount > 0 || self.max_wallet_bps > 0 || now < self.presale_end_ts;
+        require!(!capped, PassesError::HolderPageCapped);
+        Ok(())
+    }
+
+    /// Fee on a sell of `price` within the holding period of the last buy,
+    /// fails when the owner doesn't allow early sells
+    pub fn early_exit_fee(&self, last_buy_ts: i64, now: i64, price: u64) -> Result<u64> {
+        let hold_end = last_buy_ts
+            .checked_add(self.min_hold_secs)
+            .ok_or(PassesError::MathOverflow)?;
+        if now >= hold_end {
+            return Ok(0);
+        }
+        require!(
+            self.early_exit_fee_bps > 0,
+            PassesError::HoldingPeriodNotOver
+        );
+        calc_fee(self.early_exit_fee_bps, price)
+    }
+
+    /// During the presale only allowlisted wallets can buy, up to the wallet cap
+    pub fn check_presale(
+        &self,
+        buyer: &Pubkey,
+        proof: &[[u8; 32]],
+        balance: u64,
+        now: i64,
+    ) -> Result<()> {
+        if now >= self.presale_end_ts {
+            return Ok(());
+        }
+        require!(
+            merkle::verify(proof, &self.presale_merkle_root, merkle::leaf(buyer)),
+            PassesError::NotAllowlisted
+        );
+        require!(
+            balance <= self.presale_wallet_cap,
+            PassesError::PresaleCapExceeded
+        );
+        Ok(())
+    }
+}
+
+// Both curves share a market's supply, so a market trades in one currency only
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MarketCurrency {
+    // Not traded yet, or migrated from before the currency was tracked
+    #[default]
+    Unset,
+    Token,
+    Sol,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TradeSide {
+    Buy,
+    Sell,
+}
+
+/// Cumulative trading stats of a market, volumes are the curve prices of the trades
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct TradeStats {
+    // Token volume of buys
+    pub buy_volume: u64,
+    // Token volume of sells
+    pub sell_volume: u64,
+    // SOL volume of buys
+    pub buy_volume_sol: u64,
+    // SOL volume of sells
+    pub sell_volume_sol: u64,
+    // Number of buys and sells
+    pub trade_count: u64,
+    // Price per pass of the last trade, in its currency
+    pub last_price: u64,
+    // The last trade was paid in SOL
+    pub last_price_sol: bool,
+    // Unix timestamp of the last trade
+    pub last_trade_ts: i64,
+    // Highest supply the market reached
+    pub ath_supply: u64,
+}
+
+impl TradeStats {
+    pub const LEN: usize = 8 // buy_volume
+        + 8 // sell_volume
+        + 8 // buy_volume_sol
+        + 8 // sell_volume_sol
+        + 8 // trade_count
+        + 8 // last_price
+        + 1 // last_price_sol
+        + 8 // last_trade_ts
+        + 8; // ath_supply
+}
+
+/// Parameters set by the owner when issuing passes
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct IssuanceParams {
+    // Supply cap, 0 means no cap
+    pub max_supply: u64,
+    // Unix timestamp before which buys fail
+    pub launch_ts: i64,
+    // Supply added to the curve position, raises the starting price
+    pub price_offset: u64,
+    // Merkle root of the presale allowlist
+    pub presale_merkle_root: [u8; 32],
+    // End of the presale that starts at launch, 0 means no presale
+    pub presale_end_ts: i64,
+    // Max balance of an allowlisted wallet during the presale
+    pub presale_wallet_cap: u64,
+    // Seconds over which the issued passes vest linearly from the launch, 0 means no vesting
+    pub vesting_duration: i64,
+    // Keep the market's reserve in its own reserve wallets instead of the marketplace escrows
+    pub isolated_reserve: bool,
 }
 
 #[account]
-#[derive(Default, Debug)]
+#[derive(Debug)]
 pub struct PassesBalance {
+    // Account layout version
+    pub version: u8,
     // The passes balances for respective holder and owner
     pub amount: u64,
 
     pub bump: u8,
+    // Unix timestamp of the holder's last buy, starts the holding period
+    pub last_buy_ts: i64,
+    // Reward pool token index at the last settlement
+    pub token_reward_index: u128,
+    // Reward pool SOL index at the last settlement
+    pub sol_reward_index: u128,
+    // Settled token rewards waiting to be claimed
+    pub pending_token_rewards: u64,
+    // Settled SOL rewards waiting to be claimed
+    pub pending_sol_rewards: u64,
+    // Passes locked in the holder's stake, they can't be sold or moved
+    pub staked_amount: u64,
+    // Reserved for future fields, new fields take their space from here
+    pub reserved: [u8; 32],
 }
 
 impl PassesBalance {
-    pub const LEN: usize = DISCRIMINATOR_LENGTH + std::mem::size_of::<PassesBalance>();
+    pub const LEN: usize = DISCRIMINATOR_LENGTH
+        + 1 // version
+        + 8 // amount
+        + 1 // bump
+        + 8 // last_buy_ts
+        + 16 // token_reward_index
+        + 16 // sol_reward_index
+        + 8 // pending_token_rewards
+        + 8 // pending_sol_rewards
+        + 8 // staked_amount
+        + 32; // reserved
+    /// Size of the version 1 layout, the staked amount used up its reserved space
+    pub const V1_LEN: usize = DISCRIMINATOR_LENGTH
+        + 1 // version
+        + 8 // amount
+        + 1 // bump
+        + 8 // last_buy_ts
+        + 16 // token_reward_index
+        + 16 // sol_reward_index
+        + 8 // pending_token_rewards
+        + 8 // pending_sol_rewards
+        + 8; // staked_amount
     pub const SEED: &[u8] = b"balance";
+    pub const VERSION: u8 = 2;
+    // Keeps an airdrop transaction within the packet size and compute budget
+    pub const MAX_AIRDROP_RECIPIENTS: usize = 10;
+
+    /// Decode a balance in the unversioned or version 1 layout, which `migrate_balance` upgrades
+    pub fn try_deserialize_unmigrated(data: &[u8]) -> Result<Self> {
+        require!(
+            data.len() >= DISCRIMINATOR_LENGTH && data[..8] == Self::DISCRIMINATOR,
+            PassesError::InvalidAccountVersion
+        );
+        match data.len() {
+            v0::PassesBalance::LEN => {
+                let old = v0::PassesBalance::deserialize(&mut &data[8..])?;
+                Ok(Self {
+                    version: 0,
+                    amount: old.amount,
+                    bump: old.bump,
+                    last_buy_ts: 0,
+                    token_reward_index: 0,
+                    sol_reward_index: 0,
+                    pending_token_rewards: 0,
+                    pending_sol_rewards: 0,
+                    staked_amount: 0,
+                    reserved: [0; 32],
+                })
+            }
+            // version 1 lacks the reserved space, it is zeroed
+            Self::V1_LEN => {
+                let mut data = data.to_vec();
+                data.resize(Self::LEN, 0);
+                let passes_balance = Self::try_deserialize(&mut &data[..])?;
+                require!(
+                    passes_balance.version == 1,
+                    PassesError::InvalidAccountVersion
+                );
+                Ok(passes_balance)
+            }
+            _ => err!(PassesError::InvalidAccountVersion),
+        }
+    }
+
+    /// Passes the holder can sell or move, the balance minus the staked passes
+    pub fn unstaked(&self) -> u64 {
+        self.amount.saturating_sub(self.staked_amount)
+    }
+
+    /// Credit the rewards accrued since the last settlement, must run before the amount changes
+    pub fn settle_rewards(&mut self, reward_pool: &RewardPool) -> Result<()> {
+        let token_rewards = RewardPool::accrued(
+            self.amount,
+            reward_pool.token_index,
+            self.token_reward_index,
+        )?;
+        let sol_rewards =
+            RewardPool::accrued(self.amount, reward_pool.sol_index, self.sol_reward_index)?;
+        self.pending_token_rewards = self
+            .pending_token_rewards
+            .checked_add(token_rewards)
+            .ok_or(PassesError::MathOverflow)?;
+        self.pending_sol_rewards = self
+            .pending_sol_rewards
+            .checked_add(sol_rewards)
+            .ok_or(PassesError::MathOverflow)?;
+        self.token_reward_index = reward_pool.token_index;
+        self.sol_reward_index = reward_pool.sol_index;
+        Ok(())
+    }
+}
+
+/// Rewards shared by the holders of an owner's passes, paid from the escrows.
+/// The indexes accumulate the rewards per pass held in a balance account.
+#[account]
+#[derive(Debug)]
+pub struct RewardPool {
+    // Account layout version
+    pub version: u8,
+    // The passes owner
+    pub owner: Pubkey,
+    // Share of the owner fees going to holders
+    pub holder_fee_bps: u64,
+    // Token rewards per pass, scaled by INDEX_PRECISION
+    pub token_index: u128,
+    // SOL rewards per pass, scaled by INDEX_PRECISION
+    pub sol_index: u128,
+
+    pub bump: u8,
+    // Reserved for future fields, new fields take their space from here
+    pub reserved: [u8; 32],
+}
+
+impl RewardPool {
+    pub const LEN: usize = DISCRIMINATOR_LENGTH
+        + 1 // version
+        + 32 // owner
+        + 8 // holder_fee_bps
+        + 16 // token_index
+        + 16 // sol_index
+        + 1 // bump
+        + 32; // reserved
+    pub const SEED: &[u8] = b"rewards";
+    pub const VERSION: u8 = 1;
+    pub const INDEX_PRECISION: u128 = 1_000_000_000_000;
+
+    /// Holders' share of `owner_fees`, nothing when no pass earns rewards
+    pub fn holder_fees(&self, owner_fees: u64, supply: u64) -> Result<u64> {
+        if supply == 0 {
+            return Ok(0);
+        }
+        calc_fee(self.holder_fee_bps, owner_fees)
+    }
+
+    /// Move the holders' share of token `owner_fees` into the pool, returns the share
+    pub fn share_token_fees(&mut self, owner_fees: u64, supply: u64) -> Result<u64> {
+        let holder_fees = self.holder_fees(owner_fees, supply)?;
+        self.distribute_token(holder_fees, supply)?;
+        Ok(holder_fees)
+    }
+
+    /// Move the holders' share of SOL `owner_fees` into the pool, returns the share
+    pub fn share_sol_fees(&mut self, owner_fees: u64, supply: u64) -> Result<u64> {
+        let holder_fees = self.holder_fees(owner_fees, supply)?;
+        self.distribute_sol(holder_fees, supply)?;
+        Ok(holder_fees)
+    }
+
+    /// Share `amount` of token rewards between `supply` passes
+    pub fn distribute_token(&mut self, amount: u64, supply: u64) -> Result<()> {
+        self.token_index = Self::increase_index(self.token_index, amount, supply)?;
+        Ok(())
+    }
+
+    /// Share `amount` of SOL rewards between `supply` passes
+    pub fn distribute_sol(&mut self, amount: u64, supply: u64) -> Result<()> {
+        self.sol_index = Self::increase_index(self.sol_index, amount, supply)?;
+        Ok(())
+    }
+
+    // The index rounds down so holders never claim more than was distributed
+    fn increase_index(index: u128, amount: u64, supply: u64) -> Result<u128> {
+        if amount == 0 {
+            return Ok(index);
+        }
+        require!(supply > 0, PassesError::ZeroSupply);
+        let increase = math::checked_mul(amount as u128, Self::INDEX_PRECISION)? / supply as u128;
+        Ok(index
+            .checked_add(increase)
+            .ok_or(PassesError::MathOverflow)?)
+    }
+
+    fn accrued(amount: u64, index: u128, last_index: u128) -> Result<u64> {
+        let delta = index
+            .checked_sub(last_index)
+            .ok_or(PassesError::MathOverflow)?;
+        math::checked_as_u64(math::checked_mul(amount as u128, delta)? / Self::INDEX_PRECISION)
+    }
+}
+
+/// Staking in an owner's market, the owner funds token rewards paid out
+/// at a constant rate between the staked passes
+#[account]
+#[derive(Debug)]
+pub struct StakePool {
+    // Account layout version
+    pub version: u8,
+    // The passes owner
+    pub owner: Pubkey,
+    // Passes staked by all holders
+    pub total_staked: u64,
+    // Token rewards paid per second to all stakes
+    pub reward_rate: u64,
+    // Unix timestamp the funded rewards run out
+    pub reward_end_ts: i64,
+    // Token rewards per staked pass, scaled by INDEX_PRECISION
+    pub reward_index: u128,
+    // Unix timestamp of the last index update
+    pub last_update_ts: i64,
+
+    pub bump: u8,
+    // Reserved for future fields, new fields take their space from here
+    pub reserved: [u8; 32],
+}
+
+impl StakePool {
+    pub const LEN: usize = DISCRIMINATOR_LENGTH
+        + 1 // version
+        + 32 // owner
+        + 8 // total_staked
+        + 8 // reward_rate
+        + 8 // reward_end_ts
+        + 16 // reward_index
+        + 8 // last_update_ts
+        + 1 // bump
+        + 32; // reserved
+    pub const SEED: &[u8] = b"stake_pool";
+    pub const VERSION: u8 = 1;
+    pub const INDEX_PRECISION: u128 = 1_000_000_000_000;
+
+    /// Accrue the rewards paid since the last update, must run before the staked amount changes.
+    /// Nothing accrues while no pass is staked, those rewards stay in escrow.
+    pub fn update(&mut self, now: i64) -> Result<()> {
+        let until = now.min(self.reward_end_ts);
+        if until > self.last_update_ts && self.total_staked > 0 {
+            let elapsed = (until - self.last_update_ts) as u128;
+            let rewards = math::checked_mul(elapsed, self.reward_rate as u128)?;
+            // rounds down so stakers never claim more than was funded
+            let increase =
+                math::checked_mul(rewards, Self::INDEX_PRECISION)? / self.total_staked as u128;
+            self.reward_index = self
+                .reward_index
+                .checked_add(increase)
+                .ok_or(PassesError::MathOverflow)?;
+        }
+        self.last_update_ts = self.last_update_ts.max(now);
+        Ok(())
+    }
+
+    /// Pay `amount` plus the rewards not paid yet over the next `duration` seconds
+    pub fn fund(&mut self, amount: u64, duration: i64, now: i64) -> Result<()> {
+        require!(duration > 0, PassesError::InvalidStakeRewards);
+        let leftover = if now < self.reward_end_ts {
+            math::checked_mul((self.reward_end_ts - now) as u128, self.reward_rate as u128)?
+        } else {
+            0
+        };
+        let total = leftover
+            .checked_add(amount as u128)
+            .ok_or(PassesError::MathOverflow)?;
+        self.reward_rate = math::checked_as_u64(total / duration as u128)?;
+        require!(self.reward_rate > 0, PassesError::InvalidStakeRewards);
+        self.reward_end_ts = now.checked_add(duration).ok_or(PassesError::MathOverflow)?;
+        Ok(())
+    }
+}
+
+/// A holder's staked passes in an owner's market
+#[account]
+#[derive(Debug)]
+pub struct PassesStake {
+    // Account layout version
+    pub version: u8,
+    // The passes owner
+    pub owner: Pubkey,
+    // The holder
+    pub holder: Pubkey,
+    // Staked passes, mirrored by the holder's PassesBalance::staked_amount
+    pub amount: u64,
+    // Time-weighted points, staked passes times seconds staked
+    pub points: u128,
+    // Stake pool index at the last settlement
+    pub reward_index: u128,
+    // Settled token rewards waiting to be claimed
+    pub pending_rewards: u64,
+    // Unix timestamp of the last settlement
+    pub last_update_ts: i64,
+
+    pub bump: u8,
+    // Reserved for future fields, new fields take their space from here
+    pub reserved: [u8; 32],
+}
+
+impl PassesStake {
+    pub const LEN: usize = DISCRIMINATOR_LENGTH
+        + 1 // version
+        + 32 // owner
+        + 32 // holder
+        + 8 // amount
+        + 16 // points
+        + 16 // reward_index
+        + 8 // pending_rewards
+        + 8 // last_update_ts
+        + 1 // bump
+        + 32; // reserved
+    pub const SEED: &[u8] = b"stake";
+    pub const VERSION: u8 = 1;
+
+    /// Credit the points and rewards earned since the last settlement, the pool must be updated first
+    pub fn settle(&mut self, stake_pool: &StakePool, now: i64) -> Result<()> {
+        if now > self.last_update_ts {
+            let points =
+                math::checked_mul(self.amount as u128, (now - self.last_update_ts) as u128)?;
+            self.points = self
+                .points
+                .checked_add(points)
+                .ok_or(PassesError::MathOverflow)?;
+            self.last_update_ts = now;
+        }
+        let delta = stake_pool
+            .reward_index
+            .checked_sub(self.reward_index)
+            .ok_or(PassesError::MathOverflow)?;
+        let rewards = math::checked_as_u64(
+            math::checked_mul(self.amount as u128, delta)? / StakePool::INDEX_PRECISION,
+        )?;
+        self.pending_rewards = self
+            .pending_rewards
+            .checked_add(rewards)
+            .ok_or(PassesError::MathOverflow)?;
+        self.reward_index = stake_pool.reward_index;
+        Ok(())
+    }
+}
+
+/// Linear vesting of the passes an owner issued to themselves
+#[account]
+#[derive(Debug)]
+pub struct OwnerVesting {
+    // Account layout version
+    pub version: u8,
+    // The passes owner
+    pub owner: Pubkey,
+    // Passes under vesting
+    pub amount: u64,
+    // Unix timestamp the vesting starts at
+    pub start_ts: i64,
+    // Unix timestamp all passes are vested at
+    pub end_ts: i64,
+
+    pub bump: u8,
+    // Reserved for future fields, new fields take their space from here
+    pub reserved: [u8; 32],
+}
+
+impl OwnerVesting {
+    pub const LEN: usize = DISCRIMINATOR_LENGTH
+        + 1 // version
+        + 32 // owner
+        + 8 // amount
+        + 8 // start_ts
+        + 8 // end_ts
+        + 1 // bump
+        + 32; // reserved
+    pub const SEED: &[u8] = b"vesting";
+    pub const VERSION: u8 = 1;
+
+    /// Passes still locked at `now`
+    pub fn locked(&self, now: i64) -> Result<u64> {
+        if now >= self.end_ts {
+            return Ok(0);
+        }
+        if now <= self.start_ts {
+            return Ok(self.amount);
+        }
+        let elapsed = (now - self.start_ts) as u128;
+        let duration = (self.end_ts - self.start_ts) as u128;
+        let vested = math::checked_mul(self.amount as u128, elapsed)? / duration;
+        math::checked_as_u64(self.amount as u128 - vested)
+    }
+}
+
+#[account]
+#[derive(Debug)]
+pub struct PassesMetadata {
+    // Account layout version
+    pub version: u8,
+    // The passes owner
+    pub owner: Pubkey,
+    // Market name
+    pub name: String,
+    // URI of the market description
+    pub description_uri: String,
+    // URI of the market image
+    pub image_uri: String,
+    // The owner's social handle
+    pub social_handle: String,
+
+    pub bump: u8,
+}
+
+impl PassesMetadata {
+    pub const MAX_NAME_LEN: usize = 32;
+    pub const MAX_URI_LEN: usize = 200;
+    pub const MAX_SOCIAL_HANDLE_LEN: usize = 32;
+
+    pub const LEN: usize = DISCRIMINATOR_LENGTH
+        + 1 // version
+        + 32 // owner
+        + 4 + Self::MAX_NAME_LEN // name
+        + 4 + Self::MAX_URI_LEN // description_uri
+        + 4 + Self::MAX_URI_LEN // image_uri
+        + 4 + Self::MAX_SOCIAL_HANDLE_LEN // social_handle
+        + 1; // bump
+    pub const SEED: &[u8] = b"metadata";
+    pub const VERSION: u8 = 1;
+}
+
+/// Compact storage of passes balances for up to `HOLDERS_PER_PAGE` holders of one owner.
+/// A holder moves their balance here to close their `PassesBalance` account and get its rent
+/// back, and moves it back to a `PassesBalance` account to trade.
+#[account(zero_copy)]
+#[derive(Debug)]
+pub struct HolderPage {
+    /// The passes owner
+    pub passes_owner: Pubkey,
+    /// The page index
+    pub page: u32,
+    /// Number of used entries
+    pub len: u16,
+
+    pub bump: u8,
+    pub padding: u8,
+    /// Holder keys, the first `len` entries are used
+    pub holders: [Pubkey; HOLDERS_PER_PAGE],
+    /// Passes balances of the holders at the same index
+    pub amounts: [u64; HOLDERS_PER_PAGE],
+}
+
+impl HolderPage {
+    pub const LEN: usize = DISCRIMINATOR_LENGTH
+        + 32 // passes_owner
+        + 4 // page
+        + 2 // len
+        + 1 // bump
+        + 1 // padding
+        + 32 * HOLDERS_PER_PAGE // holders
+        + 8 * HOLDERS_PER_PAGE; // amounts
+    pub const SEED: &[u8] = b"holders";
+
+    pub fn find(&self, holder: &Pubkey) -> Option<usize> {
+        self.holders[..self.len as usize]
+            .iter()
+            .position(|key| key == holder)
+    }
+
+    /// Add passes to the holder's entry, creating it if needed
+    pub fn deposit(&mut self, holder: &Pubkey, amount: u64) -> Result<u64> {
+        let index = match self.find(holder) {
+            Some(index) => index,
+            None => {
+                let index = self.len as usize;
+                require!(index < HOLDERS_PER_PAGE, PassesError::HolderPageFull);
+                self.holders[index] = *holder;
+                self.amounts[index] = 0;
+                self.len += 1;
+                index
+            }
+        };
+        self.amounts[index] = self.amounts[index]
+            .checked_add(amount)
+            .ok_or(PassesError::MathOverflow)?;
+
+        Ok(self.amounts[index])
+    }
+
+    /// Remove the holder's entry and return their passes
+    pub fn withdraw(&mut self, holder: &Pubkey) -> Result<u64> {
+        let index = self.find(holder).ok_or(PassesError::HolderNotInPage)?;
+        let amount = self.amounts[index];
+
+        // keep the used entries contiguous
+        let last = self.len as usize - 1;
+        self.holders[index] = self.holders[last];
+        self.amounts[index] = self.amounts[last];
+        self.holders[last] = Pubkey::default();
+        self.amounts[last] = 0;
+        self.len -= 1;
+
+        Ok(amount)
+    }
 }
 
 #[account]
@@ -63,8 +1071,48 @@ pub struct EscrowSOL {
 }
 
 impl EscrowSOL {
-    pub const LEN: usize = DISCRIMINATOR_LENGTH + std::mem::size_of::<EscrowSOL>();
+    pub const LEN: usize = DISCRIMINATOR_LENGTH + 1; // bump
     pub const SEED: &[u8] = b"escrow";
 }
 
-// TODO pub for_future_use: [u8; 128],
+/// Account layouts before versioning (version 0), kept to migrate existing accounts
+pub mod v0 {
+    use super::*;
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Debug)]
+    pub struct Config {
+        pub admin: Pubkey,
+        pub payment_mint: Pubkey,
+        pub escrow_token_wallet: Pubkey,
+        pub escrow_sol_wallet: Pubkey,
+        pub protocol_fee_bps: u64,
+        pub owner_fee_bps: u64,
+        pub protocol_fee_token_wallet: Pubkey,
+        pub bump: u8,
+    }
+
+    impl Config {
+        // the size was computed with `std::mem::size_of`, so it includes the struct padding
+        pub const LEN: usize = DISCRIMINATOR_LENGTH + 184;
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Debug)]
+    pub struct PassesSupply {
+        pub amount: u64,
+        pub bump: u8,
+    }
+
+    impl PassesSupply {
+        pub const LEN: usize = DISCRIMINATOR_LENGTH + 16;
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Debug)]
+    pub struct PassesBalance {
+        pub amount: u64,
+        pub bump: u8,
+    }
+
+    impl PassesBalance {
+        pub const LEN: usize = DISCRIMINATOR_LENGTH + 16;
+    }
+}
@@ -2,30 +2,45 @@
 #![allow(unused_imports)]
 #![allow(dead_code)]
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use anchor_lang::{
     prelude::{borsh::BorshDeserialize, *},
-    system_program, InstructionData, Owner,
+    system_program, Discriminator, InstructionData, Owner,
 };
 use assert_matches::*;
+use base64::Engine;
 use bonfida_test_utils::ProgramTestContextExt;
 use bonfida_test_utils::ProgramTestExt;
 use maplit::hashmap;
-use solana_program::program_pack::Pack;
+use passes_test_utils::{add_upgradeable_program, program_data_address};
+use proptest::prelude::{
+    any, prop_assert, prop_assert_eq, prop_oneof, proptest, ProptestConfig, Strategy,
+};
+use solana_program::{bpf_loader_upgradeable, program_pack::Pack};
 use solana_program_test::{tokio, BanksClientError, ProgramTest, ProgramTestContext};
 use solana_sdk::{
     account::{self, ReadableAccount},
-    instruction::AccountMeta,
+    bpf_loader_upgradeable::UpgradeableLoaderState,
+    instruction::{AccountMeta, InstructionError},
     pubkey::Pubkey,
     signature::{Keypair, Signature, Signer},
+    transaction::TransactionError,
 };
 
 use crate::{
     accounts::{self},
+    error::PassesError,
+    events::{PassesMetadataUpdated, PassesTraded},
+    gate,
     instruction::{self},
-    state::{Config, PassesBalance, PassesSupply},
-    ONE_USDC, USDC_DECIMALS,
+    merkle,
+    state::{
+        self, Config, ConfigChange, HolderPage, IssuanceParams, MarketCurrency, OwnerVesting,
+        PassesBalance, PassesMetadata, PassesStake, PassesSupply, Proposal, RewardPool, StakePool,
+        TradeSide, TradeStats,
+    },
+    ONE_SOL, ONE_USDC, USDC_DECIMALS,
 };
 use utils::*;
 
@@ -73,128 +88,316 @@ async fn test_init() {
     assert_eq!(config.owner_fee_bps, OWNER_FEE_BPS);
     assert_eq!(config.protocol_fee_bps, PROTOCOL_FEE_BPS);
     assert_eq!(config.protocol_fee_token_wallet, protocol_fee_wallet);
+    assert_eq!(config.protocol_fee_sol_wallet, initializer.pubkey());
 }
 
 #[tokio::test]
-async fn test_get_price() {
-    let (mut ctx, initializer, _, _, _) = setup().await;
+async fn test_marketplaces() {
+    let (mut ctx, initializer, buyer, owner, mint) = setup().await;
 
-    // calc and get price for token (e.g. usdc)
-    let args = instruction::GetPrice {
-        supply: 3,
-        amount: 1,
-    };
-    let accounts = accounts::GetPrice {
-        invoker: initializer.pubkey(),
-    };
-    let res = simulate_tx(
+    init_passes(
         &mut ctx,
-        accounts.to_account_metas(None),
-        &args,
         &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
     )
     .await;
-    assert_matches!(res, Ok(56250_u64));
 
-    // calc and get price for sol
-    let args = instruction::GetPriceSol {
-        supply: 3,
-        amount: 1,
-    };
-    let accounts = accounts::GetPrice {
-        invoker: initializer.pubkey(),
+    // a partner runs marketplace 1 with its own admin and fees
+    let partner = Keypair::new();
+    let ix = solana_sdk::system_instruction::transfer(
+        &initializer.pubkey(),
+        &partner.pubkey(),
+        100_000_000,
+    );
+    let mut tx =
+        solana_sdk::transaction::Transaction::new_with_payer(&[ix], Some(&initializer.pubkey()));
+    tx.sign(&[&initializer], ctx.last_blockhash);
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    // the upgrade authority approves new marketplaces
+    let res = try_init_marketplace(&mut ctx, &partner, &partner, 1, &mint, 500, 300).await;
+    assert_passes_error(res, PassesError::NotUpgradeAuthority);
+    let res = try_init_marketplace(&mut ctx, &partner, &initializer, 1, &mint, 500, 300).await;
+    assert_matches!(res, Ok(()));
+
+    let (config_pda, _) = get_marketplace_pda(1, b"config", &[]);
+    assert_eq!(config_pda, gate::config_address(1));
+    assert_eq!(get_config_pda().0, gate::config_address(0));
+    let config: Config = get_account(&mut ctx, config_pda).await;
+    assert_eq!(config.marketplace_id, 1);
+    assert_eq!(config.admin, partner.pubkey());
+    assert_eq!(config.protocol_fee_bps, 500);
+    assert_eq!(config.owner_fee_bps, 300);
+    let (escrow_wallet, _) = get_marketplace_pda(1, b"escrow", &[&mint]);
+    assert_eq!(config.escrow_token_wallet, escrow_wallet);
+    assert_eq!(
+        config.escrow_sol_wallet,
+        get_marketplace_pda(1, b"escrow", &[]).0
+    );
+
+    // the default marketplace admin can't change the partner's fees
+    let args = instruction::SetProtocolFeeBps { fee_bps: 0 };
+    let accounts = accounts::SetFeePercent {
+        admin: initializer.pubkey(),
+        config: config_pda,
+        system_program: system_program::ID,
     };
-    let res = simulate_tx(
+    let res = execute_tx(
         &mut ctx,
         accounts.to_account_metas(None),
         &args,
         &initializer,
     )
     .await;
-    assert_matches!(res, Ok(5625000_u64));
+    assert!(res.is_err());
+
+    // the owner issues passes in the partner marketplace
+    let passes_supply = get_marketplace_pda(1, b"supply", &[&owner.pubkey()]).0;
+    let args = instruction::IssuePasses {
+        amount: 1,
+        params: IssuanceParams::default(),
+    };
+    let accounts = accounts::IssuePasses {
+        owner: owner.pubkey(),
+        passes_supply,
+        passes_balance: get_marketplace_pda(1, b"balance", &[&owner.pubkey(), &owner.pubkey()]).0,
+        passes_metadata: get_marketplace_pda(1, b"metadata", &[&owner.pubkey()]).0,
+        owner_vesting: None,
+        config: config_pda,
+        reserve_token_wallet: None,
+        reserve_sol_wallet: None,
+        owner_fee_wallet: anchor_spl::associated_token::get_associated_token_address(
+            &owner.pubkey(),
+            &mint,
+        ),
+        payment_mint: mint,
+        system_program: anchor_lang::system_program::ID,
+        token_program: anchor_spl::token::ID,
+        associated_token_program: anchor_spl::associated_token::ID,
+    };
+    let res = execute_tx(&mut ctx, accounts.to_account_metas(None), &args, &owner).await;
+    assert_matches!(res, Ok(()));
+
+    // the markets of the marketplaces are separate
+    let default_supply = ctx
+        .banks_client
+        .get_account(get_passes_supply_pda(&owner.pubkey()).0)
+        .await
+        .unwrap();
+    assert!(default_supply.is_none());
+
+    // the buyer pays the partner's fees into the partner's escrow
+    let default_escrow = get_escrow_token_wallet_pda(&mint).0;
+    let buyer_balance = get_marketplace_pda(1, b"balance", &[&owner.pubkey(), &buyer.pubkey()]).0;
+    let args = instruction::BuyPasses {
+        amount: 10,
+        proof: vec![],
+    };
+    let accounts = accounts::BuyPasses {
+        buyer: buyer.pubkey(),
+        passes_supply,
+        passes_balance: Some(buyer_balance),
+        holder_page: None,
+        reward_pool: None,
+        config: config_pda,
+        owner_fee_wallet: anchor_spl::associated_token::get_associated_token_address(
+            &owner.pubkey(),
+            &mint,
+        ),
+        escrow_wallet,
+        reserve_wallet: None,
+        passes_owner: owner.pubkey(),
+        payment_mint: mint,
+        protocol_fee_wallet: anchor_spl::associated_token::get_associated_token_address(
+            &partner.pubkey(),
+            &mint,
+        ),
+        buyer_wallet: anchor_spl::associated_token::get_associated_token_address(
+            &buyer.pubkey(),
+            &mint,
+        ),
+        system_program: system_program::ID,
+        token_program: anchor_spl::token::ID,
+    };
+    let res = execute_tx(&mut ctx, accounts.to_account_metas(None), &args, &buyer).await;
+    assert_matches!(res, Ok(()));
+
+    let price = crate::curve_price(1, 10).unwrap();
+    let passes_supply_account: PassesSupply = get_account(&mut ctx, passes_supply).await;
+    assert_eq!(passes_supply_account.amount, 11);
+    let passes_balance: PassesBalance = get_account(&mut ctx, buyer_balance).await;
+    assert_eq!(passes_balance.amount, 10);
+    assert_eq!(
+        get_token_account_balance(&mut ctx, escrow_wallet).await,
+        price
+    );
+    assert_eq!(get_token_account_balance(&mut ctx, default_escrow).await, 0);
+    assert_eq!(
+        get_token_account_balance(
+            &mut ctx,
+            anchor_spl::associated_token::get_associated_token_address(&partner.pubkey(), &mint)
+        )
+        .await,
+        crate::calc_fee(500, price).unwrap()
+    );
+
+    // a market can't be traded through another marketplace's config
+    let accounts = accounts::BuyPasses {
+        config: get_config_pda().0,
+        escrow_wallet: default_escrow,
+        reserve_wallet: None,
+        protocol_fee_wallet: anchor_spl::associated_token::get_associated_token_address(
+            &initializer.pubkey(),
+            &mint,
+        ),
+        ..accounts
+    };
+    let res = execute_tx(&mut ctx, accounts.to_account_metas(None), &args, &buyer).await;
+    assert!(res.is_err());
 }
 
 #[tokio::test]
-async fn test_set_fees_pct() {
-    let (mut ctx, initializer, _, _, mint) = setup().await;
-
-    // get pda
-    let (config_pda, _) = get_config_pda();
+async fn test_init_not_upgrade_authority() {
+    let (mut ctx, initializer, front_runner, _, mint) = setup().await;
 
-    init_passes(
+    // someone else than the upgrade authority tries to init first
+    let res = try_init_passes(
         &mut ctx,
-        &initializer,
+        &front_runner,
         &mint,
         PROTOCOL_FEE_BPS,
         OWNER_FEE_BPS,
     )
     .await;
+    assert_passes_error(res, PassesError::NotUpgradeAuthority);
 
-    // set protocol fee percent
-    let args = instruction::SetProtocolFeeBps { fee_bps: 11111111 };
-    let accounts = accounts::SetFeePercent {
-        admin: initializer.pubkey(),
+    let (config_pda, _) = get_config_pda();
+    let config = ctx.banks_client.get_account(config_pda).await.unwrap();
+    assert!(config.is_none());
+
+    // a fake program data account is rejected
+    let (escrow_token_wallet, _) = get_escrow_token_wallet_pda(&mint);
+    let (escrow_sol_wallet, _) = get_escrow_sol_wallet_pda();
+    let args = instruction::Init {
+        marketplace_id: 0,
+        protocol_fee_bps: PROTOCOL_FEE_BPS,
+        owner_fee_bps: OWNER_FEE_BPS,
+    };
+    let accounts = accounts::Init {
+        admin: front_runner.pubkey(),
+        authority: front_runner.pubkey(),
         config: config_pda,
+        escrow_token_wallet,
+        escrow_sol_wallet,
+        protocol_fee_wallet: anchor_spl::associated_token::get_associated_token_address(
+            &front_runner.pubkey(),
+            &mint,
+        ),
+        program: crate::id(),
+        program_data: front_runner.pubkey(),
+        payment_mint: mint,
         system_program: system_program::ID,
+        token_program: anchor_spl::token::ID,
+        associated_token_program: anchor_spl::associated_token::ID,
     };
     let res = execute_tx(
         &mut ctx,
         accounts.to_account_metas(None),
         &args,
-        &initializer,
+        &front_runner,
     )
     .await;
-    assert_matches!(res, Ok(()));
+    assert!(res.is_err());
 
-    // set owner fee percent
-    let args = instruction::SetOwnerFeeBps { fee_bps: 2222222 };
-    let accounts = accounts::SetFeePercent {
-        admin: initializer.pubkey(),
-        config: config_pda,
-        system_program: system_program::ID,
-    };
-    let res = execute_tx(
+    // the upgrade authority can still init
+    init_passes(
         &mut ctx,
-        accounts.to_account_metas(None),
-        &args,
         &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
     )
     .await;
-    assert_matches!(res, Ok(()));
 
-    // check protocol fee percent
     let config: Config = get_account(&mut ctx, config_pda).await;
-    assert_eq!(config.protocol_fee_bps, 11111111);
-    assert_eq!(config.owner_fee_bps, 2222222);
+    assert_eq!(config.admin, initializer.pubkey());
 }
 
-#[tokio::test]
-async fn test_set_fee_dst() {
-    let (mut ctx, initializer, _, _, mint) = setup().await;
+#[test]
+fn test_account_len() {
+    fn serialized_len<T: AccountDeserialize + AnchorSerialize>(len: usize) -> usize {
+        let data = vec![0u8; len];
+        let account = T::try_deserialize_unchecked(&mut data.as_slice()).unwrap();
+        8 + account.try_to_vec().unwrap().len()
+    }
 
-    // get pda
-    let (config_pda, _) = get_config_pda();
+    assert_eq!(serialized_len::<Config>(Config::LEN), Config::LEN);
+    assert_eq!(
+        serialized_len::<PassesSupply>(PassesSupply::LEN),
+        PassesSupply::LEN
+    );
+    assert_eq!(
+        serialized_len::<PassesBalance>(PassesBalance::LEN),
+        PassesBalance::LEN
+    );
+    assert_eq!(
+        serialized_len::<OwnerVesting>(OwnerVesting::LEN),
+        OwnerVesting::LEN
+    );
+    assert_eq!(
+        serialized_len::<RewardPool>(RewardPool::LEN),
+        RewardPool::LEN
+    );
+    assert_eq!(serialized_len::<StakePool>(StakePool::LEN), StakePool::LEN);
+    assert_eq!(
+        serialized_len::<PassesStake>(PassesStake::LEN),
+        PassesStake::LEN
+    );
+}
 
-    init_passes(
-        &mut ctx,
-        &initializer,
-        &mint,
-        PROTOCOL_FEE_BPS,
-        OWNER_FEE_BPS,
-    )
-    .await;
+#[tokio::test]
+async fn test_migrate_config() {
+    let (mut ctx, initializer, buyer, _, mint) = setup().await;
 
-    // set protocol fee dst
+    let (config_pda, config_bump) = get_config_pda();
+    let (escrow_token_wallet, _) = get_escrow_token_wallet_pda(&mint);
+    let (escrow_sol_wallet, _) = get_escrow_sol_wallet_pda();
     let protocol_fee_wallet =
         anchor_spl::associated_token::get_associated_token_address(&initializer.pubkey(), &mint);
-    let args = instruction::SetProtocolFeeDst {};
-    let accounts = accounts::SetProtocolFeeDst {
-        admin: initializer.pubkey(),
+    set_v0_account(
+        &mut ctx,
+        &config_pda,
+        Config::DISCRIMINATOR,
+        &state::v0::Config {
+            admin: initializer.pubkey(),
+            payment_mint: mint,
+            escrow_token_wallet,
+            escrow_sol_wallet,
+            protocol_fee_bps: PROTOCOL_FEE_BPS,
+            owner_fee_bps: OWNER_FEE_BPS,
+            protocol_fee_token_wallet: protocol_fee_wallet,
+            bump: config_bump,
+        },
+        state::v0::Config::LEN,
+    );
+
+    // only the admin can migrate the config
+    let args = instruction::MigrateConfig {};
+    let accounts = accounts::MigrateConfig {
+        admin: buyer.pubkey(),
         config: config_pda,
-        protocol_fee_wallet,
         system_program: system_program::ID,
     };
+    let res = execute_tx(&mut ctx, accounts.to_account_metas(None), &args, &buyer).await;
+    assert_passes_error(res, PassesError::NotAnAdmin);
 
+    let accounts = accounts::MigrateConfig {
+        admin: initializer.pubkey(),
+        config: config_pda,
+        system_program: system_program::ID,
+    };
     let res = execute_tx(
         &mut ctx,
         accounts.to_account_metas(None),
@@ -204,38 +407,41 @@ async fn test_set_fee_dst() {
     .await;
     assert_matches!(res, Ok(()));
 
-    // check protocol fee percent
+    let account = ctx
+        .banks_client
+        .get_account(config_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), Config::LEN);
+
     let config: Config = get_account(&mut ctx, config_pda).await;
+    assert_eq!(config.version, Config::VERSION);
+    assert_eq!(config.admin, initializer.pubkey());
     assert_eq!(config.payment_mint, mint);
+    assert_eq!(config.escrow_token_wallet, escrow_token_wallet);
+    assert_eq!(config.escrow_sol_wallet, escrow_sol_wallet);
+    assert_eq!(config.protocol_fee_bps, PROTOCOL_FEE_BPS);
+    assert_eq!(config.owner_fee_bps, OWNER_FEE_BPS);
     assert_eq!(config.protocol_fee_token_wallet, protocol_fee_wallet);
-}
-
-#[tokio::test]
-async fn test_issue_passes() {
-    let (mut ctx, initializer, buyer, owner, mint) = setup().await;
+    assert_eq!(config.protocol_fee_sol_wallet, initializer.pubkey());
+    assert_eq!(config.bump, config_bump);
+    assert!(!config.buys_paused);
 
-    init_passes(
+    // the config is already migrated
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let res = execute_tx(
         &mut ctx,
+        accounts.to_account_metas(None),
+        &args,
         &initializer,
-        &mint,
-        PROTOCOL_FEE_BPS,
-        OWNER_FEE_BPS,
     )
     .await;
-
-    issue_passes(&mut ctx, &owner, &mint).await;
-
-    let (passes_supply_pda, _) = get_passes_supply_pda(&owner.pubkey());
-    let passes_supply: PassesSupply = get_account(&mut ctx, passes_supply_pda).await;
-    assert_eq!(passes_supply.amount, 1);
-
-    let (passes_balance_pda, _) = get_passes_balance_pda(&owner.pubkey(), &owner.pubkey());
-    let passes_balance: PassesBalance = get_account(&mut ctx, passes_balance_pda).await;
-    assert_eq!(passes_balance.amount, 1);
+    assert_passes_error(res, PassesError::InvalidAccountVersion);
 }
 
 #[tokio::test]
-async fn test_buy_passes_w_usdc() {
+async fn test_migrate_supply_and_balance() {
     let (mut ctx, initializer, buyer, owner, mint) = setup().await;
 
     init_passes(
@@ -247,21 +453,99 @@ async fn test_buy_passes_w_usdc() {
     )
     .await;
 
-    issue_passes(&mut ctx, &owner, &mint).await;
+    // the owner issued passes and the buyer bought 10 with the old program
+    let (escrow_wallet, _) = get_escrow_token_wallet_pda(&mint);
+    ctx.mint_tokens(&initializer, &mint, &escrow_wallet, 2406250)
+        .await
+        .unwrap();
+    let (passes_supply_pda, supply_bump) = get_passes_supply_pda(&owner.pubkey());
+    let (owner_balance_pda, owner_balance_bump) =
+        get_passes_balance_pda(&owner.pubkey(), &owner.pubkey());
+    let (buyer_balance_pda, buyer_balance_bump) =
+        get_passes_balance_pda(&owner.pubkey(), &buyer.pubkey());
+    set_v0_account(
+        &mut ctx,
+        &passes_supply_pda,
+        PassesSupply::DISCRIMINATOR,
+        &state::v0::PassesSupply {
+            amount: 11,
+            bump: supply_bump,
+        },
+        state::v0::PassesSupply::LEN,
+    );
+    set_v0_account(
+        &mut ctx,
+        &owner_balance_pda,
+        PassesBalance::DISCRIMINATOR,
+        &state::v0::PassesBalance {
+            amount: 1,
+            bump: owner_balance_bump,
+        },
+        state::v0::PassesBalance::LEN,
+    );
+    set_v0_account(
+        &mut ctx,
+        &buyer_balance_pda,
+        PassesBalance::DISCRIMINATOR,
+        &state::v0::PassesBalance {
+            amount: 10,
+            bump: buyer_balance_bump,
+        },
+        state::v0::PassesBalance::LEN,
+    );
 
-    // buyer buy passes
-    buy_passes(
+    // old accounts cannot be traded before the migration
+    let res = try_sell_passes(
         &mut ctx,
         &buyer,
         &owner.pubkey(),
         &initializer.pubkey(),
         &mint,
-        10,
+        5,
     )
     .await;
+    assert!(res.is_err());
+
+    // anyone can pay for the migration
+    let args = instruction::MigrateSupply {};
+    let accounts = accounts::MigrateSupply {
+        payer: buyer.pubkey(),
+        passes_supply: passes_supply_pda,
+        passes_owner: owner.pubkey(),
+        system_program: system_program::ID,
+    };
+    let res = execute_tx(&mut ctx, accounts.to_account_metas(None), &args, &buyer).await;
+    assert_matches!(res, Ok(()));
 
-    // buyer buy passes second time
-    buy_passes(
+    for holder in [owner.pubkey(), buyer.pubkey()] {
+        let args = instruction::MigrateBalance {};
+        let accounts = accounts::MigrateBalance {
+            payer: buyer.pubkey(),
+            config: get_config_pda().0,
+            passes_balance: get_passes_balance_pda(&owner.pubkey(), &holder).0,
+            passes_owner: owner.pubkey(),
+            holder,
+            system_program: system_program::ID,
+        };
+        let res = execute_tx(&mut ctx, accounts.to_account_metas(None), &args, &buyer).await;
+        assert_matches!(res, Ok(()));
+    }
+
+    let passes_supply: PassesSupply = get_account(&mut ctx, passes_supply_pda).await;
+    assert_eq!(passes_supply.version, PassesSupply::VERSION);
+    assert_eq!(passes_supply.amount, 11);
+    assert_eq!(passes_supply.bump, supply_bump);
+    assert!(!passes_supply.paused);
+    assert_eq!(passes_supply.stats.ath_supply, 11);
+    assert_eq!(passes_supply.stats.trade_count, 0);
+
+    let passes_balance: PassesBalance = get_account(&mut ctx, buyer_balance_pda).await;
+    assert_eq!(passes_balance.version, PassesBalance::VERSION);
+    assert_eq!(passes_balance.amount, 10);
+    assert_eq!(passes_balance.bump, buyer_balance_bump);
+
+    // migrated accounts trade as usual
+    sell_passes(
         &mut ctx,
         &buyer,
         &owner.pubkey(),
@@ -271,45 +555,12 @@ async fn test_buy_passes_w_usdc() {
     )
     .await;
 
-    // check escrow fund
-    let (escrow_wallet, _) = get_escrow_token_wallet_pda(&mint);
-    let account = get_token_account(&mut ctx, escrow_wallet).await;
-    assert_eq!(account.amount, 2406250 + 756250);
-
-    // check protocol fee wallet
-    let protocol_fee_wallet =
-        anchor_spl::associated_token::get_associated_token_address(&initializer.pubkey(), &mint);
-    let account = get_token_account(&mut ctx, protocol_fee_wallet).await;
-    assert_eq!(account.amount, 24063 + 7563);
-
-    // check owner fee wallet
-    let owner_fee_wallet =
-        anchor_spl::associated_token::get_associated_token_address(&owner.pubkey(), &mint);
-    let account = get_token_account(&mut ctx, owner_fee_wallet).await;
-    assert_eq!(account.amount, 24063 + 7563);
-
-    // check total owner's pass supply
-    let (passes_supply_pda, _) = get_passes_supply_pda(&owner.pubkey());
     let passes_supply: PassesSupply = get_account(&mut ctx, passes_supply_pda).await;
-    assert_eq!(passes_supply.amount, 12);
-
-    // check byuer's pass balance of owner's pass
-    let (passes_balance_pda, _) = get_passes_balance_pda(&owner.pubkey(), &buyer.pubkey());
-    let passes_balance: PassesBalance = get_account(&mut ctx, passes_balance_pda).await;
-    assert_eq!(passes_balance.amount, 11);
-
-    // check buyer balance after purchase and ...
-    let buyer_wallet =
-        anchor_spl::associated_token::get_associated_token_address(&buyer.pubkey(), &mint);
-    let account = get_token_account(&mut ctx, buyer_wallet).await;
-    assert_eq!(
-        account.amount,
-        5_u64 * ONE_USDC - 2406250 - 24063 - 24063 - 756250 - 7563 - 7563
-    );
+    assert_eq!(passes_supply.amount, 10);
 }
 
 #[tokio::test]
-async fn test_sell_passes_w_usdc() {
+async fn test_migrate_balance_v1() {
     let (mut ctx, initializer, buyer, owner, mint) = setup().await;
 
     init_passes(
@@ -320,10 +571,7 @@ async fn test_sell_passes_w_usdc() {
         OWNER_FEE_BPS,
     )
     .await;
-
     issue_passes(&mut ctx, &owner, &mint).await;
-
-    // buyer buy next passes
     buy_passes(
         &mut ctx,
         &buyer,
@@ -334,59 +582,3103 @@ async fn test_sell_passes_w_usdc() {
     )
     .await;
 
-    // buyer sell part of their passes
-    sell_passes(
+    // the buyer's balance was written by the version 1 program, without the reserved space
+    let (buyer_balance_pda, _) = get_passes_balance_pda(&owner.pubkey(), &buyer.pubkey());
+    let passes_balance: PassesBalance = get_account(&mut ctx, buyer_balance_pda).await;
+    set_v0_account(
+        &mut ctx,
+        &buyer_balance_pda,
+        PassesBalance::DISCRIMINATOR,
+        &PassesBalance {
+            version: 1,
+            staked_amount: 2,
+            ..passes_balance
+        },
+        PassesBalance::V1_LEN,
+    );
+
+    // version 1 balances can't trade until they are migrated
+    let res = try_sell_passes(
         &mut ctx,
         &buyer,
         &owner.pubkey(),
         &initializer.pubkey(),
         &mint,
-        5,
+        1,
+    )
+    .await;
+    assert!(res.is_err());
+    let res = try_buy_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        1,
+    )
+    .await;
+    assert!(res.is_err());
+
+    let args = instruction::MigrateBalance {};
+    let accounts = accounts::MigrateBalance {
+        payer: buyer.pubkey(),
+        config: get_config_pda().0,
+        passes_balance: buyer_balance_pda,
+        passes_owner: owner.pubkey(),
+        holder: buyer.pubkey(),
+        system_program: system_program::ID,
+    };
+    let res = execute_tx(&mut ctx, accounts.to_account_metas(None), &args, &buyer).await;
+    assert_matches!(res, Ok(()));
+
+    let account = ctx
+        .banks_client
+        .get_account(buyer_balance_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), PassesBalance::LEN);
+    let passes_balance: PassesBalance = get_account(&mut ctx, buyer_balance_pda).await;
+    assert_eq!(passes_balance.version, PassesBalance::VERSION);
+    assert_eq!(passes_balance.amount, 10);
+    assert_eq!(passes_balance.staked_amount, 2);
+    assert_eq!(passes_balance.reserved, [0; 32]);
+
+    // migrated balances trade as usual
+    ctx.get_new_latest_blockhash().await.unwrap();
+    sell_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        1,
+    )
+    .await;
+
+    // the balance is already migrated
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let res = execute_tx(&mut ctx, accounts.to_account_metas(None), &args, &buyer).await;
+    assert_passes_error(res, PassesError::InvalidAccountVersion);
+
+    // balances of other marketplaces are found under their marketplace seed
+    let res = try_init_marketplace(
+        &mut ctx,
+        &initializer,
+        &initializer,
+        1,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+    assert_matches!(res, Ok(()));
+    let (partner_balance_pda, partner_balance_bump) =
+        get_marketplace_pda(1, b"balance", &[&owner.pubkey(), &buyer.pubkey()]);
+    let passes_balance: PassesBalance = get_account(&mut ctx, buyer_balance_pda).await;
+    set_v0_account(
+        &mut ctx,
+        &partner_balance_pda,
+        PassesBalance::DISCRIMINATOR,
+        &PassesBalance {
+            version: 1,
+            amount: 3,
+            bump: partner_balance_bump,
+            ..passes_balance
+        },
+        PassesBalance::V1_LEN,
+    );
+
+    // under their marketplace's config only
+    let accounts = accounts::MigrateBalance {
+        passes_balance: partner_balance_pda,
+        ..accounts
+    };
+    let res = execute_tx(&mut ctx, accounts.to_account_metas(None), &args, &buyer).await;
+    assert!(res.is_err());
+
+    let accounts = accounts::MigrateBalance {
+        config: get_marketplace_pda(1, b"config", &[]).0,
+        ..accounts
+    };
+    let res = execute_tx(&mut ctx, accounts.to_account_metas(None), &args, &buyer).await;
+    assert_matches!(res, Ok(()));
+    let passes_balance: PassesBalance = get_account(&mut ctx, partner_balance_pda).await;
+    assert_eq!(passes_balance.version, PassesBalance::VERSION);
+    assert_eq!(passes_balance.amount, 3);
+}
+
+#[tokio::test]
+async fn test_migrate_supply_v1() {
+    let (mut ctx, initializer, buyer, owner, mint) = setup().await;
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+
+    issue_passes(&mut ctx, &owner, &mint).await;
+    buy_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        5,
+    )
+    .await;
+
+    // shrink the supply back to the version 1 layout, the stats were its reserved space
+    let (passes_supply_pda, _) = get_passes_supply_pda(&owner.pubkey());
+    let mut passes_supply: PassesSupply = get_account(&mut ctx, passes_supply_pda).await;
+    passes_supply.version = 1;
+    passes_supply.stats = TradeStats::default();
+    set_v0_account(
+        &mut ctx,
+        &passes_supply_pda,
+        PassesSupply::DISCRIMINATOR,
+        &passes_supply,
+        PassesSupply::V1_LEN,
+    );
+
+    // the market is frozen until its supply is migrated
+    let res = try_buy_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        1,
+    )
+    .await;
+    assert!(res.is_err());
+    let res = try_sell_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        1,
+    )
+    .await;
+    assert!(res.is_err());
+
+    let args = instruction::MigrateSupply {};
+    let accounts = accounts::MigrateSupply {
+        payer: buyer.pubkey(),
+        passes_supply: passes_supply_pda,
+        passes_owner: owner.pubkey(),
+        system_program: system_program::ID,
+    };
+    let res = execute_tx(&mut ctx, accounts.to_account_metas(None), &args, &buyer).await;
+    assert_matches!(res, Ok(()));
+
+    let migrated: PassesSupply = get_account(&mut ctx, passes_supply_pda).await;
+    assert_eq!(migrated.version, PassesSupply::VERSION);
+    assert_eq!(migrated.amount, 6);
+    assert_eq!(migrated.bump, passes_supply.bump);
+    assert_eq!(migrated.stats.ath_supply, 6);
+    assert_eq!(migrated.stats.trade_count, 0);
+
+    // and trades again afterwards
+    ctx.get_new_latest_blockhash().await.unwrap();
+    sell_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        1,
+    )
+    .await;
+    buy_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        1,
+    )
+    .await;
+    let migrated: PassesSupply = get_account(&mut ctx, passes_supply_pda).await;
+    assert_eq!(migrated.amount, 6);
+    assert_eq!(migrated.stats.trade_count, 2);
+    assert!(!migrated.stats_complete);
+
+    // the stats miss the trades before the migration, so the reserve can't be isolated
+    let res = try_isolate_reserve(&mut ctx, &initializer, &owner.pubkey(), &mint, None).await;
+    assert_passes_error(res, PassesError::IncompleteTradeStats);
+
+    // current accounts cannot be migrated again
+    let accounts = accounts::MigrateSupply {
+        payer: owner.pubkey(),
+        ..accounts
+    };
+    let res = execute_tx(&mut ctx, accounts.to_account_metas(None), &args, &owner).await;
+    assert_passes_error(res, PassesError::InvalidAccountVersion);
+}
+
+#[tokio::test]
+async fn test_get_price() {
+    let (mut ctx, initializer, _, _, _) = setup().await;
+
+    // calc and get price for token (e.g. usdc)
+    let args = instruction::GetPrice {
+        supply: 3,
+        amount: 1,
+    };
+    let accounts = accounts::GetPrice {
+        invoker: initializer.pubkey(),
+    };
+    let res = simulate_tx(
+        &mut ctx,
+        accounts.to_account_metas(None),
+        &args,
+        &initializer,
+    )
+    .await;
+    assert_matches!(res, Ok(56250_u64));
+
+    // calc and get price for sol
+    let args = instruction::GetPriceSol {
+        supply: 3,
+        amount: 1,
+    };
+    let accounts = accounts::GetPrice {
+        invoker: initializer.pubkey(),
+    };
+    let res = simulate_tx(
+        &mut ctx,
+        accounts.to_account_metas(None),
+        &args,
+        &initializer,
+    )
+    .await;
+    assert_matches!(res, Ok(5625000_u64));
+}
+
+// Supplies and amounts the SOL curve prices without overflowing
+const CURVE_MAX: u64 = 1_000;
+
+proptest! {
+    #[test]
+    fn prop_curve_price(supply in 1..=CURVE_MAX, a in 1..=CURVE_MAX, b in 1..=CURVE_MAX) {
+        // buying in two steps costs the same as in one
+        prop_assert_eq!(
+            crate::curve_price(supply, a + b).unwrap(),
+            crate::curve_price(supply, a).unwrap() + crate::curve_price(supply + a, b).unwrap()
+        );
+        prop_assert_eq!(
+            crate::curve_price_sol(supply, a + b).unwrap(),
+            crate::curve_price_sol(supply, a).unwrap() + crate::curve_price_sol(supply + a, b).unwrap()
+        );
+
+        // more passes and a higher supply cost more
+        prop_assert!(crate::curve_price(supply, a + b).unwrap() > crate::curve_price(supply, a).unwrap());
+        prop_assert!(crate::curve_price(supply + b, a).unwrap() > crate::curve_price(supply, a).unwrap());
+        prop_assert!(crate::curve_price_sol(supply, a + b).unwrap() > crate::curve_price_sol(supply, a).unwrap());
+        prop_assert!(crate::curve_price_sol(supply + b, a).unwrap() > crate::curve_price_sol(supply, a).unwrap());
+
+        // the curve divides exactly, prices are whole multiples of a curve unit
+        prop_assert_eq!(crate::curve_price(supply, a).unwrap() % (ONE_USDC / 160), 0);
+        prop_assert_eq!(crate::curve_price_sol(supply, a).unwrap() % (ONE_SOL / 1600), 0);
+    }
+
+    #[test]
+    fn prop_round_trips(
+        price_offset in 0..=10u64,
+        trades in proptest::collection::vec((any::<bool>(), 1..=20u64), 1..100)
+    ) {
+        // replay buys and sells on both curves, the reserve collects what buys pay and pays
+        // what sells receive
+        let mut supply = 1;
+        let mut token_reserve = 0u64;
+        let mut sol_reserve = 0u64;
+        for (buy, amount) in trades {
+            let curve_supply = supply + price_offset;
+            if buy {
+                token_reserve += crate::curve_price(curve_supply, amount).unwrap();
+                sol_reserve += crate::curve_price_sol(curve_supply, amount).unwrap();
+                supply += amount;
+            } else {
+                // the last pass can't be sold
+                let amount = amount.min(supply - 1);
+                let curve_supply = curve_supply - amount;
+                token_reserve = token_reserve
+                    .checked_sub(crate::curve_price(curve_supply, amount).unwrap())
+                    .unwrap();
+                sol_reserve = sol_reserve
+                    .checked_sub(crate::curve_price_sol(curve_supply, amount).unwrap())
+                    .unwrap();
+                supply -= amount;
+            }
+
+            // prices are exact, the reserve is always the buyback price of every sellable pass
+            let buyback_supply = 1 + price_offset;
+            prop_assert_eq!(token_reserve, crate::curve_price(buyback_supply, supply - 1).unwrap());
+            prop_assert_eq!(sol_reserve, crate::curve_price_sol(buyback_supply, supply - 1).unwrap());
+        }
+    }
+
+    #[test]
+    fn prop_calc_fee(fee_bps in 0..=10_000u64, amount in 0..=u64::MAX / 2, more in 0..=u64::MAX / 2) {
+        let fee = crate::calc_fee(fee_bps, amount).unwrap();
+
+        // fees round up, by less than one unit
+        let exact = amount as u128 * fee_bps as u128;
+        prop_assert!(fee as u128 * crate::math::BPS_POWER >= exact);
+        prop_assert!(fee == 0 || (fee as u128 - 1) * crate::math::BPS_POWER < exact);
+        prop_assert!(fee <= amount);
+
+        // larger amounts and fees never pay less
+        prop_assert!(crate::calc_fee(fee_bps, amount + more).unwrap() >= fee);
+        if fee_bps < 10_000 {
+            prop_assert!(crate::calc_fee(fee_bps + 1, amount).unwrap() >= fee);
+        }
+    }
+}
+
+#[test]
+fn test_curve_price_overflow() {
+    // the units are scaled in u128, prices past u64 only fail once they don't fit
+    assert_eq!(
+        crate::curve_price_sol(200_000, 1).unwrap(),
+        200_000u64.pow(2) * (ONE_SOL / 1600)
+    );
+    assert_eq!(
+        crate::curve_price(1 << 40, 1),
+        Err(PassesError::MathOverflow.into())
+    );
+    assert_eq!(
+        crate::curve_price_sol(u64::MAX, 1),
+        Err(PassesError::MathOverflow.into())
+    );
+    assert_eq!(
+        crate::calc_price(u64::MAX / 2, u64::MAX / 2),
+        Err(PassesError::MathOverflow.into())
+    );
+}
+
+#[tokio::test]
+async fn test_set_fees_pct() {
+    let (mut ctx, initializer, _, _, mint) = setup().await;
+
+    // get pda
+    let (config_pda, _) = get_config_pda();
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+
+    // fees above the whole price are rejected
+    let accounts = accounts::SetFeePercent {
+        admin: initializer.pubkey(),
+        config: config_pda,
+        system_program: system_program::ID,
+    };
+    let args = instruction::SetProtocolFeeBps { fee_bps: 11111111 };
+    let res = execute_tx(
+        &mut ctx,
+        accounts.to_account_metas(None),
+        &args,
+        &initializer,
+    )
+    .await;
+    assert_passes_error(res, PassesError::InvalidFee);
+
+    let args = instruction::SetOwnerFeeBps {
+        fee_bps: 10_000 - PROTOCOL_FEE_BPS + 1,
+    };
+    let res = execute_tx(
+        &mut ctx,
+        accounts.to_account_metas(None),
+        &args,
+        &initializer,
+    )
+    .await;
+    assert_passes_error(res, PassesError::InvalidFee);
+
+    // set protocol fee percent
+    let args = instruction::SetProtocolFeeBps { fee_bps: 1111 };
+    let res = execute_tx(
+        &mut ctx,
+        accounts.to_account_metas(None),
+        &args,
+        &initializer,
+    )
+    .await;
+    assert_matches!(res, Ok(()));
+
+    // set owner fee percent
+    let args = instruction::SetOwnerFeeBps { fee_bps: 2222 };
+    let res = execute_tx(
+        &mut ctx,
+        accounts.to_account_metas(None),
+        &args,
+        &initializer,
+    )
+    .await;
+    assert_matches!(res, Ok(()));
+
+    // check protocol fee percent
+    let config: Config = get_account(&mut ctx, config_pda).await;
+    assert_eq!(config.protocol_fee_bps, 1111);
+    assert_eq!(config.owner_fee_bps, 2222);
+}
+
+#[tokio::test]
+async fn test_set_fee_dst() {
+    let (mut ctx, initializer, _, _, mint) = setup().await;
+
+    // get pda
+    let (config_pda, _) = get_config_pda();
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+
+    // set protocol fee dst
+    let protocol_fee_wallet =
+        anchor_spl::associated_token::get_associated_token_address(&initializer.pubkey(), &mint);
+    let args = instruction::SetProtocolFeeDst {};
+    let accounts = accounts::SetProtocolFeeDst {
+        admin: initializer.pubkey(),
+        config: config_pda,
+        protocol_fee_wallet,
+        system_program: system_program::ID,
+    };
+
+    let res = execute_tx(
+        &mut ctx,
+        accounts.to_account_metas(None),
+        &args,
+        &initializer,
+    )
+    .await;
+    assert_matches!(res, Ok(()));
+
+    // check protocol fee percent
+    let config: Config = get_account(&mut ctx, config_pda).await;
+    assert_eq!(config.payment_mint, mint);
+    assert_eq!(config.protocol_fee_token_wallet, protocol_fee_wallet);
+}
+
+#[tokio::test]
+async fn test_issue_passes() {
+    let (mut ctx, initializer, buyer, owner, mint) = setup().await;
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+
+    issue_passes(&mut ctx, &owner, &mint).await;
+
+    let (passes_supply_pda, _) = get_passes_supply_pda(&owner.pubkey());
+    let passes_supply: PassesSupply = get_account(&mut ctx, passes_supply_pda).await;
+    assert_eq!(passes_supply.amount, 1);
+
+    let (passes_balance_pda, _) = get_passes_balance_pda(&owner.pubkey(), &owner.pubkey());
+    let passes_balance: PassesBalance = get_account(&mut ctx, passes_balance_pda).await;
+    assert_eq!(passes_balance.amount, 1);
+
+    let (passes_metadata_pda, _) = get_passes_metadata_pda(&owner.pubkey());
+    let passes_metadata: PassesMetadata = get_account(&mut ctx, passes_metadata_pda).await;
+    assert_eq!(passes_metadata.owner, owner.pubkey());
+    assert_eq!(passes_metadata.name, "");
+}
+
+#[tokio::test]
+async fn test_update_passes_metadata() {
+    let (mut ctx, initializer, buyer, owner, mint) = setup().await;
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+
+    // only issued markets have metadata
+    let res = try_update_passes_metadata(&mut ctx, &owner, "name", "", "", "").await;
+    assert!(res.is_err());
+
+    issue_passes(&mut ctx, &owner, &mint).await;
+
+    let events = try_update_passes_metadata(
+        &mut ctx,
+        &owner,
+        "Passes",
+        "https://example.com/passes.json",
+        "https://example.com/passes.png",
+        "@passes",
+    )
+    .await
+    .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].owner, owner.pubkey());
+    assert_eq!(events[0].name, "Passes");
+    assert_eq!(events[0].social_handle, "@passes");
+
+    let (passes_metadata_pda, _) = get_passes_metadata_pda(&owner.pubkey());
+    let passes_metadata: PassesMetadata = get_account(&mut ctx, passes_metadata_pda).await;
+    assert_eq!(passes_metadata.name, "Passes");
+    assert_eq!(
+        passes_metadata.description_uri,
+        "https://example.com/passes.json"
+    );
+    assert_eq!(passes_metadata.image_uri, "https://example.com/passes.png");
+    assert_eq!(passes_metadata.social_handle, "@passes");
+
+    // fields longer than the limits are rejected
+    let long_name = "a".repeat(PassesMetadata::MAX_NAME_LEN + 1);
+    let res = try_update_passes_metadata(&mut ctx, &owner, &long_name, "", "", "").await;
+    assert_passes_error(res.map(|_| ()), PassesError::MetadataTooLong);
+
+    let long_uri = "a".repeat(PassesMetadata::MAX_URI_LEN + 1);
+    let res = try_update_passes_metadata(&mut ctx, &owner, "", "", &long_uri, "").await;
+    assert_passes_error(res.map(|_| ()), PassesError::MetadataTooLong);
+
+    // fields at the limits fit into the account
+    let max_uri = "a".repeat(PassesMetadata::MAX_URI_LEN);
+    let max_handle = "a".repeat(PassesMetadata::MAX_SOCIAL_HANDLE_LEN);
+    let max_name = "a".repeat(PassesMetadata::MAX_NAME_LEN);
+    try_update_passes_metadata(&mut ctx, &owner, &max_name, &max_uri, &max_uri, &max_handle)
+        .await
+        .unwrap();
+
+    // a non-owner has no market to describe
+    let res = try_update_passes_metadata(&mut ctx, &buyer, "name", "", "", "").await;
+    assert!(res.is_err());
+}
+
+#[tokio::test]
+async fn test_issuance_params() {
+    let (mut ctx, initializer, buyer, owner, mint) = setup().await;
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+
+    // the price offset is capped
+    let params = IssuanceParams {
+        price_offset: PassesSupply::MAX_PRICE_OFFSET + 1,
+        ..Default::default()
+    };
+    let res = try_issue_passes_with_params(&mut ctx, &owner, &mint, 1, params).await;
+    assert_passes_error(res, PassesError::InvalidIssuanceParams);
+
+    let launch_ts = get_unix_timestamp(&mut ctx).await + 3600;
+    let params = IssuanceParams {
+        max_supply: 3,
+        launch_ts,
+        price_offset: 10,
+        ..Default::default()
+    };
+    try_issue_passes_with_params(&mut ctx, &owner, &mint, 1, params.clone())
+        .await
+        .unwrap();
+
+    let (passes_supply_pda, _) = get_passes_supply_pda(&owner.pubkey());
+    let passes_supply: PassesSupply = get_account(&mut ctx, passes_supply_pda).await;
+    assert_eq!(passes_supply.max_supply, params.max_supply);
+    assert_eq!(passes_supply.launch_ts, params.launch_ts);
+    assert_eq!(passes_supply.price_offset, params.price_offset);
+
+    // buys fail before the launch
+    let res = try_buy_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        1,
+    )
+    .await;
+    assert_passes_error(res, PassesError::NotLaunched);
+    let res = try_buy_passes_sol(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        1,
+    )
+    .await;
+    assert_passes_error(res, PassesError::NotLaunched);
+
+    set_unix_timestamp(&mut ctx, launch_ts).await;
+
+    // the supply can't go over the cap
+    let res = try_buy_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        3,
+    )
+    .await;
+    assert_passes_error(res, PassesError::MaxSupplyExceeded);
+
+    // passes 2 and 3 are priced at curve positions 11 and 12
+    buy_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        2,
+    )
+    .await;
+
+    let (escrow_wallet, _) = get_escrow_token_wallet_pda(&mint);
+    let account = get_token_account(&mut ctx, escrow_wallet).await;
+    assert_eq!(account.amount, (121 + 144) * ONE_USDC / 160);
+
+    // sells use the same offset, so the escrow keeps the price of the remaining pass
+    sell_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        1,
+    )
+    .await;
+
+    let account = get_token_account(&mut ctx, escrow_wallet).await;
+    assert_eq!(account.amount, 121 * ONE_USDC / 160);
+}
+
+#[test]
+fn test_merkle_proofs() {
+    for size in 1..=9 {
+        let wallets: Vec<Pubkey> = (0..size).map(|_| Pubkey::new_unique()).collect();
+        let (root, proofs) = merkle_tree(&wallets);
+
+        for (wallet, proof) in wallets.iter().zip(proofs.iter()) {
+            assert!(merkle::verify(proof, &root, merkle::leaf(wallet)));
+            // the proof doesn't work for another wallet or another root
+            assert!(!merkle::verify(
+                proof,
+                &root,
+                merkle::leaf(&Pubkey::new_unique())
+            ));
+            assert!(!merkle::verify(proof, &[0; 32], merkle::leaf(wallet)));
+        }
+    }
+
+    // a node can't be presented as a leaf
+    let wallets: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+    let (root, proofs) = merkle_tree(&wallets);
+    let node = merkle::hash_pair(&merkle::leaf(&wallets[0]), &proofs[0][0]);
+    assert!(merkle::verify(&proofs[0][1..], &root, node));
+    assert_ne!(merkle::leaf(&wallets[0]), node);
+}
+
+#[tokio::test]
+async fn test_presale() {
+    let (mut ctx, initializer, buyer, owner, mint) = setup().await;
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+
+    let allowlist: Vec<Pubkey> = vec![Pubkey::new_unique(), buyer.pubkey(), Pubkey::new_unique()];
+    let (root, proofs) = merkle_tree(&allowlist);
+    let buyer_proof = proofs[1].clone();
+
+    let now = get_unix_timestamp(&mut ctx).await;
+    let params = IssuanceParams {
+        launch_ts: now,
+        presale_merkle_root: root,
+        presale_end_ts: now + 3600,
+        presale_wallet_cap: 2,
+        ..Default::default()
+    };
+
+    // a presale needs a wallet cap
+    let res = try_issue_passes_with_params(
+        &mut ctx,
+        &owner,
+        &mint,
+        1,
+        IssuanceParams {
+            presale_wallet_cap: 0,
+            ..params.clone()
+        },
+    )
+    .await;
+    assert_passes_error(res, PassesError::InvalidIssuanceParams);
+
+    try_issue_passes_with_params(&mut ctx, &owner, &mint, 1, params)
+        .await
+        .unwrap();
+
+    // wallets off the allowlist can't buy during the presale
+    let res = try_buy_passes_sol(
+        &mut ctx,
+        &owner,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        1,
+    )
+    .await;
+    assert_passes_error(res, PassesError::NotAllowlisted);
+
+    // nor with someone else's proof
+    let res = try_buy_passes_sol_with_proof(
+        &mut ctx,
+        &owner,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        1,
+        buyer_proof.clone(),
+    )
+    .await;
+    assert_passes_error(res, PassesError::NotAllowlisted);
+
+    // an allowlisted buyer needs the proof
+    let res = try_buy_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        1,
+    )
+    .await;
+    assert_passes_error(res, PassesError::NotAllowlisted);
+
+    try_buy_passes_with_proof(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        2,
+        buyer_proof.clone(),
+    )
+    .await
+    .unwrap();
+
+    // and can't go over the wallet cap
+    let res = try_buy_passes_with_proof(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        1,
+        buyer_proof.clone(),
+    )
+    .await;
+    assert_passes_error(res, PassesError::PresaleCapExceeded);
+
+    // nor around it through a holder page
+    create_holder_page(&mut ctx, &owner, &owner.pubkey(), 0)
+        .await
+        .unwrap();
+    let into_page = accounts::BuyPasses {
+        passes_balance: None,
+        holder_page: Some(get_holder_page_pda(&owner.pubkey(), 0).0),
+        ..buy_passes_accounts(
+            &mut ctx,
+            &buyer.pubkey(),
+            &owner.pubkey(),
+            &initializer.pubkey(),
+            &mint,
+        )
+        .await
+    };
+    let args = instruction::BuyPasses {
+        amount: 1,
+        proof: buyer_proof,
+    };
+    let res = execute_tx(&mut ctx, into_page.to_account_metas(None), &args, &buyer).await;
+    assert_passes_error(res, PassesError::HolderPageCapped);
+
+    // after the presale anyone can buy without a proof, in the market's currency
+    set_unix_timestamp(&mut ctx, now + 3600).await;
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let owner_wallet =
+        anchor_spl::associated_token::get_associated_token_address(&owner.pubkey(), &mint);
+    ctx.mint_tokens(&initializer, &mint, &owner_wallet, ONE_USDC)
+        .await
+        .unwrap();
+    buy_passes(
+        &mut ctx,
+        &owner,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        1,
+    )
+    .await;
+    buy_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        1,
+    )
+    .await;
+
+    let (passes_supply_pda, _) = get_passes_supply_pda(&owner.pubkey());
+    let passes_supply: PassesSupply = get_account(&mut ctx, passes_supply_pda).await;
+    assert_eq!(passes_supply.amount, 5);
+}
+
+#[tokio::test]
+async fn test_owner_vesting() {
+    let (mut ctx, initializer, buyer, owner, mint) = setup().await;
+    let admin = initializer.pubkey();
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+
+    // the 10 issued passes vest over 1000 seconds
+    let now = get_unix_timestamp(&mut ctx).await;
+    let params = IssuanceParams {
+        vesting_duration: 1000,
+        ..Default::default()
+    };
+    try_issue_passes_with_params(&mut ctx, &owner, &mint, 10, params)
+        .await
+        .unwrap();
+
+    let (owner_vesting_pda, _) = get_owner_vesting_pda(&owner.pubkey());
+    let owner_vesting: OwnerVesting = get_account(&mut ctx, owner_vesting_pda).await;
+    assert_eq!(owner_vesting.amount, 10);
+    assert_eq!(owner_vesting.start_ts, now);
+    assert_eq!(owner_vesting.end_ts, now + 1000);
+
+    buy_passes_sol(&mut ctx, &buyer, &owner.pubkey(), &admin, &mint, 3).await;
+
+    // nothing is vested yet
+    let res = try_sell_passes_sol(&mut ctx, &owner, &owner.pubkey(), &admin, &mint, 1).await;
+    assert_passes_error(res, PassesError::PassesVesting);
+
+    // the owner can't skip the vesting account
+    let args = instruction::SellPassesSol { amount: 1 };
+    let accounts = accounts::SellPassesSol {
+        seller: owner.pubkey(),
+        passes_supply: get_passes_supply_pda(&owner.pubkey()).0,
+        passes_balance: get_passes_balance_pda(&owner.pubkey(), &owner.pubkey()).0,
+        owner_vesting: None,
+        reward_pool: None,
+        config: get_config_pda().0,
+        escrow_wallet: get_escrow_sol_wallet_pda().0,
+        reserve_wallet: None,
+        passes_owner: owner.pubkey(),
+        protocol_fee_wallet: admin,
+        system_program: system_program::ID,
+    };
+    let res = execute_tx(&mut ctx, accounts.to_account_metas(None), &args, &owner).await;
+    assert_passes_error(res, PassesError::VestingAccountMissing);
+
+    // buyers are not affected
+    sell_passes_sol(&mut ctx, &buyer, &owner.pubkey(), &admin, &mint, 1).await;
+
+    // vested passes are still issued ones, which the reserve doesn't back
+    set_unix_timestamp(&mut ctx, now + 1000).await;
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let res = try_sell_passes_sol(&mut ctx, &owner, &owner.pubkey(), &admin, &mint, 1).await;
+    assert_passes_error(res, PassesError::IssuedPassesLocked);
+
+    // the owner sells the passes they bought
+    buy_passes_sol(&mut ctx, &owner, &owner.pubkey(), &admin, &mint, 1).await;
+    ctx.get_new_latest_blockhash().await.unwrap();
+    sell_passes_sol(&mut ctx, &owner, &owner.pubkey(), &admin, &mint, 1).await;
+
+    let (passes_balance_pda, _) = get_passes_balance_pda(&owner.pubkey(), &owner.pubkey());
+    let passes_balance: PassesBalance = get_account(&mut ctx, passes_balance_pda).await;
+    assert_eq!(passes_balance.amount, 10);
+}
+
+#[tokio::test]
+async fn test_buy_passes_w_usdc() {
+    let (mut ctx, initializer, buyer, owner, mint) = setup().await;
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+
+    issue_passes(&mut ctx, &owner, &mint).await;
+
+    // buyer buy passes
+    buy_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        10,
+    )
+    .await;
+
+    // buyer buy passes second time
+    buy_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        1,
+    )
+    .await;
+
+    // check escrow fund
+    let (escrow_wallet, _) = get_escrow_token_wallet_pda(&mint);
+    let account = get_token_account(&mut ctx, escrow_wallet).await;
+    assert_eq!(account.amount, 2406250 + 756250);
+
+    // check protocol fee wallet
+    let protocol_fee_wallet =
+        anchor_spl::associated_token::get_associated_token_address(&initializer.pubkey(), &mint);
+    let account = get_token_account(&mut ctx, protocol_fee_wallet).await;
+    assert_eq!(account.amount, 24063 + 7563);
+
+    // check owner fee wallet
+    let owner_fee_wallet =
+        anchor_spl::associated_token::get_associated_token_address(&owner.pubkey(), &mint);
+    let account = get_token_account(&mut ctx, owner_fee_wallet).await;
+    assert_eq!(account.amount, 24063 + 7563);
+
+    // check total owner's pass supply
+    let (passes_supply_pda, _) = get_passes_supply_pda(&owner.pubkey());
+    let passes_supply: PassesSupply = get_account(&mut ctx, passes_supply_pda).await;
+    assert_eq!(passes_supply.amount, 12);
+
+    // check byuer's pass balance of owner's pass
+    let (passes_balance_pda, _) = get_passes_balance_pda(&owner.pubkey(), &buyer.pubkey());
+    let passes_balance: PassesBalance = get_account(&mut ctx, passes_balance_pda).await;
+    assert_eq!(passes_balance.amount, 11);
+
+    // check buyer balance after purchase and ...
+    let buyer_wallet =
+        anchor_spl::associated_token::get_associated_token_address(&buyer.pubkey(), &mint);
+    let account = get_token_account(&mut ctx, buyer_wallet).await;
+    assert_eq!(
+        account.amount,
+        5_u64 * ONE_USDC - 2406250 - 24063 - 24063 - 756250 - 7563 - 7563
+    );
+}
+
+#[tokio::test]
+async fn test_sell_passes_w_usdc() {
+    let (mut ctx, initializer, buyer, owner, mint) = setup().await;
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+
+    issue_passes(&mut ctx, &owner, &mint).await;
+
+    // buyer buy next passes
+    buy_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        10,
+    )
+    .await;
+
+    // buyer sell part of their passes
+    sell_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        5,
+    )
+    .await;
+
+    // check total owner's pass supply
+    let (passes_supply_pda, _) = get_passes_supply_pda(&owner.pubkey());
+    let passes_supply: PassesSupply = get_account(&mut ctx, passes_supply_pda).await;
+    assert_eq!(passes_supply.amount, 6);
+
+    // check byuer's pass balance of owner's pass
+    let (passes_balance_pda, _) = get_passes_balance_pda(&owner.pubkey(), &buyer.pubkey());
+    let passes_balance: PassesBalance = get_account(&mut ctx, passes_balance_pda).await;
+    assert_eq!(passes_balance.amount, 5);
+
+    // check protocol fee wallet
+    let protocol_fee_wallet =
+        anchor_spl::associated_token::get_associated_token_address(&initializer.pubkey(), &mint);
+    let account = get_token_account(&mut ctx, protocol_fee_wallet).await;
+    // assert_eq!(account.amount, 24062 + 20625);
+    assert_eq!(account.amount, 24063 + 20625);
+
+    // check owner fee wallet
+    let owner_fee_wallet =
+        anchor_spl::associated_token::get_associated_token_address(&owner.pubkey(), &mint);
+    let account = get_token_account(&mut ctx, owner_fee_wallet).await;
+    // assert_eq!(account.amount, 24062 + 20625);
+    assert_eq!(account.amount, 24063 + 20625);
+
+    // check buyer balance after purchase and ...
+    let buyer_wallet =
+        anchor_spl::associated_token::get_associated_token_address(&buyer.pubkey(), &mint);
+    let account = get_token_account(&mut ctx, buyer_wallet).await;
+    assert_eq!(
+        account.amount,
+        5_u64 * ONE_USDC - 2406250 - 24063 - 24063 + 2062500 - 20625 - 20625
+    );
+}
+
+#[tokio::test]
+async fn test_buy_passes_w_sol() {
+    let (mut ctx, initializer, buyer, owner, mint) = setup().await;
+
+    // get all pda s
+    let (escrow_wallet, _) = get_escrow_sol_wallet_pda();
+    let (passes_supply_pda, _) = get_passes_supply_pda(&owner.pubkey());
+    let (passes_balance_pda, _) = get_passes_balance_pda(&owner.pubkey(), &buyer.pubkey());
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+
+    issue_passes(&mut ctx, &owner, &mint).await;
+
+    let escrow_wallet_lamports_before = get_lamports(&mut ctx, &escrow_wallet).await;
+    let protocol_fee_wallet_lamports_before = get_lamports(&mut ctx, &initializer.pubkey()).await;
+    let owner_lamports_before = get_lamports(&mut ctx, &owner.pubkey()).await;
+
+    // buyer buy passes
+    buy_passes_sol(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        10,
+    )
+    .await;
+
+    let passes_supply: PassesSupply = get_account(&mut ctx, passes_supply_pda).await;
+    assert_eq!(passes_supply.amount, 11);
+
+    let passes_balance: PassesBalance = get_account(&mut ctx, passes_balance_pda).await;
+    assert_eq!(passes_balance.amount, 10);
+
+    assert_eq!(
+        get_lamports(&mut ctx, &escrow_wallet).await,
+        escrow_wallet_lamports_before + 240_625_000
+    );
+
+    assert_eq!(
+        get_lamports(&mut ctx, &initializer.pubkey()).await,
+        protocol_fee_wallet_lamports_before + 2_406_250
+    );
+
+    assert_eq!(
+        get_lamports(&mut ctx, &owner.pubkey()).await,
+        owner_lamports_before + 2_406_250
+    );
+}
+
+#[tokio::test]
+async fn test_sell_passes_w_sol() {
+    let (mut ctx, initializer, buyer, owner, mint) = setup().await;
+
+    // get all pda s
+    let (escrow_wallet, _) = get_escrow_sol_wallet_pda();
+    let (passes_supply_pda, _) = get_passes_supply_pda(&owner.pubkey());
+    let (passes_balance_pda, _) = get_passes_balance_pda(&owner.pubkey(), &buyer.pubkey());
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+
+    issue_passes(&mut ctx, &owner, &mint).await;
+
+    let escrow_wallet_lamports_before = get_lamports(&mut ctx, &escrow_wallet).await;
+    let protocol_fee_wallet_lamports_before = get_lamports(&mut ctx, &initializer.pubkey()).await;
+    let owner_lamports_before = get_lamports(&mut ctx, &owner.pubkey()).await;
+
+    // buyer buy passes
+    buy_passes_sol(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        10,
+    )
+    .await;
+
+    // buyer sell part of their passes
+    sell_passes_sol(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        5,
     )
     .await;
 
     // check total owner's pass supply
     let (passes_supply_pda, _) = get_passes_supply_pda(&owner.pubkey());
     let passes_supply: PassesSupply = get_account(&mut ctx, passes_supply_pda).await;
-    assert_eq!(passes_supply.amount, 6);
+    assert_eq!(passes_supply.amount, 6);
+
+    // check byuer's pass balance of owner's pass
+    let (passes_balance_pda, _) = get_passes_balance_pda(&owner.pubkey(), &buyer.pubkey());
+    let passes_balance: PassesBalance = get_account(&mut ctx, passes_balance_pda).await;
+    assert_eq!(passes_balance.amount, 5);
+
+    assert_eq!(
+        get_lamports(&mut ctx, &escrow_wallet).await,
+        escrow_wallet_lamports_before + 240_625_000 - 206_250_000
+    );
+
+    assert_eq!(
+        get_lamports(&mut ctx, &initializer.pubkey()).await,
+        protocol_fee_wallet_lamports_before + 2_406_250 + 2_062_500
+    );
+
+    assert_eq!(
+        get_lamports(&mut ctx, &owner.pubkey()).await,
+        owner_lamports_before + 2_406_250 + 2_062_500
+    );
+}
+
+#[tokio::test]
+async fn test_trade_stats() {
+    let (mut ctx, initializer, buyer, owner, mint) = setup().await;
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+
+    issue_passes(&mut ctx, &owner, &mint).await;
+
+    let (passes_supply_pda, _) = get_passes_supply_pda(&owner.pubkey());
+    let passes_supply: PassesSupply = get_account(&mut ctx, passes_supply_pda).await;
+    assert_eq!(
+        passes_supply.stats,
+        TradeStats {
+            ath_supply: 1,
+            ..Default::default()
+        }
+    );
+
+    set_unix_timestamp(&mut ctx, 1_700_000_000).await;
+
+    // the buy emits its trade with the stats after it
+    let (escrow_wallet, _) = get_escrow_token_wallet_pda(&mint);
+    let args = instruction::BuyPasses {
+        amount: 10,
+        proof: vec![],
+    };
+    let accounts = accounts::BuyPasses {
+        buyer: buyer.pubkey(),
+        passes_supply: passes_supply_pda,
+        passes_balance: Some(get_passes_balance_pda(&owner.pubkey(), &buyer.pubkey()).0),
+        holder_page: None,
+        reward_pool: find_reward_pool(&mut ctx, &owner.pubkey()).await,
+        config: get_config_pda().0,
+        owner_fee_wallet: anchor_spl::associated_token::get_associated_token_address(
+            &owner.pubkey(),
+            &mint,
+        ),
+        escrow_wallet,
+        reserve_wallet: None,
+        passes_owner: owner.pubkey(),
+        payment_mint: mint,
+        protocol_fee_wallet: anchor_spl::associated_token::get_associated_token_address(
+            &initializer.pubkey(),
+            &mint,
+        ),
+        buyer_wallet: anchor_spl::associated_token::get_associated_token_address(
+            &buyer.pubkey(),
+            &mint,
+        ),
+        system_program: system_program::ID,
+        token_program: anchor_spl::token::ID,
+    };
+    let events = execute_tx_with_events::<_, PassesTraded>(
+        &mut ctx,
+        accounts.to_account_metas(None),
+        &args,
+        &buyer,
+    )
+    .await
+    .unwrap();
+    let buy_price = crate::curve_price(1, 10).unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].owner, owner.pubkey());
+    assert_eq!(events[0].trader, buyer.pubkey());
+    assert_eq!(events[0].side, TradeSide::Buy);
+    assert!(!events[0].sol);
+    assert_eq!(events[0].amount, 10);
+    assert_eq!(events[0].price, buy_price);
+    assert_eq!(events[0].early_exit_fees, 0);
+    assert_eq!(events[0].marketplace_id, 0);
+    assert_eq!(events[0].supply, 11);
+    assert_eq!(
+        events[0].stats,
+        TradeStats {
+            buy_volume: buy_price,
+            trade_count: 1,
+            last_price: buy_price / 10,
+            last_trade_ts: 1_700_000_000,
+            ath_supply: 11,
+            ..Default::default()
+        }
+    );
+
+    set_unix_timestamp(&mut ctx, 1_700_000_100).await;
+    sell_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        4,
+    )
+    .await;
+
+    set_unix_timestamp(&mut ctx, 1_700_000_200).await;
+    buy_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        2,
+    )
+    .await;
+
+    set_unix_timestamp(&mut ctx, 1_700_000_300).await;
+    ctx.get_new_latest_blockhash().await.unwrap();
+    sell_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        1,
+    )
+    .await;
+
+    // the volumes add up by side, the peak supply stays after the sells
+    let sell_price = crate::curve_price(8, 1).unwrap();
+    let expected = TradeStats {
+        buy_volume: buy_price + crate::curve_price(7, 2).unwrap(),
+        sell_volume: crate::curve_price(7, 4).unwrap() + sell_price,
+        trade_count: 4,
+        last_price: sell_price,
+        last_trade_ts: 1_700_000_300,
+        ath_supply: 11,
+        ..Default::default()
+    };
+    let passes_supply: PassesSupply = get_account(&mut ctx, passes_supply_pda).await;
+    assert_eq!(passes_supply.amount, 8);
+    assert_eq!(passes_supply.stats, expected);
+
+    // the view returns the same stats
+    let args = instruction::GetTradeStats {};
+    let accounts = accounts::GetTradeStats {
+        config: get_config_pda().0,
+        passes_supply: passes_supply_pda,
+        passes_owner: owner.pubkey(),
+    };
+    let res =
+        simulate_tx::<_, TradeStats>(&mut ctx, accounts.to_account_metas(None), &args, &buyer)
+            .await;
+    assert_eq!(res.unwrap(), expected);
+}
+
+#[tokio::test]
+async fn test_isolated_reserve() {
+    let (mut ctx, initializer, buyer, owner, mint) = setup().await;
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+
+    // the owner keeps their reserve in its own wallets from the start
+    let params = IssuanceParams {
+        isolated_reserve: true,
+        ..Default::default()
+    };
+    let res = try_issue_passes_with_params(&mut ctx, &owner, &mint, 1, params).await;
+    assert_matches!(res, Ok(()));
+    let passes_supply: PassesSupply =
+        get_account(&mut ctx, get_passes_supply_pda(&owner.pubkey()).0).await;
+    assert!(passes_supply.isolated_reserve);
+
+    // another creator's market keeps the marketplace escrows
+    let creator = Keypair::new();
+    let ix = solana_sdk::system_instruction::transfer(
+        &initializer.pubkey(),
+        &creator.pubkey(),
+        100_000_000,
+    );
+    let mut tx =
+        solana_sdk::transaction::Transaction::new_with_payer(&[ix], Some(&initializer.pubkey()));
+    tx.sign(&[&initializer], ctx.last_blockhash);
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+    issue_passes(&mut ctx, &creator, &mint).await;
+
+    let (escrow_token_wallet, _) = get_escrow_token_wallet_pda(&mint);
+    let (escrow_sol_wallet, _) = get_escrow_sol_wallet_pda();
+    let (reserve_token_wallet, _) = get_reserve_token_wallet_pda(&owner.pubkey());
+    let (reserve_sol_wallet, _) = get_reserve_sol_wallet_pda(&owner.pubkey());
+    let escrow_lamports = get_lamports(&mut ctx, &escrow_sol_wallet).await;
+    let reserve_lamports = get_lamports(&mut ctx, &reserve_sol_wallet).await;
+
+    // the owner's trades only move their reserve wallets
+    buy_passes_sol(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        10,
+    )
+    .await;
+    sell_passes_sol(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        4,
+    )
+    .await;
+    buy_passes_sol(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        2,
+    )
+    .await;
+    sell_passes_sol(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        1,
+    )
+    .await;
+    assert_eq!(
+        get_token_account_balance(&mut ctx, reserve_token_wallet).await,
+        0
+    );
+    assert_eq!(
+        get_lamports(&mut ctx, &reserve_sol_wallet).await - reserve_lamports,
+        crate::curve_price_sol(1, 7).unwrap()
+    );
+    assert_eq!(
+        get_token_account_balance(&mut ctx, escrow_token_wallet).await,
+        0
+    );
+    assert_eq!(
+        get_lamports(&mut ctx, &escrow_sol_wallet).await,
+        escrow_lamports
+    );
+
+    // the creator's trades fill the token escrow
+    buy_passes(
+        &mut ctx,
+        &buyer,
+        &creator.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        3,
+    )
+    .await;
+    buy_passes(
+        &mut ctx,
+        &buyer,
+        &creator.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        5,
+    )
+    .await;
+    let token_reserve = crate::curve_price(1, 8).unwrap();
+    assert_eq!(
+        get_token_account_balance(&mut ctx, escrow_token_wallet).await,
+        token_reserve
+    );
+    assert_eq!(
+        get_lamports(&mut ctx, &escrow_sol_wallet).await,
+        escrow_lamports
+    );
+
+    // the admin migrates the creator's market with the net volume its stats tracked
+    let creator_key = creator.pubkey();
+    let res = try_isolate_reserve(&mut ctx, &creator, &creator_key, &mint, None).await;
+    assert!(res.is_err());
+    let res = try_isolate_reserve(&mut ctx, &initializer, &creator_key, &mint, None).await;
+    assert_matches!(res, Ok(()));
+
+    let passes_supply: PassesSupply =
+        get_account(&mut ctx, get_passes_supply_pda(&creator_key).0).await;
+    assert!(passes_supply.isolated_reserve);
+    let (creator_reserve_wallet, _) = get_reserve_token_wallet_pda(&creator_key);
+    assert_eq!(
+        get_token_account_balance(&mut ctx, creator_reserve_wallet).await,
+        token_reserve
+    );
+    assert_eq!(
+        get_token_account_balance(&mut ctx, escrow_token_wallet).await,
+        0
+    );
+    assert_eq!(
+        get_lamports(&mut ctx, &escrow_sol_wallet).await,
+        escrow_lamports
+    );
+
+    // the reserve moves once
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let res = try_isolate_reserve(&mut ctx, &initializer, &creator_key, &mint, None).await;
+    assert!(res.is_err());
+
+    // sells are paid from the migrated reserve
+    sell_passes(
+        &mut ctx,
+        &buyer,
+        &creator_key,
+        &initializer.pubkey(),
+        &mint,
+        2,
+    )
+    .await;
+    assert_eq!(
+        get_token_account_balance(&mut ctx, creator_reserve_wallet).await,
+        crate::curve_price(1, 6).unwrap()
+    );
+    assert_eq!(
+        get_token_account_balance(&mut ctx, escrow_token_wallet).await,
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_pause_trading() {
+    let (mut ctx, initializer, buyer, owner, mint) = setup().await;
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+
+    issue_passes(&mut ctx, &owner, &mint).await;
+
+    buy_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        2,
+    )
+    .await;
+
+    // admin pauses buys in all markets
+    let res = set_pause(
+        &mut ctx,
+        &initializer,
+        &instruction::SetBuysPaused { paused: true },
+    )
+    .await;
+    assert_matches!(res, Ok(()));
+
+    let config: Config = get_account(&mut ctx, get_config_pda().0).await;
+    assert!(config.buys_paused);
+    assert!(!config.sells_paused);
+
+    let res = try_buy_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        1,
+    )
+    .await;
+    assert_passes_error(res, PassesError::Paused);
+
+    let res = try_buy_passes_sol(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        1,
+    )
+    .await;
+    assert_passes_error(res, PassesError::Paused);
+
+    // sells are still allowed
+    sell_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        1,
+    )
+    .await;
+
+    // admin pauses sells and resumes buys
+    let res = set_pause(
+        &mut ctx,
+        &initializer,
+        &instruction::SetSellsPaused { paused: true },
+    )
+    .await;
+    assert_matches!(res, Ok(()));
+    let res = set_pause(
+        &mut ctx,
+        &initializer,
+        &instruction::SetBuysPaused { paused: false },
+    )
+    .await;
+    assert_matches!(res, Ok(()));
+
+    // resend the same sell as before in a new block
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let res = try_sell_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        1,
+    )
+    .await;
+    assert_passes_error(res, PassesError::Paused);
+
+    let res = try_sell_passes_sol(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        1,
+    )
+    .await;
+    assert_passes_error(res, PassesError::Paused);
+
+    buy_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        3,
+    )
+    .await;
+
+    // only the admin can pause
+    let res = set_pause(
+        &mut ctx,
+        &buyer,
+        &instruction::SetSellsPaused { paused: false },
+    )
+    .await;
+    assert!(res.is_err());
+}
+
+#[tokio::test]
+async fn test_pause_issuance() {
+    let (mut ctx, initializer, _, owner, mint) = setup().await;
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+
+    let res = set_pause(
+        &mut ctx,
+        &initializer,
+        &instruction::SetIssuancePaused { paused: true },
+    )
+    .await;
+    assert_matches!(res, Ok(()));
+
+    let res = try_issue_passes(&mut ctx, &owner, &mint).await;
+    assert_passes_error(res, PassesError::Paused);
+
+    let res = set_pause(
+        &mut ctx,
+        &initializer,
+        &instruction::SetIssuancePaused { paused: false },
+    )
+    .await;
+    assert_matches!(res, Ok(()));
+
+    ctx.get_new_latest_blockhash().await.unwrap();
+    issue_passes(&mut ctx, &owner, &mint).await;
+}
+
+#[tokio::test]
+async fn test_pause_passes() {
+    let (mut ctx, initializer, buyer, owner, mint) = setup().await;
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+
+    issue_passes(&mut ctx, &owner, &mint).await;
+
+    buy_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        2,
+    )
+    .await;
+
+    // owner pauses their market
+    let res = set_passes_paused(&mut ctx, &owner, true).await;
+    assert_matches!(res, Ok(()));
+
+    let (passes_supply_pda, _) = get_passes_supply_pda(&owner.pubkey());
+    let passes_supply: PassesSupply = get_account(&mut ctx, passes_supply_pda).await;
+    assert!(passes_supply.paused);
+
+    let res = try_buy_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        1,
+    )
+    .await;
+    assert_passes_error(res, PassesError::Paused);
+
+    let res = try_sell_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        1,
+    )
+    .await;
+    assert_passes_error(res, PassesError::Paused);
+
+    // owner resumes their market
+    let res = set_passes_paused(&mut ctx, &owner, false).await;
+    assert_matches!(res, Ok(()));
+
+    ctx.get_new_latest_blockhash().await.unwrap();
+    sell_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        1,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_guardian() {
+    let (mut ctx, initializer, _, owner, mint) = setup().await;
+    let guardian = owner;
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+
+    // guardian cannot pause before being appointed
+    let res = set_pause(
+        &mut ctx,
+        &guardian,
+        &instruction::SetBuysPaused { paused: true },
+    )
+    .await;
+    assert!(res.is_err());
+
+    // only the admin can appoint the guardian
+    let res = set_guardian(&mut ctx, &guardian, &guardian.pubkey()).await;
+    assert!(res.is_err());
+
+    let res = set_guardian(&mut ctx, &initializer, &guardian.pubkey()).await;
+    assert_matches!(res, Ok(()));
+
+    let (config_pda, _) = get_config_pda();
+    let config: Config = get_account(&mut ctx, config_pda).await;
+    assert_eq!(config.guardian, guardian.pubkey());
+
+    // guardian pauses and unpauses trading
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let res = set_pause(
+        &mut ctx,
+        &guardian,
+        &instruction::SetBuysPaused { paused: true },
+    )
+    .await;
+    assert_matches!(res, Ok(()));
+    let res = set_pause(
+        &mut ctx,
+        &guardian,
+        &instruction::SetSellsPaused { paused: true },
+    )
+    .await;
+    assert_matches!(res, Ok(()));
+
+    let config: Config = get_account(&mut ctx, config_pda).await;
+    assert!(config.buys_paused);
+    assert!(config.sells_paused);
+
+    let res = set_pause(
+        &mut ctx,
+        &guardian,
+        &instruction::SetBuysPaused { paused: false },
+    )
+    .await;
+    assert_matches!(res, Ok(()));
+
+    // guardian has no fee authority
+    let args = instruction::SetProtocolFeeBps { fee_bps: 0 };
+    let accounts = accounts::SetFeePercent {
+        admin: guardian.pubkey(),
+        config: config_pda,
+        system_program: system_program::ID,
+    };
+    let res = execute_tx(&mut ctx, accounts.to_account_metas(None), &args, &guardian).await;
+    assert!(res.is_err());
+}
+
+#[tokio::test]
+async fn test_set_fee_dst_not_admin() {
+    let (mut ctx, initializer, buyer, owner, mint) = setup().await;
+    let treasury = Keypair::new();
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+
+    issue_passes(&mut ctx, &owner, &mint).await;
+
+    // route protocol fees to a token account not owned by the admin
+    let treasury_wallet = ctx
+        .initialize_token_accounts(mint, &[treasury.pubkey()])
+        .await
+        .unwrap()[0];
+    let (config_pda, _) = get_config_pda();
+    let args = instruction::SetProtocolFeeDst {};
+    let accounts = accounts::SetProtocolFeeDst {
+        admin: initializer.pubkey(),
+        config: config_pda,
+        protocol_fee_wallet: treasury_wallet,
+        system_program: system_program::ID,
+    };
+    let res = execute_tx(
+        &mut ctx,
+        accounts.to_account_metas(None),
+        &args,
+        &initializer,
+    )
+    .await;
+    assert_matches!(res, Ok(()));
+
+    let config: Config = get_account(&mut ctx, config_pda).await;
+    assert_eq!(config.protocol_fee_token_wallet, treasury_wallet);
+
+    let (escrow_wallet, _) = get_escrow_token_wallet_pda(&mint);
+    let buyer_wallet =
+        anchor_spl::associated_token::get_associated_token_address(&buyer.pubkey(), &mint);
+    let owner_fee_wallet =
+        anchor_spl::associated_token::get_associated_token_address(&owner.pubkey(), &mint);
+    let args = instruction::BuyPasses {
+        amount: 10,
+        proof: vec![],
+    };
+    let accounts = accounts::BuyPasses {
+        buyer: buyer.pubkey(),
+        passes_supply: get_passes_supply_pda(&owner.pubkey()).0,
+        passes_balance: Some(get_passes_balance_pda(&owner.pubkey(), &buyer.pubkey()).0),
+        holder_page: None,
+        reward_pool: None,
+        config: config_pda,
+        owner_fee_wallet,
+        escrow_wallet,
+        reserve_wallet: None,
+        passes_owner: owner.pubkey(),
+        payment_mint: mint,
+        protocol_fee_wallet: treasury_wallet,
+        buyer_wallet,
+        system_program: system_program::ID,
+        token_program: anchor_spl::token::ID,
+    };
+    let res = execute_tx(&mut ctx, accounts.to_account_metas(None), &args, &buyer).await;
+    assert_matches!(res, Ok(()));
+
+    let account = get_token_account(&mut ctx, treasury_wallet).await;
+    assert_eq!(account.amount, 24063);
+
+    // the admin's wallet is no longer accepted
+    let res = try_buy_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        1,
+    )
+    .await;
+    assert!(res.is_err());
+}
+
+#[tokio::test]
+async fn test_set_fee_sol_dst() {
+    let (mut ctx, initializer, buyer, owner, mint) = setup().await;
+    let treasury = Keypair::new().pubkey();
+    ctx.set_account(
+        &treasury,
+        &account::AccountSharedData::new(1_000_000_000, 0, &system_program::ID),
+    );
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+
+    issue_passes(&mut ctx, &owner, &mint).await;
+
+    // only the admin can set the sol fee destination
+    let (config_pda, _) = get_config_pda();
+    let args = instruction::SetProtocolFeeSolDst {};
+    let accounts = accounts::SetProtocolFeeSolDst {
+        admin: buyer.pubkey(),
+        config: config_pda,
+        protocol_fee_wallet: treasury,
+        system_program: system_program::ID,
+    };
+    let res = execute_tx(&mut ctx, accounts.to_account_metas(None), &args, &buyer).await;
+    assert!(res.is_err());
+
+    let accounts = accounts::SetProtocolFeeSolDst {
+        admin: initializer.pubkey(),
+        config: config_pda,
+        protocol_fee_wallet: treasury,
+        system_program: system_program::ID,
+    };
+    let res = execute_tx(
+        &mut ctx,
+        accounts.to_account_metas(None),
+        &args,
+        &initializer,
+    )
+    .await;
+    assert_matches!(res, Ok(()));
+
+    let config: Config = get_account(&mut ctx, config_pda).await;
+    assert_eq!(config.protocol_fee_sol_wallet, treasury);
+
+    // the admin no longer receives sol fees
+    let res = try_buy_passes_sol(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        10,
+    )
+    .await;
+    assert!(res.is_err());
+
+    let treasury_lamports_before = get_lamports(&mut ctx, &treasury).await;
+
+    buy_passes_sol(&mut ctx, &buyer, &owner.pubkey(), &treasury, &mint, 10).await;
+    sell_passes_sol(&mut ctx, &buyer, &owner.pubkey(), &treasury, &mint, 5).await;
+
+    assert_eq!(
+        get_lamports(&mut ctx, &treasury).await,
+        treasury_lamports_before + 2_406_250 + 2_062_500
+    );
+}
+
+#[tokio::test]
+async fn test_multisig_config_change() {
+    let (mut ctx, initializer, admin2, admin3, mint) = setup().await;
+    let outsider = Keypair::new();
+    ctx.set_account(
+        &outsider.pubkey(),
+        &account::AccountSharedData::new(1_000_000_000, 0, &system_program::ID),
+    );
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+
+    // threshold above the number of admins is rejected
+    let admins = vec![initializer.pubkey(), admin2.pubkey(), admin3.pubkey()];
+    let res = set_admins(&mut ctx, &initializer, admins.clone(), 4).await;
+    assert_passes_error(res, PassesError::InvalidAdmins);
+
+    let res = set_admins(&mut ctx, &initializer, admins.clone(), 2).await;
+    assert_matches!(res, Ok(()));
+
+    let (config_pda, _) = get_config_pda();
+    let config: Config = get_account(&mut ctx, config_pda).await;
+    assert_eq!(config.admins(), admins.as_slice());
+    assert_eq!(config.admin_threshold, 2);
+
+    // single admin changes are disabled
+    let args = instruction::SetProtocolFeeBps { fee_bps: 500 };
+    let accounts = accounts::SetFeePercent {
+        admin: initializer.pubkey(),
+        config: config_pda,
+        system_program: system_program::ID,
+    };
+    let res = execute_tx(
+        &mut ctx,
+        accounts.to_account_metas(None),
+        &args,
+        &initializer,
+    )
+    .await;
+    assert_passes_error(res, PassesError::MultisigRequired);
+
+    let res = set_admins(&mut ctx, &initializer, vec![initializer.pubkey()], 1).await;
+    assert_passes_error(res, PassesError::MultisigRequired);
+
+    // only admins can propose
+    let res = propose_config_change(
+        &mut ctx,
+        &outsider,
+        ConfigChange::ProtocolFeeBps { fee_bps: 500 },
+    )
+    .await;
+    assert_passes_error(res.map(|_| ()), PassesError::NotAnAdmin);
+
+    let proposal = propose_config_change(
+        &mut ctx,
+        &initializer,
+        ConfigChange::ProtocolFeeBps { fee_bps: 500 },
+    )
+    .await
+    .unwrap();
+    assert_eq!(proposal, get_proposal_pda(0).0);
+
+    // the proposer's approval alone is not enough
+    let res = execute_proposal(&mut ctx, &initializer, &proposal, None).await;
+    assert_passes_error(res, PassesError::NotEnoughApprovals);
+
+    let res = approve_proposal(&mut ctx, &initializer, &proposal).await;
+    assert_passes_error(res, PassesError::AlreadyApproved);
+
+    let res = approve_proposal(&mut ctx, &outsider, &proposal).await;
+    assert_passes_error(res, PassesError::NotAnAdmin);
+
+    let res = approve_proposal(&mut ctx, &admin2, &proposal).await;
+    assert_matches!(res, Ok(()));
+
+    let res = execute_proposal(&mut ctx, &admin3, &proposal, None).await;
+    assert_matches!(res, Ok(()));
+
+    let config: Config = get_account(&mut ctx, config_pda).await;
+    assert_eq!(config.protocol_fee_bps, 500);
+    assert_eq!(config.proposal_count, 1);
+
+    let proposal_account: Proposal = get_account(&mut ctx, proposal).await;
+    assert!(proposal_account.executed);
+    assert_eq!(
+        proposal_account.approvals(),
+        &[initializer.pubkey(), admin2.pubkey()]
+    );
+
+    let res = execute_proposal(&mut ctx, &admin2, &proposal, None).await;
+    assert_passes_error(res, PassesError::ProposalExecuted);
+
+    // pause through a proposal
+    let proposal = propose_config_change(
+        &mut ctx,
+        &admin3,
+        ConfigChange::Pause {
+            buys: true,
+            sells: false,
+            issuance: true,
+        },
+    )
+    .await
+    .unwrap();
+    let res = approve_proposal(&mut ctx, &admin2, &proposal).await;
+    assert_matches!(res, Ok(()));
+    let res = execute_proposal(&mut ctx, &admin2, &proposal, None).await;
+    assert_matches!(res, Ok(()));
+
+    let config: Config = get_account(&mut ctx, config_pda).await;
+    assert!(config.buys_paused);
+    assert!(!config.sells_paused);
+    assert!(config.issuance_paused);
+}
+
+#[tokio::test]
+async fn test_multisig_pause() {
+    let (mut ctx, initializer, admin2, guardian, mint) = setup().await;
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+    let res = set_guardian(&mut ctx, &initializer, &guardian.pubkey()).await;
+    assert_matches!(res, Ok(()));
+    let admins = vec![initializer.pubkey(), admin2.pubkey()];
+    let res = set_admins(&mut ctx, &initializer, admins, 2).await;
+    assert_matches!(res, Ok(()));
+
+    // the admin can't pause alone under a multisig
+    let res = set_pause(
+        &mut ctx,
+        &initializer,
+        &instruction::SetBuysPaused { paused: true },
+    )
+    .await;
+    assert!(res.is_err());
+
+    // the guardian still can
+    let res = set_pause(
+        &mut ctx,
+        &guardian,
+        &instruction::SetBuysPaused { paused: true },
+    )
+    .await;
+    assert_matches!(res, Ok(()));
+    let config: Config = get_account(&mut ctx, get_config_pda().0).await;
+    assert!(config.buys_paused);
+}
+
+#[tokio::test]
+async fn test_multisig_isolate_reserve() {
+    let (mut ctx, initializer, buyer, owner, mint) = setup().await;
+    let admin2 = Keypair::new();
+    ctx.set_account(
+        &admin2.pubkey(),
+        &account::AccountSharedData::new(1_000_000_000, 0, &system_program::ID),
+    );
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+    issue_passes(&mut ctx, &owner, &mint).await;
+    issue_passes(&mut ctx, &buyer, &mint).await;
+    buy_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        5,
+    )
+    .await;
+
+    let admins = vec![initializer.pubkey(), admin2.pubkey()];
+    let res = set_admins(&mut ctx, &initializer, admins, 2).await;
+    assert_matches!(res, Ok(()));
+
+    // a multisig isolates reserves through a proposal
+    let owner_key = owner.pubkey();
+    let res = try_isolate_reserve(&mut ctx, &initializer, &owner_key, &mint, None).await;
+    assert_passes_error(res, PassesError::MultisigRequired);
+
+    let proposal = propose_config_change(
+        &mut ctx,
+        &initializer,
+        ConfigChange::IsolateReserve { owner: owner_key },
+    )
+    .await
+    .unwrap();
+    let res = try_isolate_reserve(&mut ctx, &initializer, &owner_key, &mint, Some(proposal)).await;
+    assert_passes_error(res, PassesError::InvalidProposal);
+
+    let res = approve_proposal(&mut ctx, &admin2, &proposal).await;
+    assert_matches!(res, Ok(()));
+    let res = execute_proposal(&mut ctx, &admin2, &proposal, None).await;
+    assert_matches!(res, Ok(()));
+
+    // the executed proposal only isolates the market it names
+    let other_key = buyer.pubkey();
+    let res = try_isolate_reserve(&mut ctx, &admin2, &other_key, &mint, Some(proposal)).await;
+    assert_passes_error(res, PassesError::InvalidProposal);
+
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let res = try_isolate_reserve(&mut ctx, &admin2, &owner_key, &mint, Some(proposal)).await;
+    assert_matches!(res, Ok(()));
+
+    let passes_supply: PassesSupply =
+        get_account(&mut ctx, get_passes_supply_pda(&owner_key).0).await;
+    assert!(passes_supply.isolated_reserve);
+    assert_eq!(
+        get_token_account_balance(&mut ctx, get_reserve_token_wallet_pda(&owner_key).0).await,
+        crate::curve_price(1, 5).unwrap()
+    );
+    assert_eq!(
+        get_token_account_balance(&mut ctx, get_escrow_token_wallet_pda(&mint).0).await,
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_multisig_fee_wallet_and_admins() {
+    let (mut ctx, initializer, admin2, admin3, mint) = setup().await;
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+
+    let admins = vec![initializer.pubkey(), admin2.pubkey(), admin3.pubkey()];
+    let res = set_admins(&mut ctx, &initializer, admins, 2).await;
+    assert_matches!(res, Ok(()));
+
+    // move token fees to admin3's wallet
+    let wallet =
+        anchor_spl::associated_token::get_associated_token_address(&admin3.pubkey(), &mint);
+    let proposal = propose_config_change(
+        &mut ctx,
+        &initializer,
+        ConfigChange::ProtocolFeeTokenWallet { wallet },
+    )
+    .await
+    .unwrap();
+    let res = approve_proposal(&mut ctx, &admin3, &proposal).await;
+    assert_matches!(res, Ok(()));
+
+    // the wallet account must be provided
+    let res = execute_proposal(&mut ctx, &admin3, &proposal, None).await;
+    assert_passes_error(res, PassesError::InvalidFeeWallet);
+
+    let res = execute_proposal(&mut ctx, &admin3, &proposal, Some(wallet)).await;
+    assert_matches!(res, Ok(()));
+
+    let (config_pda, _) = get_config_pda();
+    let config: Config = get_account(&mut ctx, config_pda).await;
+    assert_eq!(config.protocol_fee_token_wallet, wallet);
+
+    // remove admin2 and require both remaining admins
+    let new_admins = vec![initializer.pubkey(), admin3.pubkey()];
+    let proposal = propose_config_change(
+        &mut ctx,
+        &admin2,
+        ConfigChange::Admins {
+            admins: new_admins.clone(),
+            threshold: 2,
+        },
+    )
+    .await
+    .unwrap();
+    let res = approve_proposal(&mut ctx, &admin3, &proposal).await;
+    assert_matches!(res, Ok(()));
+    let res = execute_proposal(&mut ctx, &admin3, &proposal, None).await;
+    assert_matches!(res, Ok(()));
+
+    let config: Config = get_account(&mut ctx, config_pda).await;
+    assert_eq!(config.admins(), new_admins.as_slice());
+
+    // approvals of removed admins no longer count
+    let proposal = propose_config_change(
+        &mut ctx,
+        &initializer,
+        ConfigChange::OwnerFeeBps { fee_bps: 0 },
+    )
+    .await
+    .unwrap();
+    let res = approve_proposal(&mut ctx, &admin2, &proposal).await;
+    assert_passes_error(res, PassesError::NotAnAdmin);
+    let res = execute_proposal(&mut ctx, &initializer, &proposal, None).await;
+    assert_passes_error(res, PassesError::NotEnoughApprovals);
+}
+
+#[tokio::test]
+async fn test_proposal_fee_bounds_and_expiry() {
+    let (mut ctx, initializer, admin2, admin3, mint) = setup().await;
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+
+    let admins = vec![initializer.pubkey(), admin2.pubkey(), admin3.pubkey()];
+    let res = set_admins(&mut ctx, &initializer, admins, 2).await;
+    assert_matches!(res, Ok(()));
+
+    // the fees can't add up to more than the price
+    let proposal = propose_config_change(
+        &mut ctx,
+        &initializer,
+        ConfigChange::OwnerFeeBps {
+            fee_bps: 10_000 - PROTOCOL_FEE_BPS + 1,
+        },
+    )
+    .await
+    .unwrap();
+    let res = approve_proposal(&mut ctx, &admin2, &proposal).await;
+    assert_matches!(res, Ok(()));
+    let res = execute_proposal(&mut ctx, &admin2, &proposal, None).await;
+    assert_passes_error(res, PassesError::InvalidFee);
+
+    // approvals and execution stop at the expiry
+    let proposal = propose_config_change(
+        &mut ctx,
+        &initializer,
+        ConfigChange::ProtocolFeeBps { fee_bps: 500 },
+    )
+    .await
+    .unwrap();
+    let proposal_account: Proposal = get_account(&mut ctx, proposal).await;
+    let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+    assert_eq!(
+        proposal_account.expires_ts,
+        clock.unix_timestamp + Proposal::EXPIRY_SECS
+    );
+
+    set_unix_timestamp(&mut ctx, proposal_account.expires_ts - 1).await;
+    let res = approve_proposal(&mut ctx, &admin2, &proposal).await;
+    assert_matches!(res, Ok(()));
+
+    set_unix_timestamp(&mut ctx, proposal_account.expires_ts).await;
+    let res = approve_proposal(&mut ctx, &admin3, &proposal).await;
+    assert_passes_error(res, PassesError::ProposalExpired);
+    let res = execute_proposal(&mut ctx, &admin3, &proposal, None).await;
+    assert_passes_error(res, PassesError::ProposalExpired);
+
+    let (config_pda, _) = get_config_pda();
+    let config: Config = get_account(&mut ctx, config_pda).await;
+    assert_eq!(config.protocol_fee_bps, PROTOCOL_FEE_BPS);
+}
+
+#[tokio::test]
+async fn test_wallet_cap() {
+    let (mut ctx, initializer, buyer, owner, mint) = setup().await;
+    let admin = initializer.pubkey();
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+
+    issue_passes(&mut ctx, &owner, &mint).await;
+
+    let res = set_wallet_cap(&mut ctx, &owner, 0, 10_001).await;
+    assert_passes_error(res, PassesError::InvalidWalletCap);
+
+    // absolute cap
+    set_wallet_cap(&mut ctx, &owner, 5, 0).await.unwrap();
+
+    buy_passes_sol(&mut ctx, &buyer, &owner.pubkey(), &admin, &mint, 5).await;
+    let res = try_buy_passes_sol(&mut ctx, &buyer, &owner.pubkey(), &admin, &mint, 1).await;
+    assert_passes_error(res, PassesError::WalletCapExceeded);
+
+    // a holder page would hide passes from the cap, so capped passes stay out of pages
+    create_holder_page(&mut ctx, &owner, &owner.pubkey(), 0)
+        .await
+        .unwrap();
+    let into_page = accounts::BuyPassesSol {
+        buyer: buyer.pubkey(),
+        passes_supply: get_passes_supply_pda(&owner.pubkey()).0,
+        passes_balance: None,
+        holder_page: Some(get_holder_page_pda(&owner.pubkey(), 0).0),
+        reward_pool: None,
+        config: get_config_pda().0,
+        escrow_wallet: get_escrow_sol_wallet_pda().0,
+        reserve_wallet: None,
+        protocol_fee_wallet: admin,
+        passes_owner: owner.pubkey(),
+        system_program: system_program::ID,
+    };
+    let args = instruction::BuyPassesSol {
+        amount: 1,
+        proof: vec![],
+    };
+    let res = execute_tx(&mut ctx, into_page.to_account_metas(None), &args, &buyer).await;
+    assert_passes_error(res, PassesError::HolderPageCapped);
+    let res = convert_to_holder_page(&mut ctx, &buyer, &owner.pubkey(), 0).await;
+    assert_passes_error(res, PassesError::HolderPageCapped);
+
+    // the owner isn't capped in their own market
+    buy_passes_sol(&mut ctx, &owner, &owner.pubkey(), &admin, &mint, 4).await;
+
+    // cap of 50% of the supply after the buy
+    set_wallet_cap(&mut ctx, &owner, 0, 5_000).await.unwrap();
+
+    let res = try_buy_passes_sol(&mut ctx, &buyer, &owner.pubkey(), &admin, &mint, 1).await;
+    assert_passes_error(res, PassesError::WalletCapExceeded);
+
+    buy_passes_sol(&mut ctx, &owner, &owner.pubkey(), &admin, &mint, 2).await;
+    ctx.get_new_latest_blockhash().await.unwrap();
+    buy_passes_sol(&mut ctx, &buyer, &owner.pubkey(), &admin, &mint, 1).await;
+
+    // passes moved back from a holder page count towards the cap
+    set_wallet_cap(&mut ctx, &owner, 0, 0).await.unwrap();
+    convert_to_holder_page(&mut ctx, &buyer, &owner.pubkey(), 0)
+        .await
+        .unwrap();
+    ctx.get_new_latest_blockhash().await.unwrap();
+    buy_passes_sol(&mut ctx, &buyer, &owner.pubkey(), &admin, &mint, 1).await;
+
+    set_wallet_cap(&mut ctx, &owner, 5, 5_000).await.unwrap();
+    let res = convert_from_holder_page(&mut ctx, &buyer, &owner.pubkey(), 0).await;
+    assert_passes_error(res, PassesError::WalletCapExceeded);
+
+    ctx.get_new_latest_blockhash().await.unwrap();
+    set_wallet_cap(&mut ctx, &owner, 0, 5_000).await.unwrap();
+    convert_from_holder_page(&mut ctx, &buyer, &owner.pubkey(), 0)
+        .await
+        .unwrap();
+
+    let (passes_balance_pda, _) = get_passes_balance_pda(&owner.pubkey(), &buyer.pubkey());
+    let passes_balance: PassesBalance = get_account(&mut ctx, passes_balance_pda).await;
+    assert_eq!(passes_balance.amount, 7);
+}
+
+#[tokio::test]
+async fn test_holding_period() {
+    let (mut ctx, initializer, buyer, owner, mint) = setup().await;
+    let admin = initializer.pubkey();
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+
+    issue_passes(&mut ctx, &owner, &mint).await;
+
+    let res = set_holding_period(&mut ctx, &owner, -1, 0).await;
+    assert_passes_error(res, PassesError::InvalidHoldingPeriod);
+    let res = set_holding_period(
+        &mut ctx,
+        &owner,
+        3600,
+        PassesSupply::MAX_EARLY_EXIT_FEE_BPS + 1,
+    )
+    .await;
+    assert_passes_error(res, PassesError::InvalidFee);
+
+    // early sells are rejected without an early exit fee
+    set_holding_period(&mut ctx, &owner, 3600, 0).await.unwrap();
+
+    let now = get_unix_timestamp(&mut ctx).await;
+    buy_passes_sol(&mut ctx, &buyer, &owner.pubkey(), &admin, &mint, 2).await;
+
+    let (passes_balance_pda, _) = get_passes_balance_pda(&owner.pubkey(), &buyer.pubkey());
+    let passes_balance: PassesBalance = get_account(&mut ctx, passes_balance_pda).await;
+    assert_eq!(passes_balance.last_buy_ts, now);
+
+    let res = try_sell_passes_sol(&mut ctx, &buyer, &owner.pubkey(), &admin, &mint, 1).await;
+    assert_passes_error(res, PassesError::HoldingPeriodNotOver);
+
+    // with a 10% early exit fee the owner gets it on top of their fees
+    set_holding_period(&mut ctx, &owner, 3600, 1_000)
+        .await
+        .unwrap();
+
+    let owner_lamports = get_lamports(&mut ctx, &owner.pubkey()).await;
+    let buyer_lamports = get_lamports(&mut ctx, &buyer.pubkey()).await;
+    ctx.get_new_latest_blockhash().await.unwrap();
+    sell_passes_sol(&mut ctx, &buyer, &owner.pubkey(), &admin, &mint, 1).await;
+
+    // price 2_500_000, fees 25_000 each, early exit fee 250_000
+    assert_eq!(
+        get_lamports(&mut ctx, &owner.pubkey()).await,
+        owner_lamports + 25_000 + 250_000
+    );
+    assert_eq!(
+        get_lamports(&mut ctx, &buyer.pubkey()).await,
+        buyer_lamports + 2_500_000 - 25_000 - 25_000 - 250_000 - 5000
+    );
+
+    // no fee once the holding period is over
+    set_unix_timestamp(&mut ctx, now + 3600).await;
+    ctx.get_new_latest_blockhash().await.unwrap();
+
+    let owner_lamports = get_lamports(&mut ctx, &owner.pubkey()).await;
+    sell_passes_sol(&mut ctx, &buyer, &owner.pubkey(), &admin, &mint, 1).await;
+    assert_eq!(
+        get_lamports(&mut ctx, &owner.pubkey()).await,
+        owner_lamports + 6_250
+    );
+}
+
+#[tokio::test]
+async fn test_early_exit_fee_limits() {
+    let (mut ctx, initializer, buyer, owner, mint) = setup().await;
+    let admin = initializer.pubkey();
+
+    // the marketplace fees and the largest early exit fee take the whole price
+    init_passes(&mut ctx, &initializer, &mint, 2_500, 2_500).await;
+    issue_passes(&mut ctx, &owner, &mint).await;
+    set_holding_period(&mut ctx, &owner, 3600, PassesSupply::MAX_EARLY_EXIT_FEE_BPS)
+        .await
+        .unwrap();
+
+    buy_passes_sol(&mut ctx, &buyer, &owner.pubkey(), &admin, &mint, 2).await;
+    let buyer_lamports = get_lamports(&mut ctx, &buyer.pubkey()).await;
+    sell_passes_sol(&mut ctx, &buyer, &owner.pubkey(), &admin, &mint, 1).await;
+    assert_eq!(
+        get_lamports(&mut ctx, &buyer.pubkey()).await,
+        buyer_lamports - 5000
+    );
+
+    // raising the fees afterwards makes early sells fail with a clear error
+    let args = instruction::SetProtocolFeeBps { fee_bps: 2_501 };
+    let accounts = accounts::SetFeePercent {
+        admin,
+        config: get_config_pda().0,
+        system_program: system_program::ID,
+    };
+    let res = execute_tx(
+        &mut ctx,
+        accounts.to_account_metas(None),
+        &args,
+        &initializer,
+    )
+    .await;
+    assert_matches!(res, Ok(()));
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let res = try_sell_passes_sol(&mut ctx, &buyer, &owner.pubkey(), &admin, &mint, 1).await;
+    assert_passes_error(res, PassesError::FeesExceedPrice);
+
+    // and the owner can't set an early exit fee past the price
+    let res =
+        set_holding_period(&mut ctx, &owner, 3600, PassesSupply::MAX_EARLY_EXIT_FEE_BPS).await;
+    assert_passes_error(res, PassesError::InvalidFee);
+    set_holding_period(&mut ctx, &owner, 3600, 4_999)
+        .await
+        .unwrap();
+
+    // the fees round up a unit past the price, which comes off the early exit fee
+    let owner_lamports = get_lamports(&mut ctx, &owner.pubkey()).await;
+    ctx.get_new_latest_blockhash().await.unwrap();
+    sell_passes_sol(&mut ctx, &buyer, &owner.pubkey(), &admin, &mint, 1).await;
+    assert_eq!(
+        get_lamports(&mut ctx, &owner.pubkey()).await,
+        owner_lamports + 156_250 + 312_437
+    );
+}
+
+#[tokio::test]
+async fn test_airdrop_passes() {
+    let (mut ctx, initializer, users, mint) = setup_users(2).await;
+    let (owner, buyer) = (&users[0], &users[1]);
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+
+    // the issued pass stays with the owner, who gifts passes they bought
+    issue_passes(&mut ctx, owner, &mint).await;
+    let res = airdrop_passes(&mut ctx, owner, &[(buyer.pubkey(), 1)]).await;
+    assert_passes_error(res, PassesError::IssuedPassesLocked);
+    buy_passes(
+        &mut ctx,
+        owner,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        59,
+    )
+    .await;
+    ctx.get_new_latest_blockhash().await.unwrap();
+    airdrop_passes(&mut ctx, owner, &[(buyer.pubkey(), 1)])
+        .await
+        .unwrap();
+
+    // a full batch of new recipients and an existing holder fits in one transaction
+    let mut recipients: Vec<(Pubkey, u64)> = (1..PassesBalance::MAX_AIRDROP_RECIPIENTS as u64)
+        .map(|amount| (Pubkey::new_unique(), amount))
+        .collect();
+    recipients.push((buyer.pubkey(), 5));
+    let total: u64 = recipients.iter().map(|(_, amount)| amount).sum();
+
+    let compute_limit = 200_000;
+    let ixs = [
+        solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_limit(compute_limit),
+        airdrop_passes_ix(&owner.pubkey(), &recipients),
+    ];
+    let mut tx = solana_sdk::transaction::Transaction::new_with_payer(&ixs, Some(&owner.pubkey()));
+    tx.sign(&[owner], ctx.last_blockhash);
+    let tx_size = 1 + 64 * tx.signatures.len() + tx.message.serialize().len();
+    assert!(tx_size <= solana_sdk::packet::PACKET_DATA_SIZE);
+
+    let result = ctx
+        .banks_client
+        .process_transaction_with_metadata(tx)
+        .await
+        .unwrap();
+    assert_matches!(result.result, Ok(()));
+    assert!(result.metadata.unwrap().compute_units_consumed <= compute_limit as u64);
+
+    for (recipient, amount) in recipients.iter() {
+        let (passes_balance_pda, _) = get_passes_balance_pda(&owner.pubkey(), recipient);
+        let passes_balance: PassesBalance = get_account(&mut ctx, passes_balance_pda).await;
+        let expected = if *recipient == buyer.pubkey() {
+            amount + 1
+        } else {
+            *amount
+        };
+        assert_eq!(passes_balance.amount, expected);
+    }
+
+    let (passes_balance_pda, _) = get_passes_balance_pda(&owner.pubkey(), &owner.pubkey());
+    let passes_balance: PassesBalance = get_account(&mut ctx, passes_balance_pda).await;
+    assert_eq!(passes_balance.amount, 60 - 1 - total);
+
+    // the supply doesn't change
+    let (passes_supply_pda, _) = get_passes_supply_pda(&owner.pubkey());
+    let passes_supply: PassesSupply = get_account(&mut ctx, passes_supply_pda).await;
+    assert_eq!(passes_supply.amount, 60);
+
+    // one recipient over the max batch size
+    let recipients: Vec<(Pubkey, u64)> = (0..=PassesBalance::MAX_AIRDROP_RECIPIENTS)
+        .map(|_| (Pubkey::new_unique(), 1))
+        .collect();
+    let res = airdrop_passes(&mut ctx, owner, &recipients).await;
+    assert_passes_error(res, PassesError::TooManyRecipients);
+
+    // more than the owner's balance
+    let res = airdrop_passes(&mut ctx, owner, &[(Pubkey::new_unique(), 60)]).await;
+    assert_passes_error(res, PassesError::InsufficientPasses);
+
+    // a balance account that doesn't belong to the recipient
+    let mut ix = airdrop_passes_ix(&owner.pubkey(), &[(Pubkey::new_unique(), 1)]);
+    ix.accounts[7].pubkey = Pubkey::new_unique();
+    let mut tx = solana_sdk::transaction::Transaction::new_with_payer(&[ix], Some(&owner.pubkey()));
+    tx.sign(&[owner], ctx.last_blockhash);
+    let res = ctx.banks_client.process_transaction(tx).await;
+    assert_passes_error(res, PassesError::InvalidRecipients);
+
+    // lamports sent to a recipient's balance address beforehand don't block the airdrop
+    let recipient = Pubkey::new_unique();
+    let (passes_balance_pda, _) = get_passes_balance_pda(&owner.pubkey(), &recipient);
+    let lamports = solana_sdk::rent::Rent::default().minimum_balance(0);
+    let ix =
+        solana_sdk::system_instruction::transfer(&buyer.pubkey(), &passes_balance_pda, lamports);
+    let mut tx = solana_sdk::transaction::Transaction::new_with_payer(&[ix], Some(&buyer.pubkey()));
+    tx.sign(&[buyer], ctx.last_blockhash);
+    ctx.banks_client.process_transaction(tx).await.unwrap();
+
+    airdrop_passes(&mut ctx, owner, &[(recipient, 2)])
+        .await
+        .unwrap();
+    let passes_balance: PassesBalance = get_account(&mut ctx, passes_balance_pda).await;
+    assert_eq!(passes_balance.amount, 2);
+    let rent = solana_sdk::rent::Rent::default().minimum_balance(PassesBalance::LEN);
+    assert_eq!(get_lamports(&mut ctx, &passes_balance_pda).await, rent);
+
+    // the owner's last bought passes, but not the issued one
+    let res = airdrop_passes(&mut ctx, owner, &[(Pubkey::new_unique(), 7)]).await;
+    assert_passes_error(res, PassesError::IssuedPassesLocked);
+
+    // airdrops respect the wallet cap
+    set_wallet_cap(&mut ctx, owner, 2, 0).await.unwrap();
+    let res = airdrop_passes(&mut ctx, owner, &[(Pubkey::new_unique(), 3)]).await;
+    assert_passes_error(res, PassesError::WalletCapExceeded);
+}
+
+#[tokio::test]
+async fn test_holder_rewards() {
+    let (mut ctx, initializer, buyer, owner, mint) = setup().await;
+    let admin = initializer.pubkey();
+    let (escrow_wallet, _) = get_escrow_sol_wallet_pda();
+    let (reward_pool_pda, _) = get_reward_pool_pda(&owner.pubkey());
+    let (passes_supply_pda, _) = get_passes_supply_pda(&owner.pubkey());
+    let (owner_balance_pda, _) = get_passes_balance_pda(&owner.pubkey(), &owner.pubkey());
+    let (buyer_balance_pda, _) = get_passes_balance_pda(&owner.pubkey(), &buyer.pubkey());
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+    issue_passes(&mut ctx, &owner, &mint).await;
+
+    let res = set_reward_share(&mut ctx, &owner, 10_001).await;
+    assert_passes_error(res, PassesError::InvalidFee);
+    let res = set_reward_share(&mut ctx, &owner, 5_000).await;
+    assert_matches!(res, Ok(()));
+
+    let passes_supply: PassesSupply = get_account(&mut ctx, passes_supply_pda).await;
+    assert!(passes_supply.rewards);
+    let reward_pool: RewardPool = get_account(&mut ctx, reward_pool_pda).await;
+    assert_eq!(reward_pool.holder_fee_bps, 5_000);
+
+    // trades must carry the reward pool once holders share rewards
+    let args = instruction::BuyPassesSol {
+        amount: 10,
+        proof: vec![],
+    };
+    let accounts = accounts::BuyPassesSol {
+        buyer: buyer.pubkey(),
+        passes_supply: passes_supply_pda,
+        passes_balance: Some(buyer_balance_pda),
+        holder_page: None,
+        reward_pool: None,
+        config: get_config_pda().0,
+        escrow_wallet,
+        reserve_wallet: None,
+        passes_owner: owner.pubkey(),
+        protocol_fee_wallet: admin,
+        system_program: system_program::ID,
+    };
+    let res = execute_tx(&mut ctx, accounts.to_account_metas(None), &args, &buyer).await;
+    assert_passes_error(res, PassesError::RewardPoolMissing);
+
+    let escrow_lamports_before = get_lamports(&mut ctx, &escrow_wallet).await;
+    let owner_lamports_before = get_lamports(&mut ctx, &owner.pubkey()).await;
+
+    // half of the owner fees go to the owner's pass, the only one held before the buy
+    buy_passes_sol(&mut ctx, &buyer, &owner.pubkey(), &admin, &mint, 10).await;
+    assert_eq!(
+        get_lamports(&mut ctx, &owner.pubkey()).await,
+        owner_lamports_before + 2_406_250 - 1_203_125
+    );
+
+    // deposits are shared by the 11 passes
+    let res = deposit_rewards_sol(&mut ctx, &initializer, &owner.pubkey(), 11_000).await;
+    assert_matches!(res, Ok(()));
+
+    sell_passes_sol(&mut ctx, &buyer, &owner.pubkey(), &admin, &mint, 5).await;
+
+    // the buyer earned on 10 passes until the sell
+    let buyer_lamports_before = get_lamports(&mut ctx, &buyer.pubkey()).await;
+    let res = claim_rewards_sol(&mut ctx, &buyer, &owner.pubkey()).await;
+    assert_matches!(res, Ok(()));
+    assert_eq!(
+        get_lamports(&mut ctx, &buyer.pubkey()).await,
+        buyer_lamports_before + 10 * (1_000 + 93_750) - 5000
+    );
+    let passes_balance: PassesBalance = get_account(&mut ctx, buyer_balance_pda).await;
+    assert_eq!(passes_balance.pending_sol_rewards, 0);
+
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let res = claim_rewards_sol(&mut ctx, &buyer, &owner.pubkey()).await;
+    assert_passes_error(res, PassesError::ZeroAmount);
+
+    let owner_lamports_before = get_lamports(&mut ctx, &owner.pubkey()).await;
+    let res = claim_rewards_sol(&mut ctx, &owner, &owner.pubkey()).await;
+    assert_matches!(res, Ok(()));
+    assert_eq!(
+        get_lamports(&mut ctx, &owner.pubkey()).await,
+        owner_lamports_before + 1_203_125 + 1_000 + 93_750 - 5000
+    );
+
+    // every reward was paid out, the escrow keeps exactly the value of the passes left
+    assert_eq!(
+        get_lamports(&mut ctx, &escrow_wallet).await,
+        escrow_lamports_before + 240_625_000 - 206_250_000
+    );
+
+    // token rewards accrue on the current balances
+    let res = deposit_rewards(&mut ctx, &buyer, &owner.pubkey(), &mint, 6_000).await;
+    assert_matches!(res, Ok(()));
+
+    // holders claim before their passes move to a page
+    let res = create_holder_page(&mut ctx, &owner, &owner.pubkey(), 0).await;
+    assert_matches!(res, Ok(()));
+    let res = convert_to_holder_page(&mut ctx, &owner, &owner.pubkey(), 0).await;
+    assert_passes_error(res, PassesError::UnclaimedRewards);
+
+    let owner_wallet =
+        anchor_spl::associated_token::get_associated_token_address(&owner.pubkey(), &mint);
+    let owner_tokens_before = get_token_account_balance(&mut ctx, owner_wallet).await;
+    let res = claim_rewards(&mut ctx, &owner, &owner.pubkey(), &mint).await;
+    assert_matches!(res, Ok(()));
+    assert_eq!(
+        get_token_account_balance(&mut ctx, owner_wallet).await,
+        owner_tokens_before + 1_000
+    );
+
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let res = convert_to_holder_page(&mut ctx, &owner, &owner.pubkey(), 0).await;
+    assert_matches!(res, Ok(()));
+    let passes_supply: PassesSupply = get_account(&mut ctx, passes_supply_pda).await;
+    assert_eq!(passes_supply.paged_amount, 1);
+    assert_eq!(passes_supply.rewarded_supply(), 5);
 
-    // check byuer's pass balance of owner's pass
-    let (passes_balance_pda, _) = get_passes_balance_pda(&owner.pubkey(), &buyer.pubkey());
-    let passes_balance: PassesBalance = get_account(&mut ctx, passes_balance_pda).await;
-    assert_eq!(passes_balance.amount, 5);
+    // paged passes don't earn
+    let res = deposit_rewards(&mut ctx, &buyer, &owner.pubkey(), &mint, 5_000).await;
+    assert_matches!(res, Ok(()));
 
-    // check protocol fee wallet
-    let protocol_fee_wallet =
-        anchor_spl::associated_token::get_associated_token_address(&initializer.pubkey(), &mint);
-    let account = get_token_account(&mut ctx, protocol_fee_wallet).await;
-    // assert_eq!(account.amount, 24062 + 20625);
-    assert_eq!(account.amount, 24063 + 20625);
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let res = convert_from_holder_page(&mut ctx, &owner, &owner.pubkey(), 0).await;
+    assert_matches!(res, Ok(()));
+    let passes_supply: PassesSupply = get_account(&mut ctx, passes_supply_pda).await;
+    assert_eq!(passes_supply.paged_amount, 0);
 
-    // check owner fee wallet
-    let owner_fee_wallet =
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let res = claim_rewards(&mut ctx, &owner, &owner.pubkey(), &mint).await;
+    assert_passes_error(res, PassesError::ZeroAmount);
+
+    let buyer_wallet =
+        anchor_spl::associated_token::get_associated_token_address(&buyer.pubkey(), &mint);
+    let buyer_tokens_before = get_token_account_balance(&mut ctx, buyer_wallet).await;
+    let res = claim_rewards(&mut ctx, &buyer, &owner.pubkey(), &mint).await;
+    assert_matches!(res, Ok(()));
+    assert_eq!(
+        get_token_account_balance(&mut ctx, buyer_wallet).await,
+        buyer_tokens_before + 5_000 + 5_000
+    );
+}
+
+#[tokio::test]
+async fn test_staking() {
+    let (mut ctx, initializer, buyer, owner, mint) = setup().await;
+    let admin = initializer.pubkey();
+    let (stake_pool_pda, _) = get_stake_pool_pda(&owner.pubkey());
+    let (buyer_stake_pda, _) = get_passes_stake_pda(&owner.pubkey(), &buyer.pubkey());
+    let (buyer_balance_pda, _) = get_passes_balance_pda(&owner.pubkey(), &buyer.pubkey());
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+    issue_passes(&mut ctx, &owner, &mint).await;
+    buy_passes_sol(&mut ctx, &buyer, &owner.pubkey(), &admin, &mint, 10).await;
+
+    let now = get_unix_timestamp(&mut ctx).await;
+    let res = stake_passes(&mut ctx, &buyer, &owner.pubkey(), 11).await;
+    assert_passes_error(res, PassesError::InsufficientPasses);
+    let res = stake_passes(&mut ctx, &buyer, &owner.pubkey(), 4).await;
+    assert_matches!(res, Ok(()));
+
+    let passes_balance: PassesBalance = get_account(&mut ctx, buyer_balance_pda).await;
+    assert_eq!(passes_balance.amount, 10);
+    assert_eq!(passes_balance.staked_amount, 4);
+
+    // staked passes can't be sold or moved to a holder page
+    let res = try_sell_passes_sol(&mut ctx, &buyer, &owner.pubkey(), &admin, &mint, 7).await;
+    assert_passes_error(res, PassesError::PassesStaked);
+    let res = create_holder_page(&mut ctx, &owner, &owner.pubkey(), 0).await;
+    assert_matches!(res, Ok(()));
+    let res = convert_to_holder_page(&mut ctx, &buyer, &owner.pubkey(), 0).await;
+    assert_passes_error(res, PassesError::PassesStaked);
+
+    // the owner funds 10_000 over 100 seconds
+    let owner_wallet =
         anchor_spl::associated_token::get_associated_token_address(&owner.pubkey(), &mint);
-    let account = get_token_account(&mut ctx, owner_fee_wallet).await;
-    // assert_eq!(account.amount, 24062 + 20625);
-    assert_eq!(account.amount, 24063 + 20625);
+    ctx.mint_tokens(&initializer, &mint, &owner_wallet, 10_000)
+        .await
+        .unwrap();
+    let res = fund_stake_rewards(&mut ctx, &owner, &mint, 10_000, 0).await;
+    assert_passes_error(res, PassesError::InvalidStakeRewards);
+    let res = fund_stake_rewards(&mut ctx, &owner, &mint, 10_000, 100).await;
+    assert_matches!(res, Ok(()));
 
-    // check buyer balance after purchase and ...
+    let stake_pool: StakePool = get_account(&mut ctx, stake_pool_pda).await;
+    assert_eq!(stake_pool.total_staked, 4);
+    assert_eq!(stake_pool.reward_rate, 100);
+    assert_eq!(stake_pool.reward_end_ts, now + 100);
+
+    // the buyer earns everything while staking alone
+    set_unix_timestamp(&mut ctx, now + 50).await;
     let buyer_wallet =
         anchor_spl::associated_token::get_associated_token_address(&buyer.pubkey(), &mint);
-    let account = get_token_account(&mut ctx, buyer_wallet).await;
+    let buyer_tokens_before = get_token_account_balance(&mut ctx, buyer_wallet).await;
+    let res = claim_stake_rewards(&mut ctx, &buyer, &owner.pubkey(), &mint).await;
+    assert_matches!(res, Ok(()));
     assert_eq!(
-        account.amount,
-        5_u64 * ONE_USDC - 2406250 - 24063 - 24063 + 2062500 - 20625 - 20625
+        get_token_account_balance(&mut ctx, buyer_wallet).await,
+        buyer_tokens_before + 5_000
+    );
+
+    // then shares with the owner's pass 4 to 1
+    let res = stake_passes(&mut ctx, &owner, &owner.pubkey(), 1).await;
+    assert_matches!(res, Ok(()));
+
+    // nothing accrues past the funded period
+    set_unix_timestamp(&mut ctx, now + 150).await;
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let res = claim_stake_rewards(&mut ctx, &buyer, &owner.pubkey(), &mint).await;
+    assert_matches!(res, Ok(()));
+    assert_eq!(
+        get_token_account_balance(&mut ctx, buyer_wallet).await,
+        buyer_tokens_before + 5_000 + 4_000
+    );
+    let res = claim_stake_rewards(&mut ctx, &owner, &owner.pubkey(), &mint).await;
+    assert_matches!(res, Ok(()));
+    assert_eq!(
+        get_token_account_balance(&mut ctx, owner_wallet).await,
+        1_000
     );
+
+    // points keep growing with time
+    let passes_stake: PassesStake = get_account(&mut ctx, buyer_stake_pda).await;
+    assert_eq!(passes_stake.points, 4 * 150);
+
+    let res = unstake_passes(&mut ctx, &buyer, &owner.pubkey(), 5).await;
+    assert_passes_error(res, PassesError::InsufficientPasses);
+    let res = unstake_passes(&mut ctx, &buyer, &owner.pubkey(), 4).await;
+    assert_matches!(res, Ok(()));
+
+    let stake_pool: StakePool = get_account(&mut ctx, stake_pool_pda).await;
+    assert_eq!(stake_pool.total_staked, 1);
+    let passes_balance: PassesBalance = get_account(&mut ctx, buyer_balance_pda).await;
+    assert_eq!(passes_balance.staked_amount, 0);
+
+    ctx.get_new_latest_blockhash().await.unwrap();
+    sell_passes_sol(&mut ctx, &buyer, &owner.pubkey(), &admin, &mint, 10).await;
 }
 
 #[tokio::test]
-async fn test_buy_passes_w_sol() {
+async fn test_verify_holding() {
     let (mut ctx, initializer, buyer, owner, mint) = setup().await;
+    let admin = initializer.pubkey();
 
-    // get all pda s
-    let (escrow_wallet, _) = get_escrow_sol_wallet_pda();
-    let (passes_supply_pda, _) = get_passes_supply_pda(&owner.pubkey());
-    let (passes_balance_pda, _) = get_passes_balance_pda(&owner.pubkey(), &buyer.pubkey());
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+    issue_passes(&mut ctx, &owner, &mint).await;
+    buy_passes_sol(&mut ctx, &buyer, &owner.pubkey(), &admin, &mint, 10).await;
+
+    let stranger = Pubkey::new_unique();
+    for (holder, min_amount, expected) in [
+        (buyer.pubkey(), 10, true),
+        (buyer.pubkey(), 11, false),
+        (owner.pubkey(), 1, true),
+        (stranger, 0, true),
+        (stranger, 1, false),
+    ] {
+        let args = instruction::VerifyHolding { min_amount };
+        let accounts = accounts::VerifyHolding {
+            config: get_config_pda().0,
+            passes_balance: gate::passes_balance_address(0, &owner.pubkey(), &holder),
+            holder_page: None,
+            holder,
+            passes_owner: owner.pubkey(),
+        };
+        let res: std::result::Result<bool, _> =
+            simulate_tx(&mut ctx, accounts.to_account_metas(None), &args, &buyer).await;
+        assert_matches!(res, Ok(holds) if holds == expected);
+    }
+
+    // another holder's balance account is rejected
+    let args = instruction::VerifyHolding { min_amount: 1 };
+    let accounts = accounts::VerifyHolding {
+        config: get_config_pda().0,
+        passes_balance: gate::passes_balance_address(0, &owner.pubkey(), &buyer.pubkey()),
+        holder_page: None,
+        holder: stranger,
+        passes_owner: owner.pubkey(),
+    };
+    let res = execute_tx(&mut ctx, accounts.to_account_metas(None), &args, &buyer).await;
+    assert!(res.is_err());
+
+    // a balance written by the version 1 program still counts before it is migrated
+    let (buyer_balance_pda, _) = get_passes_balance_pda(&owner.pubkey(), &buyer.pubkey());
+    let passes_balance: PassesBalance = get_account(&mut ctx, buyer_balance_pda).await;
+    set_v0_account(
+        &mut ctx,
+        &buyer_balance_pda,
+        PassesBalance::DISCRIMINATOR,
+        &PassesBalance {
+            version: 1,
+            ..passes_balance
+        },
+        PassesBalance::V1_LEN,
+    );
+    let res = verify_holding(&mut ctx, &buyer, &owner.pubkey(), None, 10).await;
+    assert_matches!(res, Ok(true));
+
+    let args = instruction::MigrateBalance {};
+    let accounts = accounts::MigrateBalance {
+        payer: buyer.pubkey(),
+        config: get_config_pda().0,
+        passes_balance: buyer_balance_pda,
+        passes_owner: owner.pubkey(),
+        holder: buyer.pubkey(),
+        system_program: system_program::ID,
+    };
+    let res = execute_tx(&mut ctx, accounts.to_account_metas(None), &args, &buyer).await;
+    assert_matches!(res, Ok(()));
+
+    // passes moved into a holder page count when the page is given
+    let res = create_holder_page(&mut ctx, &owner, &owner.pubkey(), 0).await;
+    assert_matches!(res, Ok(()));
+    let res = convert_to_holder_page(&mut ctx, &buyer, &owner.pubkey(), 0).await;
+    assert_matches!(res, Ok(()));
+    buy_passes_sol(&mut ctx, &buyer, &owner.pubkey(), &admin, &mint, 1).await;
+
+    let res = verify_holding(&mut ctx, &buyer, &owner.pubkey(), None, 2).await;
+    assert_matches!(res, Ok(false));
+    let res = verify_holding(&mut ctx, &buyer, &owner.pubkey(), Some(0), 11).await;
+    assert_matches!(res, Ok(true));
+    let res = verify_holding(&mut ctx, &buyer, &owner.pubkey(), Some(0), 12).await;
+    assert_matches!(res, Ok(false));
+
+    // a page of another market is rejected
+    let args = instruction::VerifyHolding { min_amount: 1 };
+    let accounts = accounts::VerifyHolding {
+        config: get_config_pda().0,
+        passes_balance: gate::passes_balance_address(0, &buyer.pubkey(), &buyer.pubkey()),
+        holder_page: Some(get_holder_page_pda(&owner.pubkey(), 0).0),
+        holder: buyer.pubkey(),
+        passes_owner: buyer.pubkey(),
+    };
+    let res = execute_tx(&mut ctx, accounts.to_account_metas(None), &args, &buyer).await;
+    assert!(res.is_err());
+}
+
+#[tokio::test]
+async fn test_holder_page() {
+    let (mut ctx, initializer, buyer, owner, mint) = setup().await;
 
     init_passes(
         &mut ctx,
@@ -399,12 +3691,7 @@ async fn test_buy_passes_w_sol() {
 
     issue_passes(&mut ctx, &owner, &mint).await;
 
-    let escrow_wallet_lamports_before = get_lamports(&mut ctx, &escrow_wallet).await;
-    let protocol_fee_wallet_lamports_before = get_lamports(&mut ctx, &initializer.pubkey()).await;
-    let owner_lamports_before = get_lamports(&mut ctx, &owner.pubkey()).await;
-
-    // buyer buy passes
-    buy_passes_sol(
+    buy_passes(
         &mut ctx,
         &buyer,
         &owner.pubkey(),
@@ -414,37 +3701,369 @@ async fn test_buy_passes_w_sol() {
     )
     .await;
 
-    let passes_supply: PassesSupply = get_account(&mut ctx, passes_supply_pda).await;
-    assert_eq!(passes_supply.amount, 11);
+    // the owner creates a page for their holders
+    let res = create_holder_page(&mut ctx, &owner, &owner.pubkey(), 0).await;
+    assert_matches!(res, Ok(()));
+
+    let (holder_page_pda, _) = get_holder_page_pda(&owner.pubkey(), 0);
+    let (passes_balance_pda, _) = get_passes_balance_pda(&owner.pubkey(), &buyer.pubkey());
+    let balance_rent = get_lamports(&mut ctx, &passes_balance_pda).await;
+    let buyer_lamports_before = get_lamports(&mut ctx, &buyer.pubkey()).await;
+
+    // the buyer moves their passes into the page and gets the balance rent back
+    let res = convert_to_holder_page(&mut ctx, &buyer, &owner.pubkey(), 0).await;
+    assert_matches!(res, Ok(()));
+
+    let account = ctx
+        .banks_client
+        .get_account(passes_balance_pda)
+        .await
+        .unwrap();
+    assert!(account.is_none());
+    assert_eq!(
+        get_lamports(&mut ctx, &buyer.pubkey()).await,
+        buyer_lamports_before + balance_rent - 5000
+    );
+
+    let res = convert_to_holder_page(&mut ctx, &owner, &owner.pubkey(), 0).await;
+    assert_matches!(res, Ok(()));
+
+    let holder_page = get_holder_page(&mut ctx, holder_page_pda).await;
+    assert_eq!(holder_page.passes_owner, owner.pubkey());
+    assert_eq!(holder_page.len, 2);
+    assert_eq!(holder_page.holders[0], buyer.pubkey());
+    assert_eq!(holder_page.amounts[0], 10);
+    assert_eq!(holder_page.holders[1], owner.pubkey());
+    assert_eq!(holder_page.amounts[1], 1);
+
+    // paged passes must be moved back before selling
+    let res = try_sell_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        5,
+    )
+    .await;
+    assert!(res.is_err());
+
+    let res = convert_from_holder_page(&mut ctx, &buyer, &owner.pubkey(), 0).await;
+    assert_matches!(res, Ok(()));
 
     let passes_balance: PassesBalance = get_account(&mut ctx, passes_balance_pda).await;
     assert_eq!(passes_balance.amount, 10);
 
+    let holder_page = get_holder_page(&mut ctx, holder_page_pda).await;
+    assert_eq!(holder_page.len, 1);
+    assert_eq!(holder_page.holders[0], owner.pubkey());
+    assert_eq!(holder_page.amounts[0], 1);
+
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let res = convert_from_holder_page(&mut ctx, &buyer, &owner.pubkey(), 0).await;
+    assert_passes_error(res, PassesError::HolderNotInPage);
+
+    sell_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        5,
+    )
+    .await;
+
+    let passes_supply: PassesSupply =
+        get_account(&mut ctx, get_passes_supply_pda(&owner.pubkey()).0).await;
+    assert_eq!(passes_supply.amount, 6);
+}
+
+#[tokio::test]
+async fn test_buy_into_holder_page() {
+    let (mut ctx, initializer, buyer, owner, mint) = setup().await;
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+
+    issue_passes(&mut ctx, &owner, &mint).await;
+
+    let res = create_holder_page(&mut ctx, &owner, &owner.pubkey(), 0).await;
+    assert_matches!(res, Ok(()));
+
+    let (holder_page_pda, _) = get_holder_page_pda(&owner.pubkey(), 0);
+    let (passes_balance_pda, _) = get_passes_balance_pda(&owner.pubkey(), &buyer.pubkey());
+    // the buy needs exactly one of the balance and the page
+    let args = instruction::BuyPasses {
+        amount: 3,
+        proof: vec![],
+    };
+    let both = accounts::BuyPasses {
+        holder_page: Some(holder_page_pda),
+        ..buy_passes_accounts(
+            &mut ctx,
+            &buyer.pubkey(),
+            &owner.pubkey(),
+            &initializer.pubkey(),
+            &mint,
+        )
+        .await
+    };
+    let res = execute_tx(&mut ctx, both.to_account_metas(None), &args, &buyer).await;
+    assert_passes_error(res, PassesError::InvalidBuyerHolding);
+
+    let neither = accounts::BuyPasses {
+        passes_balance: None,
+        ..buy_passes_accounts(
+            &mut ctx,
+            &buyer.pubkey(),
+            &owner.pubkey(),
+            &initializer.pubkey(),
+            &mint,
+        )
+        .await
+    };
+    let res = execute_tx(&mut ctx, neither.to_account_metas(None), &args, &buyer).await;
+    assert_passes_error(res, PassesError::InvalidBuyerHolding);
+
+    // buying straight into the page doesn't create a balance account
+    let into_page = accounts::BuyPasses {
+        passes_balance: None,
+        holder_page: Some(holder_page_pda),
+        ..buy_passes_accounts(
+            &mut ctx,
+            &buyer.pubkey(),
+            &owner.pubkey(),
+            &initializer.pubkey(),
+            &mint,
+        )
+        .await
+    };
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let res = execute_tx(&mut ctx, into_page.to_account_metas(None), &args, &buyer).await;
+    assert_matches!(res, Ok(()));
+
+    let account = ctx
+        .banks_client
+        .get_account(passes_balance_pda)
+        .await
+        .unwrap();
+    assert!(account.is_none());
+
+    let holder_page = get_holder_page(&mut ctx, holder_page_pda).await;
+    assert_eq!(holder_page.len, 1);
+    assert_eq!(holder_page.holders[0], buyer.pubkey());
+    assert_eq!(holder_page.amounts[0], 3);
+
+    // a second buy adds to the buyer's entry
+    let args = instruction::BuyPasses {
+        amount: 2,
+        proof: vec![],
+    };
+    let res = execute_tx(&mut ctx, into_page.to_account_metas(None), &args, &buyer).await;
+    assert_matches!(res, Ok(()));
+
+    let holder_page = get_holder_page(&mut ctx, holder_page_pda).await;
+    assert_eq!(holder_page.len, 1);
+    assert_eq!(holder_page.amounts[0], 5);
+
+    let passes_supply: PassesSupply =
+        get_account(&mut ctx, get_passes_supply_pda(&owner.pubkey()).0).await;
+    assert_eq!(passes_supply.amount, 6);
+    assert_eq!(passes_supply.paged_amount, 5);
+
+    // the paged passes come back to a balance before being sold
+    let res = convert_from_holder_page(&mut ctx, &buyer, &owner.pubkey(), 0).await;
+    assert_matches!(res, Ok(()));
+
+    sell_passes(
+        &mut ctx,
+        &buyer,
+        &owner.pubkey(),
+        &initializer.pubkey(),
+        &mint,
+        5,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_sell_issued_passes() {
+    let (mut ctx, initializer, buyer, owner, mint) = setup().await;
+    let admin = initializer.pubkey();
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+    try_issue_passes_with_params(&mut ctx, &owner, &mint, 5, IssuanceParams::default())
+        .await
+        .unwrap();
+    let passes_supply: PassesSupply =
+        get_account(&mut ctx, get_passes_supply_pda(&owner.pubkey()).0).await;
+    assert_eq!(passes_supply.issued_amount, 5);
+
+    // nobody paid for the issued passes, so the owner can't sell them into what buyers paid
+    let res = try_sell_passes(&mut ctx, &owner, &owner.pubkey(), &admin, &mint, 1).await;
+    assert_passes_error(res, PassesError::IssuedPassesLocked);
+
+    buy_passes(&mut ctx, &buyer, &owner.pubkey(), &admin, &mint, 2).await;
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let res = try_sell_passes(&mut ctx, &owner, &owner.pubkey(), &admin, &mint, 1).await;
+    assert_passes_error(res, PassesError::IssuedPassesLocked);
+
+    // the buyer sells everything they bought and the reserve is empty again
+    sell_passes(&mut ctx, &buyer, &owner.pubkey(), &admin, &mint, 2).await;
     assert_eq!(
-        get_lamports(&mut ctx, &escrow_wallet).await,
-        escrow_wallet_lamports_before + 240_625_000
+        get_token_account_balance(&mut ctx, get_escrow_token_wallet_pda(&mint).0).await,
+        0
+    );
+}
+
+// Both curves share a market's supply, so a market keeps to the currency of its first trade and
+// passes bought with SOL can't sell for the tokens other markets put in the escrow
+#[tokio::test]
+async fn test_mixed_currency_solvency() {
+    let (mut ctx, initializer, users, mint) = setup_users(3).await;
+    let admin = initializer.pubkey();
+
+    init_passes(
+        &mut ctx,
+        &initializer,
+        &mint,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
+    )
+    .await;
+    let (owner, creator, buyer) = (&users[0], &users[1], &users[2]);
+    issue_passes(&mut ctx, owner, &mint).await;
+    issue_passes(&mut ctx, creator, &mint).await;
+
+    // the creator's market keeps its reserve in the token escrow
+    buy_passes(&mut ctx, buyer, &creator.pubkey(), &admin, &mint, 5).await;
+    let escrow_token_wallet = get_escrow_token_wallet_pda(&mint).0;
+    let creator_reserve = crate::curve_price(1, 5).unwrap();
+    assert_eq!(
+        get_token_account_balance(&mut ctx, escrow_token_wallet).await,
+        creator_reserve
     );
 
+    // the owner's market trades in SOL after its first buy
+    buy_passes_sol(&mut ctx, buyer, &owner.pubkey(), &admin, &mint, 5).await;
+    let passes_supply: PassesSupply =
+        get_account(&mut ctx, get_passes_supply_pda(&owner.pubkey()).0).await;
+    assert_eq!(passes_supply.currency, MarketCurrency::Sol);
+    let res = try_sell_passes(&mut ctx, buyer, &owner.pubkey(), &admin, &mint, 5).await;
+    assert_passes_error(res, PassesError::CurrencyMismatch);
+    let res = try_buy_passes(&mut ctx, buyer, &owner.pubkey(), &admin, &mint, 1).await;
+    assert_passes_error(res, PassesError::CurrencyMismatch);
+    let res = try_sell_passes_sol(&mut ctx, buyer, &creator.pubkey(), &admin, &mint, 5).await;
+    assert_passes_error(res, PassesError::CurrencyMismatch);
+
+    // so the creator's market keeps its reserve
     assert_eq!(
-        get_lamports(&mut ctx, &initializer.pubkey()).await,
-        protocol_fee_wallet_lamports_before + 2_406_250
+        get_token_account_balance(&mut ctx, escrow_token_wallet).await,
+        creator_reserve
+    );
+    sell_passes_sol(&mut ctx, buyer, &owner.pubkey(), &admin, &mint, 5).await;
+    sell_passes(&mut ctx, buyer, &creator.pubkey(), &admin, &mint, 5).await;
+    assert_eq!(
+        get_token_account_balance(&mut ctx, escrow_token_wallet).await,
+        0
     );
+}
+
+// Users of the trade flow fuzzer, each of them can issue a market
+const FUZZ_USERS: usize = 4;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum FuzzOp {
+    Issue {
+        owner: usize,
+        amount: u64,
+        price_offset: u64,
+        isolated_reserve: bool,
+    },
+    Buy {
+        owner: usize,
+        buyer: usize,
+        sol: bool,
+        amount: u64,
+    },
+    Sell {
+        owner: usize,
+        seller: usize,
+        sol: bool,
+        amount: u64,
+    },
+}
 
-    assert_eq!(
-        get_lamports(&mut ctx, &owner.pubkey()).await,
-        owner_lamports_before + 2_406_250
-    );
+fn fuzz_op() -> impl Strategy<Value = FuzzOp> {
+    let user = || 0..FUZZ_USERS;
+    prop_oneof![
+        1 => (user(), 1..=5u64, 0..=10u64, any::<bool>()).prop_map(
+            |(owner, amount, price_offset, isolated_reserve)| FuzzOp::Issue {
+                owner,
+                amount,
+                price_offset,
+                isolated_reserve,
+            }
+        ),
+        4 => (user(), user(), any::<bool>(), 1..=5u64)
+            .prop_map(|(owner, buyer, sol, amount)| FuzzOp::Buy { owner, buyer, sol, amount }),
+        3 => (user(), user(), any::<bool>(), 1..=5u64)
+            .prop_map(|(owner, seller, sol, amount)| FuzzOp::Sell { owner, seller, sol, amount }),
+    ]
 }
 
-#[tokio::test]
-async fn test_sell_passes_w_sol() {
-    let (mut ctx, initializer, buyer, owner, mint) = setup().await;
+proptest! {
+    #![proptest_config(ProptestConfig { cases: 16, max_shrink_iters: 64, ..ProptestConfig::default() })]
 
-    // get all pda s
-    let (escrow_wallet, _) = get_escrow_sol_wallet_pda();
-    let (passes_supply_pda, _) = get_passes_supply_pda(&owner.pubkey());
-    let (passes_balance_pda, _) = get_passes_balance_pda(&owner.pubkey(), &buyer.pubkey());
+    #[test]
+    fn prop_trade_flows(ops in proptest::collection::vec(fuzz_op(), 1..40)) {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(run_trade_flows(ops, false));
+    }
 
+    // Trades in the other currency than a market's first one fail
+    #[test]
+    fn prop_mixed_currency_trade_flows(ops in proptest::collection::vec(fuzz_op(), 1..40)) {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(run_trade_flows(ops, true));
+    }
+}
+
+// Identical transactions need a new blockhash, `sent` holds the ops sent with the current one
+async fn refresh_if_sent(ctx: &mut ProgramTestContext, sent: &mut HashSet<FuzzOp>, op: FuzzOp) {
+    if sent.contains(&op) {
+        ctx.get_new_latest_blockhash().await.unwrap();
+        sent.clear();
+    }
+    sent.insert(op);
+}
+
+// Run the ops and check the invariants after each of them: a market's supply is the sum of its
+// balances, the escrows and isolated reserves hold exactly the net volume of their markets, which
+// never pays out more than a market took in and can buy back every pass above the issued ones.
+// Unless `mixed`, the ops keep to the currency of a market's first trade, otherwise the trades in
+// the other currency must fail.
+async fn run_trade_flows(ops: Vec<FuzzOp>, mixed: bool) {
+    let (mut ctx, initializer, users, mint) = setup_users(FUZZ_USERS).await;
     init_passes(
         &mut ctx,
         &initializer,
@@ -453,69 +4072,283 @@ async fn test_sell_passes_w_sol() {
         OWNER_FEE_BPS,
     )
     .await;
+    let admin = initializer.pubkey();
+    let (escrow_token_wallet, _) = get_escrow_token_wallet_pda(&mint);
+    let (escrow_sol_wallet, _) = get_escrow_sol_wallet_pda();
+    let escrow_sol_rent = get_lamports(&mut ctx, &escrow_sol_wallet).await;
+
+    // the issued markets and the currency they trade in, true for SOL
+    let mut markets: HashMap<usize, Option<bool>> = HashMap::new();
+    let mut sent = HashSet::new();
+
+    for op in ops {
+        match op {
+            FuzzOp::Issue {
+                owner,
+                amount,
+                price_offset,
+                isolated_reserve,
+            } => {
+                if markets.contains_key(&owner) {
+                    continue;
+                }
+                let params = IssuanceParams {
+                    price_offset,
+                    isolated_reserve,
+                    ..Default::default()
+                };
+                let res =
+                    try_issue_passes_with_params(&mut ctx, &users[owner], &mint, amount, params)
+                        .await;
+                assert_matches!(res, Ok(()));
+                markets.insert(owner, None);
+            }
+            FuzzOp::Buy {
+                owner,
+                buyer,
+                sol,
+                amount,
+            } => {
+                let Some(trades_sol) = markets.get_mut(&owner) else {
+                    continue;
+                };
+                let sol = match *trades_sol {
+                    Some(first) if !mixed => first,
+                    _ => sol,
+                };
+                let pinned = *trades_sol.get_or_insert(sol);
+                refresh_if_sent(
+                    &mut ctx,
+                    &mut sent,
+                    FuzzOp::Buy {
+                        owner,
+                        buyer,
+                        sol,
+                        amount,
+                    },
+                )
+                .await;
+                let owner = users[owner].pubkey();
+                let res = if sol {
+                    try_buy_passes_sol(&mut ctx, &users[buyer], &owner, &admin, &mint, amount).await
+                } else {
+                    try_buy_passes(&mut ctx, &users[buyer], &owner, &admin, &mint, amount).await
+                };
+                if pinned != sol {
+                    assert_passes_error(res, PassesError::CurrencyMismatch);
+                } else {
+                    assert_matches!(res, Ok(()));
+                }
+            }
+            FuzzOp::Sell {
+                owner,
+                seller,
+                sol,
+                amount,
+            } => {
+                let Some(&trades_sol) = markets.get(&owner) else {
+                    continue;
+                };
+                let sol = match trades_sol {
+                    Some(first) if !mixed => first,
+                    _ => sol,
+                };
+                let owner_key = users[owner].pubkey();
+                let held = find_passes_balance(&mut ctx, &owner_key, &users[seller].pubkey()).await;
+                let amount = amount.min(held);
+                if amount == 0 {
+                    continue;
+                }
+                let op = FuzzOp::Sell {
+                    owner,
+                    seller,
+                    sol,
+                    amount,
+                };
+                refresh_if_sent(&mut ctx, &mut sent, op).await;
+                let owner_index = owner;
+                let owner = owner_key;
+                let supply: PassesSupply =
+                    get_account(&mut ctx, get_passes_supply_pda(&owner).0).await;
+                let res = if sol {
+                    try_sell_passes_sol(&mut ctx, &users[seller], &owner, &admin, &mint, amount)
+                        .await
+                } else {
+                    try_sell_passes(&mut ctx, &users[seller], &owner, &admin, &mint, amount).await
+                };
+                if supply.amount <= amount {
+                    assert_passes_error(res, PassesError::LastPass);
+                } else if trades_sol.is_some_and(|first| first != sol) {
+                    assert_passes_error(res, PassesError::CurrencyMismatch);
+                } else if seller == owner_index && held < amount + supply.issued_amount {
+                    assert_passes_error(res, PassesError::IssuedPassesLocked);
+                } else {
+                    assert_matches!(res, Ok(()));
+                    markets.insert(owner_index, Some(sol));
+                }
+            }
+        }
 
-    issue_passes(&mut ctx, &owner, &mint).await;
+        // the net volumes the escrows hold for the markets without an isolated reserve
+        let mut escrow_token_net = 0;
+        let mut escrow_sol_net = 0;
+        for (&owner, trades_sol) in &markets {
+            let owner = users[owner].pubkey();
+            let passes_supply: PassesSupply =
+                get_account(&mut ctx, get_passes_supply_pda(&owner).0).await;
+
+            let mut balances = 0;
+            for user in &users {
+                balances += find_passes_balance(&mut ctx, &owner, &user.pubkey()).await;
+            }
+            assert_eq!(passes_supply.amount, balances);
+
+            // the market never paid out more than it took in
+            let stats = &passes_supply.stats;
+            let token_net = stats.buy_volume.checked_sub(stats.sell_volume);
+            let sol_net = stats.buy_volume_sol.checked_sub(stats.sell_volume_sol);
+            let (Some(token_net), Some(sol_net)) = (token_net, sol_net) else {
+                panic!("{} paid out more than it took in: {:?}", owner, stats);
+            };
+
+            // and can buy back every pass above the issued ones in the currency it trades in
+            let curve_supply = passes_supply
+                .curve_supply(passes_supply.issued_amount)
+                .unwrap();
+            let bought = passes_supply.amount - passes_supply.issued_amount;
+            let currency = match *trades_sol {
+                None => MarketCurrency::Unset,
+                Some(false) => MarketCurrency::Token,
+                Some(true) => MarketCurrency::Sol,
+            };
+            assert_eq!(passes_supply.currency, currency);
+            if let Some(sol) = *trades_sol {
+                let (net, owed, other_net) = if sol {
+                    let owed = crate::curve_price_sol(curve_supply, bought).unwrap();
+                    (sol_net, owed, token_net)
+                } else {
+                    let owed = crate::curve_price(curve_supply, bought).unwrap();
+                    (token_net, owed, sol_net)
+                };
+                assert_eq!(net, owed, "{} holds {} and owes {}", owner, net, owed);
+                assert_eq!(other_net, 0);
+            }
+
+            if !passes_supply.isolated_reserve {
+                escrow_token_net += token_net;
+                escrow_sol_net += sol_net;
+                continue;
+            }
+            let reserve_wallet = get_reserve_token_wallet_pda(&owner).0;
+            let reserve = get_token_account_balance(&mut ctx, reserve_wallet).await;
+            assert_eq!(reserve, token_net);
+            let reserve_wallet = get_reserve_sol_wallet_pda(&owner).0;
+            let reserve = get_lamports(&mut ctx, &reserve_wallet).await - escrow_sol_rent;
+            assert_eq!(reserve, sol_net);
+        }
+        let escrow_token = get_token_account_balance(&mut ctx, escrow_token_wallet).await;
+        assert_eq!(escrow_token, escrow_token_net);
+        let escrow_sol = get_lamports(&mut ctx, &escrow_sol_wallet).await - escrow_sol_rent;
+        assert_eq!(escrow_sol, escrow_sol_net);
+    }
+}
 
-    let escrow_wallet_lamports_before = get_lamports(&mut ctx, &escrow_wallet).await;
-    let protocol_fee_wallet_lamports_before = get_lamports(&mut ctx, &initializer.pubkey()).await;
-    let owner_lamports_before = get_lamports(&mut ctx, &owner.pubkey()).await;
+proptest! {
+    #![proptest_config(ProptestConfig { cases: 8, max_shrink_iters: 32, ..ProptestConfig::default() })]
 
-    // buyer buy passes
-    buy_passes_sol(
-        &mut ctx,
-        &buyer,
-        &owner.pubkey(),
-        &initializer.pubkey(),
-        &mint,
-        10,
-    )
-    .await;
+    #[test]
+    fn prop_escrow_round_trips(
+        price_offset in 0..=10u64,
+        trades in proptest::collection::vec((any::<bool>(), 1..=10u64), 1..20)
+    ) {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(run_escrow_round_trips(price_offset, trades));
+    }
+}
 
-    // buyer sell part of their passes
-    sell_passes_sol(
+// Buy passes and sell them back, the escrows keep at least what they held before
+async fn run_escrow_round_trips(price_offset: u64, trades: Vec<(bool, u64)>) {
+    let (mut ctx, initializer, users, mint) = setup_users(3).await;
+    init_passes(
         &mut ctx,
-        &buyer,
-        &owner.pubkey(),
-        &initializer.pubkey(),
+        &initializer,
         &mint,
-        5,
+        PROTOCOL_FEE_BPS,
+        OWNER_FEE_BPS,
     )
     .await;
+    let admin = initializer.pubkey();
+    let (escrow_token_wallet, _) = get_escrow_token_wallet_pda(&mint);
+    let (escrow_sol_wallet, _) = get_escrow_sol_wallet_pda();
 
-    // check total owner's pass supply
-    let (passes_supply_pda, _) = get_passes_supply_pda(&owner.pubkey());
-    let passes_supply: PassesSupply = get_account(&mut ctx, passes_supply_pda).await;
-    assert_eq!(passes_supply.amount, 6);
-
-    // check byuer's pass balance of owner's pass
-    let (passes_balance_pda, _) = get_passes_balance_pda(&owner.pubkey(), &buyer.pubkey());
-    let passes_balance: PassesBalance = get_account(&mut ctx, passes_balance_pda).await;
-    assert_eq!(passes_balance.amount, 5);
+    // a token market and a SOL market
+    for owner in &users[..2] {
+        let params = IssuanceParams {
+            price_offset,
+            ..Default::default()
+        };
+        let res = try_issue_passes_with_params(&mut ctx, owner, &mint, 1, params).await;
+        assert_matches!(res, Ok(()));
+    }
 
-    assert_eq!(
-        get_lamports(&mut ctx, &escrow_wallet).await,
-        escrow_wallet_lamports_before + 240_625_000 - 206_250_000
-    );
+    let trader = &users[2];
+    let mut sent = HashSet::new();
+    for (sol, amount) in trades {
+        let market = usize::from(sol);
+        let owner = users[market].pubkey();
+        let escrow_token = get_token_account_balance(&mut ctx, escrow_token_wallet).await;
+        let escrow_sol = get_lamports(&mut ctx, &escrow_sol_wallet).await;
+
+        let buy = FuzzOp::Buy {
+            owner: market,
+            buyer: 2,
+            sol,
+            amount,
+        };
+        refresh_if_sent(&mut ctx, &mut sent, buy).await;
+        let res = if sol {
+            try_buy_passes_sol(&mut ctx, trader, &owner, &admin, &mint, amount).await
+        } else {
+            try_buy_passes(&mut ctx, trader, &owner, &admin, &mint, amount).await
+        };
+        assert_matches!(res, Ok(()));
 
-    assert_eq!(
-        get_lamports(&mut ctx, &initializer.pubkey()).await,
-        protocol_fee_wallet_lamports_before + 2_406_250 + 2_062_500
-    );
+        let sell = FuzzOp::Sell {
+            owner: market,
+            seller: 2,
+            sol,
+            amount,
+        };
+        refresh_if_sent(&mut ctx, &mut sent, sell).await;
+        let res = if sol {
+            try_sell_passes_sol(&mut ctx, trader, &owner, &admin, &mint, amount).await
+        } else {
+            try_sell_passes(&mut ctx, trader, &owner, &admin, &mint, amount).await
+        };
+        assert_matches!(res, Ok(()));
 
-    assert_eq!(
-        get_lamports(&mut ctx, &owner.pubkey()).await,
-        owner_lamports_before + 2_406_250 + 2_062_500
-    );
+        assert!(get_token_account_balance(&mut ctx, escrow_token_wallet).await >= escrow_token);
+        assert!(get_lamports(&mut ctx, &escrow_sol_wallet).await >= escrow_sol);
+    }
 }
 
 mod utils {
     use super::*;
 
     pub async fn setup() -> (ProgramTestContext, Keypair, Keypair, Keypair, Pubkey) {
-        let mut program_test = ProgramTest::new("passes", crate::id(), None);
+        let mut program_test = ProgramTest::default();
 
         let initializer = Keypair::new();
         create_and_fund_account(&mut program_test, &initializer.pubkey());
+        add_upgradeable_program(
+            &mut program_test,
+            "passes",
+            &crate::id(),
+            &initializer.pubkey(),
+        );
 
         let buyer = Keypair::new();
         create_and_fund_account(&mut program_test, &buyer.pubkey());
@@ -546,6 +4379,75 @@ mod utils {
         (ctx, initializer, buyer, owner, mints[TICKER].pubkey)
     }
 
+    // Setup with `count` users funded with plenty of SOL and payment tokens
+    pub async fn setup_users(count: usize) -> (ProgramTestContext, Keypair, Vec<Keypair>, Pubkey) {
+        let mut program_test = ProgramTest::default();
+
+        let initializer = Keypair::new();
+        create_and_fund_account(&mut program_test, &initializer.pubkey());
+        add_upgradeable_program(
+            &mut program_test,
+            "passes",
+            &crate::id(),
+            &initializer.pubkey(),
+        );
+
+        let users: Vec<Keypair> = (0..count).map(|_| Keypair::new()).collect();
+        for user in &users {
+            program_test.add_account(
+                user.pubkey(),
+                account::Account {
+                    lamports: 10_000 * ONE_SOL,
+                    ..account::Account::default()
+                },
+            );
+        }
+
+        let mints = init_mints(&mut program_test, &initializer.pubkey());
+        let mint = mints[TICKER].pubkey;
+
+        let mut ctx = program_test.start_with_context().await;
+        for user in &users {
+            init_and_fund_token_account(
+                &mut ctx,
+                &mint,
+                &user.pubkey(),
+                &initializer,
+                1_000_000 * ONE_USDC,
+            )
+            .await;
+        }
+
+        (ctx, initializer, users, mint)
+    }
+
+    // Deploy the program with the upgradeable loader so `init` can check the upgrade authority
+    pub async fn verify_holding(
+        ctx: &mut ProgramTestContext,
+        holder: &Keypair,
+        owner: &Pubkey,
+        holder_page: Option<u32>,
+        min_amount: u64,
+    ) -> std::result::Result<bool, BanksClientError> {
+        let args = instruction::VerifyHolding { min_amount };
+        let accounts = accounts::VerifyHolding {
+            config: get_config_pda().0,
+            passes_balance: gate::passes_balance_address(0, owner, &holder.pubkey()),
+            holder_page: holder_page.map(|page| gate::holder_page_address(0, owner, page)),
+            holder: holder.pubkey(),
+            passes_owner: *owner,
+        };
+        simulate_tx(ctx, accounts.to_account_metas(None), &args, holder).await
+    }
+
+    // PDA of `seed` in a marketplace, the marketplace id follows the seed prefix
+    pub fn get_marketplace_pda(marketplace_id: u64, seed: &[u8], keys: &[&Pubkey]) -> (Pubkey, u8) {
+        let marketplace_seed = state::marketplace_seed(marketplace_id);
+        let mut seeds = vec![seed, marketplace_seed.as_slice()];
+        seeds.extend(keys.iter().map(|key| key.as_ref()));
+        Pubkey::find_program_address(&seeds, &crate::id())
+    }
+
     pub fn get_config_pda() -> (Pubkey, u8) {
         Pubkey::find_program_address(&[b"config".as_slice()], &crate::id())
     }
@@ -570,6 +4472,113 @@ mod utils {
         )
     }
 
+    pub fn get_passes_metadata_pda(owner: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[PassesMetadata::SEED, owner.as_ref()], &crate::id())
+    }
+
+    // Build a Merkle tree over the wallets, returns the root and the proof of each wallet
+    pub fn merkle_tree(wallets: &[Pubkey]) -> ([u8; 32], Vec<Vec<[u8; 32]>>) {
+        let mut level: Vec<[u8; 32]> = wallets.iter().map(merkle::leaf).collect();
+        let mut proofs = vec![vec![]; wallets.len()];
+        let mut positions: Vec<usize> = (0..wallets.len()).collect();
+
+        while level.len() > 1 {
+            for (proof, position) in proofs.iter_mut().zip(positions.iter_mut()) {
+                // the last node of an odd level has no sibling and moves up as is
+                if let Some(sibling) = level.get(*position ^ 1) {
+                    proof.push(*sibling);
+                }
+                *position /= 2;
+            }
+            level = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => merkle::hash_pair(left, right),
+                    [node] => *node,
+                    _ => unreachable!(),
+                })
+                .collect();
+        }
+
+        (level[0], proofs)
+    }
+
+    pub fn get_owner_vesting_pda(owner: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[OwnerVesting::SEED, owner.as_ref()], &crate::id())
+    }
+
+    // The owner's vesting account if their passes vest
+    pub async fn find_owner_vesting(
+        ctx: &mut ProgramTestContext,
+        owner: &Pubkey,
+    ) -> Option<Pubkey> {
+        let (owner_vesting, _) = get_owner_vesting_pda(owner);
+        let account = ctx.banks_client.get_account(owner_vesting).await.unwrap();
+        account.map(|_| owner_vesting)
+    }
+
+    pub fn get_reward_pool_pda(owner: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[RewardPool::SEED, owner.as_ref()], &crate::id())
+    }
+
+    // The owner's reward pool if their holders share rewards
+    pub async fn find_reward_pool(ctx: &mut ProgramTestContext, owner: &Pubkey) -> Option<Pubkey> {
+        let (reward_pool, _) = get_reward_pool_pda(owner);
+        let account = ctx.banks_client.get_account(reward_pool).await.unwrap();
+        account.map(|_| reward_pool)
+    }
+
+    // The holder's balance of the owner's passes, 0 without a balance account
+    pub async fn find_passes_balance(
+        ctx: &mut ProgramTestContext,
+        owner: &Pubkey,
+        holder: &Pubkey,
+    ) -> u64 {
+        let (passes_balance, _) = get_passes_balance_pda(owner, holder);
+        match ctx.banks_client.get_account(passes_balance).await.unwrap() {
+            Some(account) => {
+                PassesBalance::try_deserialize(&mut account.data.as_slice())
+                    .unwrap()
+                    .amount
+            }
+            None => 0,
+        }
+    }
+
+    pub fn get_reserve_token_wallet_pda(owner: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[PassesSupply::RESERVE_TOKEN_SEED, owner.as_ref()],
+            &crate::id(),
+        )
+    }
+
+    pub fn get_reserve_sol_wallet_pda(owner: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[PassesSupply::RESERVE_SOL_SEED, owner.as_ref()],
+            &crate::id(),
+        )
+    }
+
+    // The owner's reserve wallet if their market's reserve is isolated
+    pub async fn find_reserve_wallet(
+        ctx: &mut ProgramTestContext,
+        reserve_wallet: Pubkey,
+    ) -> Option<Pubkey> {
+        let account = ctx.banks_client.get_account(reserve_wallet).await.unwrap();
+        account.map(|_| reserve_wallet)
+    }
+
+    pub fn get_stake_pool_pda(owner: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[StakePool::SEED, owner.as_ref()], &crate::id())
+    }
+
+    pub fn get_passes_stake_pda(owner: &Pubkey, holder: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[PassesStake::SEED, owner.as_ref(), holder.as_ref()],
+            &crate::id(),
+        )
+    }
+
     pub fn create_and_fund_account(program_test: &mut ProgramTest, address: &Pubkey) {
         program_test.add_account(
             *address,
@@ -611,30 +4620,73 @@ mod utils {
         protocol_fee_bps: u64,
         owner_fee_bps: u64,
     ) {
+        let res = try_init_passes(ctx, initializer, mint, protocol_fee_bps, owner_fee_bps).await;
+        assert_matches!(res, Ok(()));
+    }
+
+    pub async fn try_init_passes(
+        ctx: &mut ProgramTestContext,
+        initializer: &Keypair,
+        mint: &Pubkey,
+        protocol_fee_bps: u64,
+        owner_fee_bps: u64,
+    ) -> std::result::Result<(), BanksClientError> {
+        try_init_marketplace(
+            ctx,
+            initializer,
+            initializer,
+            0,
+            mint,
+            protocol_fee_bps,
+            owner_fee_bps,
+        )
+        .await
+    }
+
+    pub async fn try_init_marketplace(
+        ctx: &mut ProgramTestContext,
+        admin: &Keypair,
+        authority: &Keypair,
+        marketplace_id: u64,
+        mint: &Pubkey,
+        protocol_fee_bps: u64,
+        owner_fee_bps: u64,
+    ) -> std::result::Result<(), BanksClientError> {
         // get pdas
-        let (config, _) = get_config_pda();
-        let (escrow_token_wallet, _) = get_escrow_token_wallet_pda(mint);
-        let (escrow_sol_wallet, _) = get_escrow_sol_wallet_pda();
+        let config = get_marketplace_pda(marketplace_id, b"config", &[]).0;
+        let escrow_token_wallet = get_marketplace_pda(marketplace_id, b"escrow", &[mint]).0;
+        let escrow_sol_wallet = get_marketplace_pda(marketplace_id, b"escrow", &[]).0;
         let protocol_fee_wallet =
-            anchor_spl::associated_token::get_associated_token_address(&initializer.pubkey(), mint);
+            anchor_spl::associated_token::get_associated_token_address(&admin.pubkey(), mint);
 
         let args = instruction::Init {
+            marketplace_id,
             protocol_fee_bps,
             owner_fee_bps,
         };
         let accounts = accounts::Init {
-            admin: initializer.pubkey(),
+            admin: admin.pubkey(),
+            authority: authority.pubkey(),
             config,
             escrow_token_wallet,
             escrow_sol_wallet,
             protocol_fee_wallet,
+            program: crate::id(),
+            program_data: program_data_address(&crate::id()),
             payment_mint: *mint,
             system_program: system_program::ID,
             token_program: anchor_spl::token::ID,
             associated_token_program: anchor_spl::associated_token::ID,
         };
-        let res = execute_tx(ctx, accounts.to_account_metas(None), &args, initializer).await;
-        assert_matches!(res, Ok(()));
+        let ix = solana_sdk::instruction::Instruction {
+            program_id: crate::id(),
+            accounts: accounts.to_account_metas(None),
+            data: args.data(),
+        };
+        let mut tx =
+            solana_sdk::transaction::Transaction::new_with_payer(&[ix], Some(&admin.pubkey()));
+        tx.sign(&[admin, authority], ctx.last_blockhash);
+        ctx.banks_client.process_transaction(tx).await
     }
 
     pub fn init_mints(
@@ -652,18 +4704,51 @@ mod utils {
     }
 
     pub async fn issue_passes(ctx: &mut ProgramTestContext, owner: &Keypair, mint: &Pubkey) {
+        let res = try_issue_passes(ctx, owner, mint).await;
+        assert_matches!(res, Ok(()));
+    }
+
+    pub async fn try_issue_passes(
+        ctx: &mut ProgramTestContext,
+        owner: &Keypair,
+        mint: &Pubkey,
+    ) -> std::result::Result<(), BanksClientError> {
+        try_issue_passes_with_params(ctx, owner, mint, 1, IssuanceParams::default()).await
+    }
+
+    pub async fn try_issue_passes_with_params(
+        ctx: &mut ProgramTestContext,
+        owner: &Keypair,
+        mint: &Pubkey,
+        amount: u64,
+        params: IssuanceParams,
+    ) -> std::result::Result<(), BanksClientError> {
         let passes_supply = get_passes_supply_pda(&owner.pubkey()).0;
         let passes_balance = get_passes_balance_pda(&owner.pubkey(), &owner.pubkey()).0;
+        let passes_metadata = get_passes_metadata_pda(&owner.pubkey()).0;
         let config = get_config_pda().0;
         let owner_fee_wallet =
             anchor_spl::associated_token::get_associated_token_address(&owner.pubkey(), mint);
 
-        let args = instruction::IssuePasses { amount: 1 };
+        let owner_vesting =
+            (params.vesting_duration > 0).then(|| get_owner_vesting_pda(&owner.pubkey()).0);
+        let reserve_token_wallet = params
+            .isolated_reserve
+            .then(|| get_reserve_token_wallet_pda(&owner.pubkey()).0);
+        let reserve_sol_wallet = params
+            .isolated_reserve
+            .then(|| get_reserve_sol_wallet_pda(&owner.pubkey()).0);
+
+        let args = instruction::IssuePasses { amount, params };
         let accounts = accounts::IssuePasses {
             owner: owner.pubkey(),
             passes_supply,
             passes_balance,
+            passes_metadata,
+            owner_vesting,
             config,
+            reserve_token_wallet,
+            reserve_sol_wallet,
             owner_fee_wallet,
             payment_mint: *mint,
             system_program: anchor_lang::system_program::ID,
@@ -671,8 +4756,7 @@ mod utils {
             associated_token_program: anchor_spl::associated_token::ID,
         };
 
-        let res = execute_tx(ctx, accounts.to_account_metas(None), &args, owner).await;
-        assert_matches!(res, Ok(()));
+        execute_tx(ctx, accounts.to_account_metas(None), &args, owner).await
     }
 
     pub async fn buy_passes(
@@ -683,9 +4767,42 @@ mod utils {
         mint: &Pubkey,
         amount: u64,
     ) {
-        let signer = buyer;
-        let buyer = &buyer.pubkey();
+        let res = try_buy_passes(ctx, buyer, owner, admin, mint, amount).await;
+        assert_matches!(res, Ok(()));
+    }
+
+    pub async fn try_buy_passes(
+        ctx: &mut ProgramTestContext,
+        buyer: &Keypair,
+        owner: &Pubkey,
+        admin: &Pubkey,
+        mint: &Pubkey,
+        amount: u64,
+    ) -> std::result::Result<(), BanksClientError> {
+        try_buy_passes_with_proof(ctx, buyer, owner, admin, mint, amount, vec![]).await
+    }
+
+    pub async fn try_buy_passes_with_proof(
+        ctx: &mut ProgramTestContext,
+        buyer: &Keypair,
+        owner: &Pubkey,
+        admin: &Pubkey,
+        mint: &Pubkey,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> std::result::Result<(), BanksClientError> {
+        let args = instruction::BuyPasses { amount, proof };
+        let accounts = buy_passes_accounts(ctx, &buyer.pubkey(), owner, admin, mint).await;
+        execute_tx(ctx, accounts.to_account_metas(None), &args, buyer).await
+    }
 
+    pub async fn buy_passes_accounts(
+        ctx: &mut ProgramTestContext,
+        buyer: &Pubkey,
+        owner: &Pubkey,
+        admin: &Pubkey,
+        mint: &Pubkey,
+    ) -> accounts::BuyPasses {
         // get pdas
         let (config, _) = get_config_pda();
         let (passes_supply, _) = get_passes_supply_pda(owner);
@@ -701,33 +4818,59 @@ mod utils {
         // eprintln!("fee_token = {:#?}", protocol_fee_wallet);
         // eprintln!("escrow_wallet = {:#?}", escrow_wallet);
 
-        let args = instruction::BuyPasses { amount };
-        let accounts = accounts::BuyPasses {
+        let reward_pool = find_reward_pool(ctx, owner).await;
+        let reserve_wallet = find_reserve_wallet(ctx, get_reserve_token_wallet_pda(owner).0).await;
+
+        accounts::BuyPasses {
             buyer: *buyer,
             passes_supply,
-            passes_balance,
+            passes_balance: Some(passes_balance),
+            holder_page: None,
+            reward_pool,
             config,
             owner_fee_wallet,
             escrow_wallet,
+            reserve_wallet,
             passes_owner: *owner,
             payment_mint: *mint,
             protocol_fee_wallet,
             buyer_wallet,
             system_program: system_program::ID,
             token_program: anchor_spl::token::ID,
-        };
-        let res = execute_tx(ctx, accounts.to_account_metas(None), &args, signer).await;
-        assert_matches!(res, Ok(()));
+        }
     }
 
     pub async fn buy_passes_sol(
         ctx: &mut ProgramTestContext,
         buyer: &Keypair,
         owner: &Pubkey,
-        admin: &Pubkey,
+        protocol_fee_wallet: &Pubkey,
         mint: &Pubkey,
         amount: u64,
     ) {
+        let res = try_buy_passes_sol(ctx, buyer, owner, protocol_fee_wallet, mint, amount).await;
+        assert_matches!(res, Ok(()));
+    }
+
+    pub async fn try_buy_passes_sol(
+        ctx: &mut ProgramTestContext,
+        buyer: &Keypair,
+        owner: &Pubkey,
+        protocol_fee_wallet: &Pubkey,
+        mint: &Pubkey,
+        amount: u64,
+    ) -> std::result::Result<(), BanksClientError> {
+        try_buy_passes_sol_with_proof(ctx, buyer, owner, protocol_fee_wallet, amount, vec![]).await
+    }
+
+    pub async fn try_buy_passes_sol_with_proof(
+        ctx: &mut ProgramTestContext,
+        buyer: &Keypair,
+        owner: &Pubkey,
+        protocol_fee_wallet: &Pubkey,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> std::result::Result<(), BanksClientError> {
         let signer = buyer;
         let buyer = &buyer.pubkey();
 
@@ -737,29 +4880,46 @@ mod utils {
         let (passes_balance, _) = get_passes_balance_pda(owner, buyer);
         let (escrow_wallet, _) = get_escrow_sol_wallet_pda();
 
-        let args = instruction::BuyPassesSol { amount };
+        let reward_pool = find_reward_pool(ctx, owner).await;
+        let reserve_wallet = find_reserve_wallet(ctx, get_reserve_sol_wallet_pda(owner).0).await;
+
+        let args = instruction::BuyPassesSol { amount, proof };
         let accounts = accounts::BuyPassesSol {
             buyer: *buyer,
             passes_supply,
-            passes_balance,
+            passes_balance: Some(passes_balance),
+            holder_page: None,
+            reward_pool,
             config,
             escrow_wallet,
-            protocol_fee_wallet: *admin,
+            reserve_wallet,
+            protocol_fee_wallet: *protocol_fee_wallet,
             passes_owner: *owner,
             system_program: system_program::ID,
         };
-        let res = execute_tx(ctx, accounts.to_account_metas(None), &args, signer).await;
+        execute_tx(ctx, accounts.to_account_metas(None), &args, signer).await
+    }
+
+    pub async fn sell_passes(
+        ctx: &mut ProgramTestContext,
+        seller: &Keypair,
+        owner: &Pubkey,
+        admin: &Pubkey,
+        mint: &Pubkey,
+        amount: u64,
+    ) {
+        let res = try_sell_passes(ctx, seller, owner, admin, mint, amount).await;
         assert_matches!(res, Ok(()));
     }
 
-    pub async fn sell_passes(
+    pub async fn try_sell_passes(
         ctx: &mut ProgramTestContext,
         seller: &Keypair,
         owner: &Pubkey,
         admin: &Pubkey,
         mint: &Pubkey,
         amount: u64,
-    ) {
+    ) -> std::result::Result<(), BanksClientError> {
         let signer = seller;
         let seller = &seller.pubkey();
 
@@ -779,14 +4939,21 @@ mod utils {
         // eprintln!("fee_token = {:#?}", protocol_fee_wallet);
         // eprintln!("escrow_wallet = {:#?}", escrow_wallet);
 
+        let owner_vesting = find_owner_vesting(ctx, owner).await;
+        let reward_pool = find_reward_pool(ctx, owner).await;
+        let reserve_wallet = find_reserve_wallet(ctx, get_reserve_token_wallet_pda(owner).0).await;
+
         let args = instruction::SellPasses { amount };
         let accounts = accounts::SellPasses {
             seller: *seller,
             passes_supply,
             passes_balance,
+            owner_vesting,
+            reward_pool,
             config,
             owner_fee_wallet,
             escrow_wallet,
+            reserve_wallet,
             passes_owner: *owner,
             payment_mint: *mint,
             protocol_fee_wallet,
@@ -795,18 +4962,29 @@ mod utils {
             token_program: anchor_spl::token::ID,
             associated_token_program: anchor_spl::associated_token::ID,
         };
-        let res = execute_tx(ctx, accounts.to_account_metas(None), &args, signer).await;
-        assert_matches!(res, Ok(()));
+        execute_tx(ctx, accounts.to_account_metas(None), &args, signer).await
     }
 
     pub async fn sell_passes_sol(
         ctx: &mut ProgramTestContext,
         seller: &Keypair,
         owner: &Pubkey,
-        admin: &Pubkey,
+        protocol_fee_wallet: &Pubkey,
         mint: &Pubkey,
         amount: u64,
     ) {
+        let res = try_sell_passes_sol(ctx, seller, owner, protocol_fee_wallet, mint, amount).await;
+        assert_matches!(res, Ok(()));
+    }
+
+    pub async fn try_sell_passes_sol(
+        ctx: &mut ProgramTestContext,
+        seller: &Keypair,
+        owner: &Pubkey,
+        protocol_fee_wallet: &Pubkey,
+        mint: &Pubkey,
+        amount: u64,
+    ) -> std::result::Result<(), BanksClientError> {
         let signer = seller;
         let seller = &seller.pubkey();
 
@@ -816,19 +4994,562 @@ mod utils {
         let (passes_balance, _) = get_passes_balance_pda(owner, seller);
         let (escrow_wallet, _) = get_escrow_sol_wallet_pda();
 
+        let owner_vesting = find_owner_vesting(ctx, owner).await;
+        let reward_pool = find_reward_pool(ctx, owner).await;
+        let reserve_wallet = find_reserve_wallet(ctx, get_reserve_sol_wallet_pda(owner).0).await;
+
         let args = instruction::SellPassesSol { amount };
         let accounts = accounts::SellPassesSol {
             seller: *seller,
             passes_supply,
             passes_balance,
+            owner_vesting,
+            reward_pool,
             config,
             escrow_wallet,
+            reserve_wallet,
             passes_owner: *owner,
-            protocol_fee_wallet: *admin,
+            protocol_fee_wallet: *protocol_fee_wallet,
             system_program: system_program::ID,
         };
-        let res = execute_tx(ctx, accounts.to_account_metas(None), &args, signer).await;
-        assert_matches!(res, Ok(()));
+        execute_tx(ctx, accounts.to_account_metas(None), &args, signer).await
+    }
+
+    pub async fn try_isolate_reserve(
+        ctx: &mut ProgramTestContext,
+        admin: &Keypair,
+        owner: &Pubkey,
+        mint: &Pubkey,
+        proposal: Option<Pubkey>,
+    ) -> std::result::Result<(), BanksClientError> {
+        let args = instruction::IsolateReserve {};
+        let accounts = accounts::IsolateReserve {
+            admin: admin.pubkey(),
+            config: get_config_pda().0,
+            proposal,
+            passes_supply: get_passes_supply_pda(owner).0,
+            reserve_token_wallet: get_reserve_token_wallet_pda(owner).0,
+            reserve_sol_wallet: get_reserve_sol_wallet_pda(owner).0,
+            escrow_token_wallet: get_escrow_token_wallet_pda(mint).0,
+            escrow_sol_wallet: get_escrow_sol_wallet_pda().0,
+            passes_owner: *owner,
+            payment_mint: *mint,
+            system_program: system_program::ID,
+            token_program: anchor_spl::token::ID,
+        };
+        execute_tx(ctx, accounts.to_account_metas(None), &args, admin).await
+    }
+
+    pub async fn set_pause<T: InstructionData>(
+        ctx: &mut ProgramTestContext,
+        authority: &Keypair,
+        args: &T,
+    ) -> std::result::Result<(), BanksClientError> {
+        let accounts = accounts::SetPause {
+            authority: authority.pubkey(),
+            config: get_config_pda().0,
+        };
+        execute_tx(ctx, accounts.to_account_metas(None), args, authority).await
+    }
+
+    pub async fn set_guardian(
+        ctx: &mut ProgramTestContext,
+        admin: &Keypair,
+        guardian: &Pubkey,
+    ) -> std::result::Result<(), BanksClientError> {
+        let args = instruction::SetGuardian {
+            guardian: *guardian,
+        };
+        let accounts = accounts::SetGuardian {
+            admin: admin.pubkey(),
+            config: get_config_pda().0,
+        };
+        execute_tx(ctx, accounts.to_account_metas(None), &args, admin).await
+    }
+
+    pub async fn set_passes_paused(
+        ctx: &mut ProgramTestContext,
+        owner: &Keypair,
+        paused: bool,
+    ) -> std::result::Result<(), BanksClientError> {
+        let args = instruction::SetPassesPaused { paused };
+        let accounts = accounts::SetPassesPaused {
+            config: get_config_pda().0,
+            owner: owner.pubkey(),
+            passes_supply: get_passes_supply_pda(&owner.pubkey()).0,
+        };
+        execute_tx(ctx, accounts.to_account_metas(None), &args, owner).await
+    }
+
+    pub async fn set_wallet_cap(
+        ctx: &mut ProgramTestContext,
+        owner: &Keypair,
+        max_wallet_amount: u64,
+        max_wallet_bps: u64,
+    ) -> std::result::Result<(), BanksClientError> {
+        let args = instruction::SetWalletCap {
+            max_wallet_amount,
+            max_wallet_bps,
+        };
+        let accounts = accounts::SetWalletCap {
+            config: get_config_pda().0,
+            owner: owner.pubkey(),
+            passes_supply: get_passes_supply_pda(&owner.pubkey()).0,
+        };
+        execute_tx(ctx, accounts.to_account_metas(None), &args, owner).await
+    }
+
+    pub async fn set_holding_period(
+        ctx: &mut ProgramTestContext,
+        owner: &Keypair,
+        min_hold_secs: i64,
+        early_exit_fee_bps: u64,
+    ) -> std::result::Result<(), BanksClientError> {
+        let args = instruction::SetHoldingPeriod {
+            min_hold_secs,
+            early_exit_fee_bps,
+        };
+        let accounts = accounts::SetHoldingPeriod {
+            config: get_config_pda().0,
+            owner: owner.pubkey(),
+            passes_supply: get_passes_supply_pda(&owner.pubkey()).0,
+        };
+        execute_tx(ctx, accounts.to_account_metas(None), &args, owner).await
+    }
+
+    pub async fn set_reward_share(
+        ctx: &mut ProgramTestContext,
+        owner: &Keypair,
+        holder_fee_bps: u64,
+    ) -> std::result::Result<(), BanksClientError> {
+        let args = instruction::SetRewardShare { holder_fee_bps };
+        let accounts = accounts::SetRewardShare {
+            config: get_config_pda().0,
+            owner: owner.pubkey(),
+            passes_supply: get_passes_supply_pda(&owner.pubkey()).0,
+            reward_pool: get_reward_pool_pda(&owner.pubkey()).0,
+            system_program: system_program::ID,
+        };
+        execute_tx(ctx, accounts.to_account_metas(None), &args, owner).await
+    }
+
+    pub async fn deposit_rewards(
+        ctx: &mut ProgramTestContext,
+        depositor: &Keypair,
+        owner: &Pubkey,
+        mint: &Pubkey,
+        amount: u64,
+    ) -> std::result::Result<(), BanksClientError> {
+        let args = instruction::DepositRewards { amount };
+        let accounts = accounts::DepositRewards {
+            depositor: depositor.pubkey(),
+            passes_supply: get_passes_supply_pda(owner).0,
+            reward_pool: get_reward_pool_pda(owner).0,
+            config: get_config_pda().0,
+            escrow_wallet: get_escrow_token_wallet_pda(mint).0,
+            passes_owner: *owner,
+            payment_mint: *mint,
+            depositor_wallet: anchor_spl::associated_token::get_associated_token_address(
+                &depositor.pubkey(),
+                mint,
+            ),
+            token_program: anchor_spl::token::ID,
+        };
+        execute_tx(ctx, accounts.to_account_metas(None), &args, depositor).await
+    }
+
+    pub async fn deposit_rewards_sol(
+        ctx: &mut ProgramTestContext,
+        depositor: &Keypair,
+        owner: &Pubkey,
+        amount: u64,
+    ) -> std::result::Result<(), BanksClientError> {
+        let args = instruction::DepositRewardsSol { amount };
+        let accounts = accounts::DepositRewardsSol {
+            config: get_config_pda().0,
+            depositor: depositor.pubkey(),
+            passes_supply: get_passes_supply_pda(owner).0,
+            reward_pool: get_reward_pool_pda(owner).0,
+            escrow_wallet: get_escrow_sol_wallet_pda().0,
+            passes_owner: *owner,
+            system_program: system_program::ID,
+        };
+        execute_tx(ctx, accounts.to_account_metas(None), &args, depositor).await
+    }
+
+    pub async fn claim_rewards(
+        ctx: &mut ProgramTestContext,
+        holder: &Keypair,
+        owner: &Pubkey,
+        mint: &Pubkey,
+    ) -> std::result::Result<(), BanksClientError> {
+        let args = instruction::ClaimRewards {};
+        let accounts = accounts::ClaimRewards {
+            holder: holder.pubkey(),
+            passes_balance: get_passes_balance_pda(owner, &holder.pubkey()).0,
+            reward_pool: get_reward_pool_pda(owner).0,
+            config: get_config_pda().0,
+            escrow_wallet: get_escrow_token_wallet_pda(mint).0,
+            passes_owner: *owner,
+            payment_mint: *mint,
+            holder_wallet: anchor_spl::associated_token::get_associated_token_address(
+                &holder.pubkey(),
+                mint,
+            ),
+            token_program: anchor_spl::token::ID,
+        };
+        execute_tx(ctx, accounts.to_account_metas(None), &args, holder).await
+    }
+
+    pub async fn claim_rewards_sol(
+        ctx: &mut ProgramTestContext,
+        holder: &Keypair,
+        owner: &Pubkey,
+    ) -> std::result::Result<(), BanksClientError> {
+        let args = instruction::ClaimRewardsSol {};
+        let accounts = accounts::ClaimRewardsSol {
+            config: get_config_pda().0,
+            holder: holder.pubkey(),
+            passes_balance: get_passes_balance_pda(owner, &holder.pubkey()).0,
+            reward_pool: get_reward_pool_pda(owner).0,
+            escrow_wallet: get_escrow_sol_wallet_pda().0,
+            passes_owner: *owner,
+        };
+        execute_tx(ctx, accounts.to_account_metas(None), &args, holder).await
+    }
+
+    pub async fn fund_stake_rewards(
+        ctx: &mut ProgramTestContext,
+        owner: &Keypair,
+        mint: &Pubkey,
+        amount: u64,
+        duration: i64,
+    ) -> std::result::Result<(), BanksClientError> {
+        let args = instruction::FundStakeRewards { amount, duration };
+        let accounts = accounts::FundStakeRewards {
+            owner: owner.pubkey(),
+            stake_pool: get_stake_pool_pda(&owner.pubkey()).0,
+            config: get_config_pda().0,
+            escrow_wallet: get_escrow_token_wallet_pda(mint).0,
+            payment_mint: *mint,
+            owner_wallet: anchor_spl::associated_token::get_associated_token_address(
+                &owner.pubkey(),
+                mint,
+            ),
+            system_program: system_program::ID,
+            token_program: anchor_spl::token::ID,
+        };
+        execute_tx(ctx, accounts.to_account_metas(None), &args, owner).await
+    }
+
+    pub async fn stake_passes(
+        ctx: &mut ProgramTestContext,
+        holder: &Keypair,
+        owner: &Pubkey,
+        amount: u64,
+    ) -> std::result::Result<(), BanksClientError> {
+        let args = instruction::StakePasses { amount };
+        let accounts = accounts::StakePasses {
+            config: get_config_pda().0,
+            holder: holder.pubkey(),
+            passes_balance: get_passes_balance_pda(owner, &holder.pubkey()).0,
+            stake_pool: get_stake_pool_pda(owner).0,
+            passes_stake: get_passes_stake_pda(owner, &holder.pubkey()).0,
+            passes_owner: *owner,
+            system_program: system_program::ID,
+        };
+        execute_tx(ctx, accounts.to_account_metas(None), &args, holder).await
+    }
+
+    pub async fn unstake_passes(
+        ctx: &mut ProgramTestContext,
+        holder: &Keypair,
+        owner: &Pubkey,
+        amount: u64,
+    ) -> std::result::Result<(), BanksClientError> {
+        let args = instruction::UnstakePasses { amount };
+        let accounts = accounts::UnstakePasses {
+            config: get_config_pda().0,
+            holder: holder.pubkey(),
+            passes_balance: get_passes_balance_pda(owner, &holder.pubkey()).0,
+            stake_pool: get_stake_pool_pda(owner).0,
+            passes_stake: get_passes_stake_pda(owner, &holder.pubkey()).0,
+            passes_owner: *owner,
+        };
+        execute_tx(ctx, accounts.to_account_metas(None), &args, holder).await
+    }
+
+    pub async fn claim_stake_rewards(
+        ctx: &mut ProgramTestContext,
+        holder: &Keypair,
+        owner: &Pubkey,
+        mint: &Pubkey,
+    ) -> std::result::Result<(), BanksClientError> {
+        let args = instruction::ClaimStakeRewards {};
+        let accounts = accounts::ClaimStakeRewards {
+            holder: holder.pubkey(),
+            stake_pool: get_stake_pool_pda(owner).0,
+            passes_stake: get_passes_stake_pda(owner, &holder.pubkey()).0,
+            config: get_config_pda().0,
+            escrow_wallet: get_escrow_token_wallet_pda(mint).0,
+            passes_owner: *owner,
+            payment_mint: *mint,
+            holder_wallet: anchor_spl::associated_token::get_associated_token_address(
+                &holder.pubkey(),
+                mint,
+            ),
+            token_program: anchor_spl::token::ID,
+        };
+        execute_tx(ctx, accounts.to_account_metas(None), &args, holder).await
+    }
+
+    pub fn airdrop_passes_ix(
+        owner: &Pubkey,
+        recipients: &[(Pubkey, u64)],
+    ) -> solana_sdk::instruction::Instruction {
+        let args = instruction::AirdropPasses {
+            amounts: recipients.iter().map(|(_, amount)| *amount).collect(),
+        };
+        let mut accounts = accounts::AirdropPasses {
+            config: get_config_pda().0,
+            owner: *owner,
+            passes_supply: get_passes_supply_pda(owner).0,
+            passes_balance: get_passes_balance_pda(owner, owner).0,
+            owner_vesting: None,
+            reward_pool: None,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None);
+        for (recipient, _) in recipients {
+            accounts.push(AccountMeta::new_readonly(*recipient, false));
+            accounts.push(AccountMeta::new(
+                get_passes_balance_pda(owner, recipient).0,
+                false,
+            ));
+        }
+
+        solana_sdk::instruction::Instruction {
+            program_id: crate::id(),
+            accounts,
+            data: args.data(),
+        }
+    }
+
+    pub async fn airdrop_passes(
+        ctx: &mut ProgramTestContext,
+        owner: &Keypair,
+        recipients: &[(Pubkey, u64)],
+    ) -> std::result::Result<(), BanksClientError> {
+        let ix = airdrop_passes_ix(&owner.pubkey(), recipients);
+        let mut tx =
+            solana_sdk::transaction::Transaction::new_with_payer(&[ix], Some(&owner.pubkey()));
+        tx.sign(&[owner], ctx.last_blockhash);
+        ctx.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn set_admins(
+        ctx: &mut ProgramTestContext,
+        admin: &Keypair,
+        admins: Vec<Pubkey>,
+        threshold: u8,
+    ) -> std::result::Result<(), BanksClientError> {
+        let args = instruction::SetAdmins { admins, threshold };
+        let accounts = accounts::SetAdmins {
+            admin: admin.pubkey(),
+            config: get_config_pda().0,
+        };
+        execute_tx(ctx, accounts.to_account_metas(None), &args, admin).await
+    }
+
+    pub fn get_proposal_pda(id: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[b"proposal".as_slice(), id.to_le_bytes().as_ref()],
+            &crate::id(),
+        )
+    }
+
+    pub async fn propose_config_change(
+        ctx: &mut ProgramTestContext,
+        proposer: &Keypair,
+        change: ConfigChange,
+    ) -> std::result::Result<Pubkey, BanksClientError> {
+        let (config, _) = get_config_pda();
+        let id = get_account::<Config>(ctx, config).await.proposal_count;
+        let (proposal, _) = get_proposal_pda(id);
+
+        let args = instruction::ProposeConfigChange { change };
+        let accounts = accounts::ProposeConfigChange {
+            proposer: proposer.pubkey(),
+            config,
+            proposal,
+            system_program: system_program::ID,
+        };
+        execute_tx(ctx, accounts.to_account_metas(None), &args, proposer).await?;
+
+        Ok(proposal)
+    }
+
+    pub async fn approve_proposal(
+        ctx: &mut ProgramTestContext,
+        approver: &Keypair,
+        proposal: &Pubkey,
+    ) -> std::result::Result<(), BanksClientError> {
+        let args = instruction::ApproveProposal {};
+        let accounts = accounts::ApproveProposal {
+            approver: approver.pubkey(),
+            config: get_config_pda().0,
+            proposal: *proposal,
+        };
+        execute_tx(ctx, accounts.to_account_metas(None), &args, approver).await
+    }
+
+    pub async fn execute_proposal(
+        ctx: &mut ProgramTestContext,
+        executor: &Keypair,
+        proposal: &Pubkey,
+        protocol_fee_token_wallet: Option<Pubkey>,
+    ) -> std::result::Result<(), BanksClientError> {
+        let args = instruction::ExecuteProposal {};
+        let accounts = accounts::ExecuteProposal {
+            executor: executor.pubkey(),
+            config: get_config_pda().0,
+            proposal: *proposal,
+            protocol_fee_token_wallet,
+        };
+        execute_tx(ctx, accounts.to_account_metas(None), &args, executor).await
+    }
+
+    // Store an account in the unversioned layout, sized as the old program allocated it
+    pub fn set_v0_account<T: AnchorSerialize>(
+        ctx: &mut ProgramTestContext,
+        address: &Pubkey,
+        discriminator: [u8; 8],
+        state: &T,
+        len: usize,
+    ) {
+        let mut data = discriminator.to_vec();
+        data.extend(state.try_to_vec().unwrap());
+        data.resize(len, 0);
+
+        let mut account = account::AccountSharedData::new(
+            solana_sdk::rent::Rent::default().minimum_balance(len),
+            len,
+            &crate::id(),
+        );
+        account.set_data_from_slice(&data);
+        ctx.set_account(address, &account);
+    }
+
+    pub fn get_holder_page_pda(owner: &Pubkey, page: u32) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                b"holders".as_slice(),
+                owner.as_ref(),
+                page.to_le_bytes().as_ref(),
+            ],
+            &crate::id(),
+        )
+    }
+
+    pub async fn get_holder_page(ctx: &mut ProgramTestContext, key: Pubkey) -> HolderPage {
+        let account = ctx.banks_client.get_account(key).await.unwrap().unwrap();
+        assert_eq!(account.data[..8], HolderPage::DISCRIMINATOR);
+
+        bytemuck::pod_read_unaligned(&account.data[8..])
+    }
+
+    pub async fn create_holder_page(
+        ctx: &mut ProgramTestContext,
+        payer: &Keypair,
+        owner: &Pubkey,
+        page: u32,
+    ) -> std::result::Result<(), BanksClientError> {
+        let args = instruction::CreateHolderPage { page };
+        let accounts = accounts::CreateHolderPage {
+            config: get_config_pda().0,
+            payer: payer.pubkey(),
+            passes_supply: get_passes_supply_pda(owner).0,
+            holder_page: get_holder_page_pda(owner, page).0,
+            passes_owner: *owner,
+            system_program: system_program::ID,
+        };
+        execute_tx(ctx, accounts.to_account_metas(None), &args, payer).await
+    }
+
+    pub async fn convert_to_holder_page(
+        ctx: &mut ProgramTestContext,
+        holder: &Keypair,
+        owner: &Pubkey,
+        page: u32,
+    ) -> std::result::Result<(), BanksClientError> {
+        let reward_pool = find_reward_pool(ctx, owner).await;
+        let args = instruction::ConvertToHolderPage {};
+        let accounts = accounts::ConvertToHolderPage {
+            config: get_config_pda().0,
+            holder: holder.pubkey(),
+            passes_balance: get_passes_balance_pda(owner, &holder.pubkey()).0,
+            passes_supply: get_passes_supply_pda(owner).0,
+            holder_page: get_holder_page_pda(owner, page).0,
+            reward_pool,
+            passes_owner: *owner,
+        };
+        execute_tx(ctx, accounts.to_account_metas(None), &args, holder).await
+    }
+
+    pub async fn convert_from_holder_page(
+        ctx: &mut ProgramTestContext,
+        holder: &Keypair,
+        owner: &Pubkey,
+        page: u32,
+    ) -> std::result::Result<(), BanksClientError> {
+        let reward_pool = find_reward_pool(ctx, owner).await;
+        let args = instruction::ConvertFromHolderPage {};
+        let accounts = accounts::ConvertFromHolderPage {
+            config: get_config_pda().0,
+            holder: holder.pubkey(),
+            passes_balance: get_passes_balance_pda(owner, &holder.pubkey()).0,
+            passes_supply: get_passes_supply_pda(owner).0,
+            holder_page: get_holder_page_pda(owner, page).0,
+            reward_pool,
+            passes_owner: *owner,
+            system_program: system_program::ID,
+        };
+        execute_tx(ctx, accounts.to_account_metas(None), &args, holder).await
+    }
+
+    pub async fn try_update_passes_metadata(
+        ctx: &mut ProgramTestContext,
+        owner: &Keypair,
+        name: &str,
+        description_uri: &str,
+        image_uri: &str,
+        social_handle: &str,
+    ) -> std::result::Result<Vec<PassesMetadataUpdated>, BanksClientError> {
+        let args = instruction::UpdatePassesMetadata {
+            name: name.to_string(),
+            description_uri: description_uri.to_string(),
+            image_uri: image_uri.to_string(),
+            social_handle: social_handle.to_string(),
+        };
+        let accounts = accounts::UpdatePassesMetadata {
+            config: get_config_pda().0,
+            owner: owner.pubkey(),
+            passes_supply: get_passes_supply_pda(&owner.pubkey()).0,
+            passes_metadata: get_passes_metadata_pda(&owner.pubkey()).0,
+            system_program: anchor_lang::system_program::ID,
+        };
+
+        execute_tx_with_events(ctx, accounts.to_account_metas(None), &args, owner).await
+    }
+
+    pub fn assert_passes_error(res: std::result::Result<(), BanksClientError>, error: PassesError) {
+        assert_matches!(
+            res,
+            Err(BanksClientError::TransactionError(TransactionError::InstructionError(
+                _,
+                InstructionError::Custom(code)
+            ))) if code == u32::from(error)
+        );
     }
 
     pub async fn get_token_account(
@@ -857,6 +5578,17 @@ mod utils {
         T::try_deserialize(&mut account.data.as_slice()).unwrap()
     }
 
+    pub async fn get_unix_timestamp(ctx: &mut ProgramTestContext) -> i64 {
+        let clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+        clock.unix_timestamp
+    }
+
+    pub async fn set_unix_timestamp(ctx: &mut ProgramTestContext, unix_timestamp: i64) {
+        let mut clock: Clock = ctx.banks_client.get_sysvar().await.unwrap();
+        clock.unix_timestamp = unix_timestamp;
+        ctx.set_sysvar(&clock);
+    }
+
     pub async fn get_lamports(ctx: &mut ProgramTestContext, key: &Pubkey) -> u64 {
         let banks_client = &mut ctx.banks_client;
 
@@ -887,6 +5619,43 @@ mod utils {
         banks_client.process_transaction(tx).await
     }
 
+    pub async fn execute_tx_with_events<T: InstructionData, E: anchor_lang::Event>(
+        ctx: &mut ProgramTestContext,
+        accounts_meta: Vec<AccountMeta>,
+        args: &T,
+        payer: &Keypair,
+    ) -> std::result::Result<Vec<E>, BanksClientError> {
+        let ix = solana_sdk::instruction::Instruction {
+            program_id: crate::id(),
+            accounts: accounts_meta,
+            data: args.data(),
+        };
+
+        let mut tx =
+            solana_sdk::transaction::Transaction::new_with_payer(&[ix], Some(&payer.pubkey()));
+        tx.sign(&[payer], ctx.last_blockhash);
+
+        let result = ctx
+            .banks_client
+            .process_transaction_with_metadata(tx)
+            .await?;
+        result.result.map_err(BanksClientError::TransactionError)?;
+
+        // Events are logged as base64 encoded "Program data:" lines
+        let events = result
+            .metadata
+            .unwrap()
+            .log_messages
+            .iter()
+            .filter_map(|log| log.strip_prefix("Program data: "))
+            .filter_map(|data| base64::engine::general_purpose::STANDARD.decode(data).ok())
+            .filter(|data| data.starts_with(&E::DISCRIMINATOR))
+            .map(|data| E::try_from_slice(&data[8..]).unwrap())
+            .collect();
+
+        Ok(events)
+    }
+
     pub async fn simulate_tx<T: InstructionData, U: BorshDeserialize>(
         ctx: &mut ProgramTestContext,
         accounts_meta: Vec<AccountMeta>,
@@ -918,7 +5687,8 @@ mod utils {
             return_data.push(0u8);
         }
 
-        Ok(U::try_from_slice(return_data.as_slice()).unwrap())
+        // Padding past the returned struct is left unread
+        Ok(U::deserialize(&mut return_data.as_slice()).unwrap())
     }
 }
 
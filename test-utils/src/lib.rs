@@ -0,0 +1,55 @@
+//! Test support shared by the crates of the workspace
+
+use solana_program_test::{find_file, read_file, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    pubkey::Pubkey,
+    rent::Rent,
+};
+
+/// Deploy `<program_name>.so` at `program_id` with the upgradeable loader, so instructions
+/// checking the upgrade authority see `upgrade_authority` in the program data account
+pub fn add_upgradeable_program(
+    program_test: &mut ProgramTest,
+    program_name: &str,
+    program_id: &Pubkey,
+    upgrade_authority: &Pubkey,
+) {
+    let file_name = format!("{program_name}.so");
+    let program_file = find_file(&file_name)
+        .unwrap_or_else(|| panic!("{file_name} not found, build it with cargo test-sbf"));
+    let elf = read_file(program_file);
+    let program_data = program_data_address(program_id);
+    let rent = Rent::default();
+
+    let metadata_len = UpgradeableLoaderState::size_of_programdata_metadata();
+    let mut program_data_account = Account::new_data_with_space(
+        rent.minimum_balance(metadata_len + elf.len()),
+        &UpgradeableLoaderState::ProgramData {
+            slot: 0,
+            upgrade_authority_address: Some(*upgrade_authority),
+        },
+        metadata_len + elf.len(),
+        &bpf_loader_upgradeable::id(),
+    )
+    .unwrap();
+    program_data_account.data[metadata_len..].copy_from_slice(&elf);
+    program_test.add_account(program_data, program_data_account);
+
+    let mut program_account = Account::new_data(
+        rent.minimum_balance(UpgradeableLoaderState::size_of_program()),
+        &UpgradeableLoaderState::Program {
+            programdata_address: program_data,
+        },
+        &bpf_loader_upgradeable::id(),
+    )
+    .unwrap();
+    program_account.executable = true;
+    program_test.add_account(*program_id, program_account);
+}
+
+/// Program data account of an upgradeable program
+pub fn program_data_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id()).0
+}